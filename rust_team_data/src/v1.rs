@@ -19,11 +19,40 @@ pub struct Team {
     pub name: String,
     pub kind: TeamKind,
     pub subteam_of: Option<String>,
+    /// Whether the team is at the top of the hierarchy.
+    #[serde(default)]
+    pub top_level: bool,
+    /// Whether the team is private, in which case it must not be shown on the website.
+    #[serde(default)]
+    pub private: bool,
     pub members: Vec<TeamMember>,
     pub alumni: Vec<TeamMember>,
     pub github: Option<TeamGitHub>,
     pub website_data: Option<TeamWebsite>,
     pub discord: Vec<TeamDiscord>,
+    pub election: Option<Election>,
+    /// When the team was founded, formatted as `YYYY-MM-DD`.
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub content_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Election {
+    pub term_months: u32,
+    /// The date of the next election, formatted as `YYYY-MM-DD`.
+    pub next_election: String,
+    pub electorate: Electorate,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Electorate {
+    Members,
+    MembersAndAlumni,
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +61,9 @@ pub struct TeamMember {
     pub github: String,
     pub github_id: usize,
     pub is_lead: bool,
+    /// The direct subteams the member is a lead of.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leads_subteams: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,7 +87,31 @@ pub struct TeamWebsite {
     pub repo: Option<String>,
     pub discord: Option<DiscordInvite>,
     pub zulip_stream: Option<String>,
+    /// The ID of the team's Zulip stream. It's set when the team refers to the stream by ID, in
+    /// which case `zulip_stream` is the current name of that stream, or when its name could be
+    /// resolved with the Zulip API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zulip_stream_id: Option<u64>,
+    /// The handle of the team's Matrix room, like `#wg-foo:matrix.org`.
+    #[serde(default)]
+    pub matrix_room: Option<String>,
     pub weight: i64,
+    pub membership: Option<TeamMembership>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamMembership {
+    pub open: bool,
+    /// Markdown explaining how to join the team.
+    pub how_to_join: String,
+    pub contact: Option<MembershipContact>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MembershipContact {
+    Zulip,
+    Email,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,17 +154,31 @@ pub struct Lists {
 pub struct ZulipGroup {
     pub name: String,
     pub members: Vec<ZulipGroupMember>,
+    /// GitHub usernames of the members who are people of the team repo, by Zulip ID.
+    #[serde(default)]
+    pub github_usernames: IndexMap<usize, String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ZulipGroupMember {
-    // TODO(rylev): this variant can be removed once
-    // it is verified that noone is relying on it
+    /// Only emitted by older versions of the API, and still accepted when parsing their output.
+    #[deprecated(note = "the API only emits `Id`, and this variant will be removed on 2027-04-01")]
+    #[serde(skip_serializing)]
     Email(String),
     Id(usize),
 }
 
+impl ZulipGroupMember {
+    pub fn zulip_id(&self) -> Option<usize> {
+        #[allow(deprecated)]
+        match self {
+            ZulipGroupMember::Email(_) => None,
+            ZulipGroupMember::Id(zulip_id) => Some(*zulip_id),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZulipGroups {
     pub groups: IndexMap<String, ZulipGroup>,
@@ -148,6 +218,39 @@ pub struct Repo {
     pub teams: Vec<RepoTeam>,
     pub members: Vec<RepoMember>,
     pub branch_protections: Vec<BranchProtection>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookSpec>,
+    #[serde(default)]
+    pub content_hash: String,
+    /// How the PRs are merged, if the repo declares it. Repos without it are merged by bors if
+    /// it's in `bots`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_merge: Option<AutoMerge>,
+    /// Teams with a custom repository role, which are not included in `teams`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_role_teams: Vec<CustomRoleAccess>,
+    /// People with a custom repository role, who are not included in `members`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_role_members: Vec<CustomRoleAccess>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoMerge {
+    Bors,
+    /// GitHub merge queues, with the checks that must pass before merging.
+    MergeQueue {
+        required_checks: Vec<String>,
+    },
+    None,
+}
+
+/// A webhook a bot needs on a repo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSpec {
+    pub bot: Bot,
+    pub url: Option<String>,
+    pub events: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -181,11 +284,27 @@ pub enum RepoPermission {
     Triage,
 }
 
+/// Access granted through a custom repository role of the organization. It's published apart
+/// from `RepoPermission`, so consumers only knowing the built-in roles can still parse the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRoleAccess {
+    pub name: String,
+    pub role: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BranchProtection {
     pub pattern: String,
     pub ci_checks: Vec<String>,
     pub dismiss_stale_review: bool,
+    /// Whether the protection is synchronized: unmanaged ones are only documented, and must not
+    /// be changed by the sync.
+    #[serde(default = "default_true")]
+    pub managed: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -193,6 +312,13 @@ pub struct Person {
     pub name: String,
     pub email: Option<String>,
     pub github_id: usize,
+    #[serde(default)]
+    pub content_hash: String,
+    /// Roles of the person independent of team membership, like `infra-oncall`.
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub pronouns: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -200,3 +326,153 @@ pub struct People {
     /// GitHub name as key.
     pub people: IndexMap<String, Person>,
 }
+
+/// The permissions each person has, granted directly or through the teams they're a member or lead
+/// of, so that consumers don't have to resolve them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectivePermissions {
+    /// GitHub name as key, with the sorted permission names as value.
+    pub people: IndexMap<String, Vec<String>>,
+}
+
+/// Everything a single person is involved in, served at `v1/people/<github>.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonDetails {
+    pub name: String,
+    pub github: String,
+    pub github_id: usize,
+    pub email: Option<String>,
+    /// Active teams the person is a member of, including through included teams.
+    pub teams: Vec<PersonTeam>,
+    /// Permissions granted directly or through team membership.
+    pub permissions: Vec<String>,
+    /// Repositories the person has individual access to.
+    pub repos: Vec<PersonRepo>,
+    /// Repositories the person has individual access to through a custom repository role.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_role_repos: Vec<PersonCustomRoleRepo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonTeam {
+    pub name: String,
+    pub is_lead: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonRepo {
+    pub org: String,
+    pub name: String,
+    pub permission: RepoPermission,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonCustomRoleRepo {
+    pub org: String,
+    pub name: String,
+    pub role: String,
+}
+
+/// SHA-256 hashes of the canonicalized source of every entity, used for cache invalidation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hashes {
+    /// Team name as key.
+    pub teams: IndexMap<String, String>,
+    /// `org/name` as key.
+    pub repos: IndexMap<String, String>,
+    /// GitHub name as key.
+    pub people: IndexMap<String, String>,
+}
+
+/// The whole resolved dataset in a single document, served at `v1/all.json`.
+///
+/// It's generated from the same data as the other documents, so it always matches them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct All {
+    /// Team name as key.
+    pub teams: IndexMap<String, Team>,
+    /// GitHub organization as key.
+    pub repos: IndexMap<String, Vec<Repo>>,
+    /// List address as key.
+    pub lists: IndexMap<String, List>,
+    /// Zulip group name as key.
+    pub zulip_groups: IndexMap<String, ZulipGroup>,
+    /// Permission name as key.
+    pub permissions: IndexMap<String, Permission>,
+    /// GitHub name as key.
+    pub people: IndexMap<String, Person>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_custom_roles() {
+        let json = r#"{
+            "org": "rust-lang",
+            "name": "foo",
+            "description": "",
+            "bots": [],
+            "teams": [{"name": "bar", "permission": "write"}],
+            "members": [],
+            "branch_protections": [],
+            "webhooks": [],
+            "content_hash": ""
+        }"#;
+        let mut repo: Repo = serde_json::from_str(json).unwrap();
+        assert!(repo.custom_role_teams.is_empty());
+        assert!(repo.custom_role_members.is_empty());
+        assert!(!serde_json::to_string(&repo)
+            .unwrap()
+            .contains("custom_role"));
+
+        repo.custom_role_members.push(CustomRoleAccess {
+            name: "baz".into(),
+            role: "triage-plus-labels".into(),
+        });
+        let serialized = serde_json::to_string(&repo).unwrap();
+        assert!(serialized
+            .contains(r#""custom_role_members":[{"name":"baz","role":"triage-plus-labels"}]"#));
+        let repo: Repo = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(repo.custom_role_members[0].role, "triage-plus-labels");
+    }
+
+    #[test]
+    fn test_zulip_group_json() {
+        let json =
+            r#"{"name":"T-foo","members":[{"id":1},{"id":2}],"github_usernames":{"1":"foo"}}"#;
+        let group: ZulipGroup = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            group.members,
+            [ZulipGroupMember::Id(1), ZulipGroupMember::Id(2)]
+        );
+        assert_eq!(group.github_usernames[&1], "foo");
+        assert_eq!(serde_json::to_string(&group).unwrap(), json);
+
+        // The representation of the previous versions of the API.
+        let json = r#"{"name":"T-foo","members":[{"email":"foo@example.com"},{"id":2}]}"#;
+        let group: ZulipGroup = serde_json::from_str(json).unwrap();
+        assert_eq!(group.members[0].zulip_id(), None);
+        assert_eq!(group.members[1].zulip_id(), Some(2));
+        assert!(group.github_usernames.is_empty());
+    }
+
+    #[test]
+    fn test_repo_without_auto_merge() {
+        let json = r#"{"org":"org","name":"repo","description":"","bots":["bors"],"teams":[],"members":[],"branch_protections":[],"content_hash":"abc"}"#;
+        let repo: Repo = serde_json::from_str(json).unwrap();
+        assert_eq!(repo.auto_merge, None);
+        assert!(repo.webhooks.is_empty());
+        assert_eq!(serde_json::to_string(&repo).unwrap(), json);
+    }
+
+    #[test]
+    fn test_person_without_optional_fields() {
+        let person: Person =
+            serde_json::from_str(r#"{"name":"Foo","email":null,"github_id":1}"#).unwrap();
+        assert_eq!(person.content_hash, "");
+        assert!(person.roles.is_empty());
+        assert_eq!(person.pronouns, None);
+    }
+}
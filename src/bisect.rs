@@ -0,0 +1,279 @@
+//! Find the first commit where the data started failing a check.
+//!
+//! Each tested commit is extracted with `git archive` to a temporary directory, loaded with the
+//! current schema and validated with only the given check. Commits whose data can't be loaded
+//! anymore are skipped, like `git bisect skip` does.
+
+use crate::data::Data;
+use crate::validate::{check_names, run_checks, CheckOptions};
+use failure::{bail, Error, ResultExt};
+use log::{info, warn};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The result of checking the data at a single commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    Passes,
+    /// The check fails, with these errors.
+    Fails(Vec<String>),
+    /// The data couldn't be loaded with the current schema.
+    Unknown(String),
+}
+
+/// The outcome of a bisection.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Bisection {
+    /// The first commit failing the check.
+    pub(crate) first_bad: String,
+    /// The errors of the check at `first_bad`.
+    pub(crate) errors: Vec<String>,
+    /// The commits right before `first_bad` that couldn't be checked, any of which might be the
+    /// actual first failing commit.
+    pub(crate) unknown: Vec<String>,
+}
+
+/// Find the first commit between `good` (passing the check) and `bad` (failing it) in the
+/// history of the data located at `root`.
+pub(crate) fn bisect_check(
+    root: &Path,
+    check: &str,
+    good: &str,
+    bad: &str,
+) -> Result<Bisection, Error> {
+    if !check_names().any(|name| name == check) {
+        bail!("unknown check: {}", check);
+    }
+    let prefix = git(root, &["rev-parse", "--show-prefix"])?;
+    let good = git(
+        root,
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", good)],
+    )?;
+    let bad = git(
+        root,
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", bad)],
+    )?;
+    let mut commits = vec![good.clone()];
+    commits.extend(
+        git(
+            root,
+            &[
+                "rev-list",
+                "--reverse",
+                "--first-parent",
+                "--ancestry-path",
+                &format!("{}..{}", good, bad),
+            ],
+        )?
+        .lines()
+        .map(String::from),
+    );
+    if commits.last() != Some(&bad) {
+        bail!("{} is not an ancestor of {}", good, bad);
+    }
+
+    bisect(&commits, |commit| {
+        info!("checking commit {}", commit);
+        check_commit(root, &prefix, commit, check)
+    })
+}
+
+/// Binary search the first failing commit, given the commits from the good one to the bad one.
+fn bisect(
+    commits: &[String],
+    mut check: impl FnMut(&str) -> Result<Outcome, Error>,
+) -> Result<Bisection, Error> {
+    let (first, last) = match commits {
+        [first, .., last] => (first, last),
+        _ => bail!("the good and the bad commits must be different"),
+    };
+    match check(first)? {
+        Outcome::Passes => {}
+        Outcome::Fails(_) => bail!("the check already fails at the good commit {}", first),
+        Outcome::Unknown(err) => bail!("the data can't be loaded at the good commit: {}", err),
+    }
+    let mut bad_errors = match check(last)? {
+        Outcome::Fails(errors) => errors,
+        Outcome::Passes => bail!("the check passes at the bad commit {}", last),
+        Outcome::Unknown(err) => bail!("the data can't be loaded at the bad commit: {}", err),
+    };
+
+    let mut good = 0;
+    let mut bad = commits.len() - 1;
+    let mut unknown = vec![false; commits.len()];
+    loop {
+        let candidates = (good + 1..bad).filter(|&i| !unknown[i]).collect::<Vec<_>>();
+        if candidates.is_empty() {
+            break;
+        }
+        let mid = candidates[candidates.len() / 2];
+        match check(&commits[mid])? {
+            Outcome::Passes => good = mid,
+            Outcome::Fails(errors) => {
+                bad = mid;
+                bad_errors = errors;
+            }
+            Outcome::Unknown(err) => {
+                warn!("skipping commit {}: {}", commits[mid], err);
+                unknown[mid] = true;
+            }
+        }
+    }
+
+    Ok(Bisection {
+        first_bad: commits[bad].clone(),
+        errors: bad_errors,
+        unknown: commits[good + 1..bad].to_vec(),
+    })
+}
+
+fn check_commit(root: &Path, prefix: &str, commit: &str, check: &str) -> Result<Outcome, Error> {
+    let dir = std::env::temp_dir().join(format!(
+        "rust-team-bisect-{}-{}",
+        std::process::id(),
+        commit
+    ));
+    extract(root, &format!("{}:{}", commit, prefix), &dir)?;
+    let outcome = match Data::load_from(&dir) {
+        Ok(data) => {
+            let report = run_checks(
+                &data,
+                &CheckOptions {
+                    only: &[check],
+                    ..CheckOptions::default()
+                },
+            )?;
+            if report.errors.is_empty() {
+                Outcome::Passes
+            } else {
+                Outcome::Fails(report.errors.into_iter().map(|e| e.message).collect())
+            }
+        }
+        Err(err) => Outcome::Unknown(err.to_string()),
+    };
+    std::fs::remove_dir_all(&dir)?;
+    Ok(outcome)
+}
+
+/// Extract the tree to the directory.
+fn extract(root: &Path, tree: &str, dest: &Path) -> Result<(), Error> {
+    std::fs::create_dir_all(dest)?;
+    let mut archive = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["archive", "--format=tar", tree])
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("failed to run `git archive`")?;
+    let status = Command::new("tar")
+        .arg("-x")
+        .arg("-C")
+        .arg(dest)
+        .stdin(archive.stdout.take().unwrap())
+        .status()
+        .context("failed to run `tar`")?;
+    if !archive.wait()?.success() || !status.success() {
+        bail!("failed to extract {}", tree);
+    }
+    Ok(())
+}
+
+/// Run git in the directory, returning its trimmed output.
+fn git(root: &Path, args: &[&str]) -> Result<String, Error> {
+    // An empty path passed to `-C` leaves the current directory unchanged.
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::{config, person, team};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_bisect_skips_unknown_commits() {
+        let commits = (0..6).map(|i| format!("c{}", i)).collect::<Vec<_>>();
+        let outcomes = vec![
+            ("c0", Outcome::Passes),
+            ("c1", Outcome::Passes),
+            ("c2", Outcome::Unknown("broken".into())),
+            ("c3", Outcome::Unknown("broken".into())),
+            ("c4", Outcome::Fails(vec!["first".into()])),
+            ("c5", Outcome::Fails(vec!["last".into()])),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+        let bisection = bisect(&commits, |commit| Ok(outcomes[commit].clone())).unwrap();
+        assert_eq!(
+            bisection,
+            Bisection {
+                first_bad: "c4".into(),
+                errors: vec!["first".into()],
+                unknown: vec!["c2".into(), "c3".into()],
+            }
+        );
+
+        assert!(bisect(&commits, |_| Ok(Outcome::Passes)).is_err());
+    }
+
+    #[test]
+    fn test_bisect_check() -> Result<(), Error> {
+        let dir =
+            std::env::temp_dir().join(format!("rust-team-bisect-repo-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("people"))?;
+        std::fs::create_dir_all(dir.join("teams").join("archive"))?;
+        std::fs::create_dir_all(dir.join("repos"))?;
+        // git doesn't track empty directories.
+        std::fs::write(dir.join("teams").join("archive").join(".gitkeep"), "")?;
+        std::fs::write(dir.join("repos").join(".gitkeep"), "")?;
+        let git = |args: &[&str]| {
+            git(
+                &dir,
+                &[
+                    &["-c", "user.name=test", "-c", "user.email=test@example.com"],
+                    args,
+                ]
+                .concat(),
+            )
+        };
+        let commit = |team: &str| -> Result<String, Error> {
+            std::fs::write(dir.join("teams").join("team.toml"), team)?;
+            git(&["add", "-A"])?;
+            git(&["commit", "-q", "-m", "update"])?;
+            git(&["rev-parse", "HEAD"])
+        };
+
+        git(&["init", "-q"])?;
+        std::fs::write(dir.join("config.toml"), config(""))?;
+        std::fs::write(dir.join("people").join("foo.toml"), person("foo", 1, ""))?;
+        let team = |members: &[&str]| team("team", &[], members, "");
+        let good = commit(&team(&["foo"]))?;
+        let first_bad = commit(&team(&["foo", "missing"]))?;
+        let bad = commit(&team(&["foo", "missing", "other"]))?;
+
+        let bisection = bisect_check(&dir, "validate_team_members", &good, &bad)?;
+        assert_eq!(bisection.first_bad, first_bad);
+        assert_eq!(
+            bisection.errors,
+            ["person `missing` is member of team `team` but doesn't exist"]
+        );
+        assert!(bisection.unknown.is_empty());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+//! On-disk cache of the API responses, shared between runs of the tool.
+//!
+//! The cache is opt-in (with `--cache`), and its entries are scoped to the credentials used to
+//! fetch them, so that a response only visible to one token is never served to another one.
+//! It's stored in `$TEAM_CACHE_DIR`, or by default in `$XDG_CACHE_HOME/team` (or
+//! `~/.cache/team`), with one JSON file per entry. `$TEAM_CACHE_MAX_AGE` (in seconds) overrides
+//! how long the entries with an expiration are trusted.
+//!
+//! It's only an optimization: entries that can't be read, are corrupt or are expired are
+//! ignored, and failing to write an entry only emits a warning.
+
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+static DIR_VAR: &str = "TEAM_CACHE_DIR";
+static MAX_AGE_VAR: &str = "TEAM_CACHE_MAX_AGE";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static REFRESH: AtomicBool = AtomicBool::new(false);
+
+/// Enable the on-disk cache for the rest of the process, as requested by `--cache`.
+pub(crate) fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Ignore the cached entries for the rest of the process, replacing them with fresh ones, as
+/// requested by `--refresh-cache`.
+pub(crate) fn refresh() {
+    REFRESH.store(true, Ordering::Relaxed);
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    /// When the entry was stored, in seconds since the Unix epoch.
+    stored_at: u64,
+    value: T,
+}
+
+#[derive(Clone)]
+pub(crate) struct DiskCache {
+    dir: PathBuf,
+    /// The credentials the entries are fetched with, if any, which are part of their keys.
+    credentials: Option<String>,
+    /// Overrides the maximum age of the entries with an expiration.
+    max_age: Option<Duration>,
+    /// Whether to ignore the existing entries.
+    refresh: bool,
+}
+
+impl DiskCache {
+    /// The cache in the default location for the entries fetched with `credentials`, or `None`
+    /// if it's not enabled or there is no location.
+    pub(crate) fn open(credentials: Option<&str>) -> Option<Self> {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return None;
+        }
+        let dir = match (
+            std::env::var_os(DIR_VAR),
+            std::env::var_os("XDG_CACHE_HOME"),
+        ) {
+            (Some(dir), _) if !dir.is_empty() => PathBuf::from(dir),
+            (_, Some(dir)) if !dir.is_empty() => PathBuf::from(dir).join("team"),
+            _ => PathBuf::from(std::env::var_os("HOME")?)
+                .join(".cache")
+                .join("team"),
+        };
+        let mut cache = DiskCache::new(dir);
+        cache.credentials = credentials.map(String::from);
+        cache.refresh = REFRESH.load(Ordering::Relaxed);
+        if let Ok(max_age) = std::env::var(MAX_AGE_VAR) {
+            match max_age.parse() {
+                Ok(secs) => cache.max_age = Some(Duration::from_secs(secs)),
+                Err(_) => warn!("ignoring invalid {}: {}", MAX_AGE_VAR, max_age),
+            }
+        }
+        Some(cache)
+    }
+
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        DiskCache {
+            dir,
+            credentials: None,
+            max_age: None,
+            refresh: false,
+        }
+    }
+
+    /// The value stored for the key in the namespace, if it's not older than `max_age`.
+    ///
+    /// `$TEAM_CACHE_MAX_AGE` takes precedence over `max_age`, unless the entry never expires.
+    pub(crate) fn get<T: DeserializeOwned>(
+        &self,
+        namespace: &str,
+        key: &str,
+        max_age: Option<Duration>,
+    ) -> Option<T> {
+        if self.refresh {
+            return None;
+        }
+        let content = std::fs::read(self.path(namespace, key)).ok()?;
+        let entry: Entry<T> = serde_json::from_slice(&content).ok()?;
+        if let Some(max_age) = max_age.map(|max_age| self.max_age.unwrap_or(max_age)) {
+            let stored_at = UNIX_EPOCH + Duration::from_secs(entry.stored_at);
+            let age = SystemTime::now().duration_since(stored_at).ok()?;
+            if age > max_age {
+                return None;
+            }
+        }
+        Some(entry.value)
+    }
+
+    /// Store the value for the key in the namespace, replacing the previous one.
+    pub(crate) fn put<T: Serialize>(&self, namespace: &str, key: &str, value: &T) {
+        let path = self.path(namespace, key);
+        if let Err(err) = write_entry(&path, value) {
+            warn!(
+                "failed to write the cache entry {}: {}",
+                path.display(),
+                err
+            );
+        }
+    }
+
+    fn path(&self, namespace: &str, key: &str) -> PathBuf {
+        let mut hasher = Sha256::new();
+        if let Some(credentials) = &self.credentials {
+            hasher.update(credentials.as_bytes());
+            hasher.update([0]);
+        }
+        hasher.update(key.as_bytes());
+        let key = hex::encode(hasher.finalize());
+        self.dir.join(namespace).join(format!("{}.json", key))
+    }
+}
+
+fn write_entry<T: Serialize>(path: &Path, value: &T) -> Result<(), failure::Error> {
+    let entry = Entry {
+        stored_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        value,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Write to a temporary file first, so that concurrent runs never read a partial entry.
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    std::fs::write(&tmp, serde_json::to_vec(&entry)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_cache() {
+        let dir = std::env::temp_dir().join(format!("rust-team-cache-{}", std::process::id()));
+        let cache = DiskCache::new(dir.clone());
+
+        assert_eq!(cache.get::<String>("ns", "missing", None), None);
+
+        cache.put("ns", "key", &"value".to_string());
+        assert_eq!(cache.get("ns", "key", None), Some("value".to_string()));
+        assert_eq!(
+            cache.get("ns", "key", Some(Duration::from_secs(60))),
+            Some("value".to_string())
+        );
+        assert_eq!(cache.get::<String>("other", "key", None), None);
+
+        // Expired entries are ignored.
+        let expired = Entry {
+            stored_at: 0,
+            value: "old",
+        };
+        std::fs::write(
+            cache.path("ns", "key"),
+            serde_json::to_vec(&expired).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(cache.get("ns", "key", None), Some("old".to_string()));
+        assert_eq!(
+            cache.get::<String>("ns", "key", Some(Duration::from_secs(60))),
+            None
+        );
+
+        // Corrupt entries are ignored.
+        std::fs::write(cache.path("ns", "key"), "{not json").unwrap();
+        assert_eq!(cache.get::<String>("ns", "key", None), None);
+        cache.put("ns", "key", &"new".to_string());
+        assert_eq!(cache.get("ns", "key", None), Some("new".to_string()));
+
+        // The configured maximum age overrides the one of the entries with an expiration.
+        let mut configured = DiskCache::new(dir.clone());
+        configured.max_age = Some(Duration::from_secs(0));
+        assert_eq!(
+            configured.get::<String>("ns", "key", Some(Duration::from_secs(60))),
+            None
+        );
+        assert_eq!(configured.get("ns", "key", None), Some("new".to_string()));
+
+        // Refreshing ignores the existing entries, but still replaces them.
+        let mut refreshing = DiskCache::new(dir.clone());
+        refreshing.refresh = true;
+        assert_eq!(refreshing.get::<String>("ns", "key", None), None);
+        refreshing.put("ns", "key", &"fresh".to_string());
+        assert_eq!(cache.get("ns", "key", None), Some("fresh".to_string()));
+
+        // Entries fetched with other credentials are not shared.
+        let mut authenticated = DiskCache::new(dir.clone());
+        authenticated.credentials = Some("token".into());
+        assert_eq!(authenticated.get::<String>("ns", "key", None), None);
+        authenticated.put("ns", "key", &"private".to_string());
+        let mut other = DiskCache::new(dir.clone());
+        other.credentials = Some("other-token".into());
+        assert_eq!(other.get::<String>("ns", "key", None), None);
+        assert_eq!(cache.get("ns", "key", None), Some("fresh".to_string()));
+        assert_eq!(
+            authenticated.get("ns", "key", None),
+            Some("private".to_string())
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,124 @@
+//! Generation of a CODEOWNERS file mirroring the access configured for a repo.
+//!
+//! Every pattern is owned by GitHub teams (as `@org/team-slug`) and people (as `@username`). The
+//! whole repo (`*`) is owned by everyone with at least write access, and the `[codeowners]`
+//! table of the repo adds path-specific owners after it, so they take precedence.
+
+use crate::data::Data;
+use crate::schema::{github_slug, Repo, RepoPermission};
+use failure::{bail, Error};
+use std::fmt::Write;
+
+/// A line of the CODEOWNERS file.
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+pub(crate) struct Rule {
+    pub(crate) pattern: String,
+    pub(crate) owners: Vec<String>,
+}
+
+/// The rules of the CODEOWNERS file of the repo, named `org/name`.
+pub(crate) fn codeowners(data: &Data, repo: &str) -> Result<Vec<Rule>, Error> {
+    let Some((org, name)) = repo.split_once('/') else {
+        bail!("invalid repo `{}`: expected `org/name`", repo);
+    };
+    let Some(repo) = data.repo(org, name) else {
+        bail!("unknown repo: {}", repo);
+    };
+
+    let mut rules = Vec::new();
+    let mut teams = repo
+        .access
+        .teams
+        .iter()
+        .filter(|(_, permission)| can_own(permission))
+        .map(|(team, _)| team.as_str())
+        .collect::<Vec<_>>();
+    teams.sort_unstable();
+    let mut people = repo
+        .access
+        .individuals
+        .iter()
+        .filter(|(_, permission)| can_own(permission))
+        .map(|(person, _)| person.as_str())
+        .collect::<Vec<_>>();
+    people.sort_unstable();
+    if !teams.is_empty() || !people.is_empty() {
+        rules.push(Rule {
+            pattern: "*".into(),
+            owners: owners(repo, &teams, &people),
+        });
+    }
+
+    for (pattern, entry) in &repo.codeowners {
+        let teams = entry.teams.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+        let people = entry.people.iter().map(|p| p.as_str()).collect::<Vec<_>>();
+        rules.push(Rule {
+            pattern: pattern.clone(),
+            owners: owners(repo, &teams, &people),
+        });
+    }
+    Ok(rules)
+}
+
+/// Render the rules as a CODEOWNERS file.
+pub(crate) fn render(repo: &str, rules: &[Rule]) -> String {
+    let mut out = format!(
+        "# Generated from the access of {} in the team repo, don't edit it manually.\n",
+        repo
+    );
+    for rule in rules {
+        let _ = writeln!(out, "{} {}", rule.pattern, rule.owners.join(" "));
+    }
+    out
+}
+
+/// Whether the permission allows owning code: GitHub ignores owners without write access.
+pub(crate) fn can_own(permission: &RepoPermission) -> bool {
+    matches!(
+        permission,
+        RepoPermission::Write | RepoPermission::Maintain | RepoPermission::Admin
+    )
+}
+
+fn owners(repo: &Repo, teams: &[&str], people: &[&str]) -> Vec<String> {
+    teams
+        .iter()
+        .map(|team| format!("@{}/{}", repo.org, github_slug(team)))
+        .chain(people.iter().map(|person| format!("@{}", person)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::config;
+
+    #[test]
+    fn test_codeowners() {
+        let data = Data::from_toml(&config("allowed-github-orgs = ['org']\n"), &[], &[])
+            .with_repos(&["org = 'org'\n\
+                           name = 'repo'\n\
+                           description = ''\n\
+                           bots = []\n\
+                           [access.teams]\n\
+                           Infra = 'admin'\n\
+                           compiler = 'write'\n\
+                           triagers = 'triage'\n\
+                           [access.individuals]\n\
+                           octocat = 'maintain'\n\
+                           [codeowners]\n\
+                           '/src/' = { teams = ['compiler'] }\n\
+                           '/docs/' = { teams = ['compiler'], people = ['octocat'] }\n"]);
+
+        let rules = codeowners(&data, "org/repo").unwrap();
+        assert_eq!(
+            render("org/repo", &rules),
+            "# Generated from the access of org/repo in the team repo, don't edit it manually.\n\
+             * @org/infra @org/compiler @octocat\n\
+             /src/ @org/compiler\n\
+             /docs/ @org/compiler @octocat\n"
+        );
+        assert!(codeowners(&data, "org/missing").is_err());
+        assert!(codeowners(&data, "repo").is_err());
+    }
+}
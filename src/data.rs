@@ -1,92 +1,257 @@
-use crate::schema::{Config, List, Person, Repo, Team, ZulipGroup};
-use failure::{bail, Error, ResultExt};
+use crate::query::Query;
+use crate::schema::{
+    deprecated_fields, Config, Deprecation, List, Person, Repo, Team, ZulipGroup, DEPRECATIONS,
+};
+use failure::{bail, err_msg, Error, ResultExt};
+use indexmap::IndexMap;
+use rust_team_data::v1;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 #[derive(Debug)]
 pub(crate) struct Data {
     people: HashMap<String, Person>,
     teams: HashMap<String, Team>,
     archived_teams: Vec<Team>,
+    draft_teams: Vec<Team>,
     repos: HashMap<(String, String), Repo>,
+    archived_repos: Vec<Repo>,
     config: Config,
+    /// The deprecated fields used by the files, along with the path of each file.
+    deprecated_fields: Vec<(String, &'static Deprecation)>,
+    /// The ancestors of each team, computed by `team_ancestors` the first time it's called.
+    team_ancestors: OnceLock<HashMap<String, Result<Vec<String>, String>>>,
 }
 
 impl Data {
-    pub(crate) fn load() -> Result<Self, Error> {
+    /// Load the data from a checkout of the team repository located at `root`.
+    pub(crate) fn load_from(root: &Path) -> Result<Self, Error> {
+        Self::load_overlaid(root, None)
+    }
+
+    /// Load the data from `root`, with the files in `overlay` (if any) replacing or adding to the
+    /// ones at the same path in `root`. An empty file in the overlay deletes the file in `root`.
+    ///
+    /// This is experimental, and meant to stage migrations touching many files.
+    pub(crate) fn load_overlaid(root: &Path, overlay: Option<&Path>) -> Result<Self, Error> {
+        let layers = Layers { root, overlay };
+        let config = match layers.file(Path::new("config.toml"))? {
+            Some(path) => load_file(&path)?,
+            None => bail!("config.toml can't be deleted by the overlay"),
+        };
         let mut data = Data {
             people: HashMap::new(),
             teams: HashMap::new(),
             archived_teams: Vec::new(),
+            draft_teams: Vec::new(),
             repos: HashMap::new(),
-            config: load_file(Path::new("config.toml"))?,
+            archived_repos: Vec::new(),
+            config,
+            deprecated_fields: Vec::new(),
+            team_ancestors: OnceLock::new(),
         };
 
-        data.load_dir("repos", true, |this, org, repo: Repo| {
-            if &repo.org != org {
-                bail!(
-                    "repo '{}' is located in the '{}' org directory but its org is '{}'",
-                    repo.name,
-                    org,
-                    repo.org
-                )
-            }
+        data.load_dir(
+            &layers,
+            Path::new("repos"),
+            true,
+            |this, path, repo: Repo, content_hash| {
+                let repo = this.prepare_repo(path, repo, content_hash)?;
+                this.repos
+                    .insert((repo.org.clone(), repo.name.clone()), repo);
+                Ok(())
+            },
+        )?;
 
-            this.repos
-                .insert((repo.org.clone(), repo.name.clone()), repo);
-            Ok(())
-        })?;
+        let archived_repos = Path::new("repos").join("archive");
+        if layers.has_dir(&archived_repos) {
+            data.load_dir(
+                &layers,
+                &archived_repos,
+                true,
+                |this, path, repo: Repo, content_hash| {
+                    let repo = this.prepare_repo(path, repo, content_hash)?;
+                    if this
+                        .repos
+                        .contains_key(&(repo.org.clone(), repo.name.clone()))
+                    {
+                        bail!(
+                            "repo '{}/{}' is both active and archived",
+                            repo.org,
+                            repo.name
+                        );
+                    }
+                    this.archived_repos.push(repo);
+                    Ok(())
+                },
+            )?;
+        }
 
-        data.load_dir("people", false, |this, _dir, person: Person| {
-            person.validate()?;
-            this.people.insert(person.github().to_string(), person);
-            Ok(())
-        })?;
+        data.load_dir(
+            &layers,
+            Path::new("people"),
+            false,
+            |this, path, mut person: Person, content_hash| {
+                person.validate()?;
+                person.set_content_hash(content_hash);
+                person.set_file_name(path.file_stem().unwrap().to_string_lossy().into_owned());
+                this.people.insert(person.github().to_string(), person);
+                Ok(())
+            },
+        )?;
 
-        data.load_dir("teams", false, |this, _dir, team: Team| {
-            this.teams.insert(team.name().to_string(), team);
-            Ok(())
-        })?;
+        data.load_dir(
+            &layers,
+            Path::new("teams"),
+            false,
+            |this, _path, mut team: Team, content_hash| {
+                team.resolve_names()?;
+                team.set_content_hash(content_hash);
+                if team.is_draft() {
+                    this.draft_teams.push(team);
+                } else {
+                    this.teams.insert(team.name().to_string(), team);
+                }
+                Ok(())
+            },
+        )?;
 
-        data.load_dir("teams/archive", false, |this, _dir, team: Team| {
-            this.archived_teams.push(team);
-            Ok(())
-        })?;
+        data.load_dir(
+            &layers,
+            &Path::new("teams").join("archive"),
+            false,
+            |this, _path, mut team: Team, _| {
+                team.resolve_names()?;
+                this.archived_teams.push(team);
+                Ok(())
+            },
+        )?;
 
         Ok(data)
     }
 
-    fn load_dir<P, T, F>(&mut self, dir: P, nested: bool, f: F) -> Result<(), Error>
+    /// Build the data out of the TOML source of the config, people and teams.
+    #[cfg(test)]
+    pub(crate) fn from_toml(config: &str, people: &[&str], teams: &[&str]) -> Self {
+        let mut data = Data {
+            people: HashMap::new(),
+            teams: HashMap::new(),
+            archived_teams: Vec::new(),
+            draft_teams: Vec::new(),
+            repos: HashMap::new(),
+            archived_repos: Vec::new(),
+            config: toml::from_str(config).unwrap(),
+            deprecated_fields: Vec::new(),
+            team_ancestors: OnceLock::new(),
+        };
+        for person in people {
+            let mut person: Person = toml::from_str(person).unwrap();
+            person.set_file_name(person.github().to_string());
+            data.people.insert(person.github().to_string(), person);
+        }
+        for team in teams {
+            let mut team: Team = toml::from_str(team).unwrap();
+            team.resolve_names().unwrap();
+            if team.is_draft() {
+                data.draft_teams.push(team);
+            } else {
+                data.teams.insert(team.name().to_string(), team);
+            }
+        }
+        data
+    }
+
+    /// Add the repos built out of their TOML source, applying the defaults of their org.
+    #[cfg(test)]
+    pub(crate) fn with_repos(mut self, repos: &[&str]) -> Self {
+        for repo in repos {
+            let mut repo: Repo = toml::from_str(repo).unwrap();
+            let defaults = self.config.org_branch_protection_defaults(&repo.org);
+            for protection in &mut repo.branch_protections {
+                protection.resolve(defaults);
+            }
+            self.repos
+                .insert((repo.org.clone(), repo.name.clone()), repo);
+        }
+        self
+    }
+
+    /// Check the repo is in the directory of its org, and apply the defaults of the org.
+    fn prepare_repo(
+        &self,
+        path: &Path,
+        mut repo: Repo,
+        content_hash: String,
+    ) -> Result<Repo, Error> {
+        let org = parent_dir_name(path).unwrap();
+        if repo.org != org {
+            bail!(
+                "repo '{}' is located in the '{}' org directory but its org is '{}'",
+                repo.name,
+                org,
+                repo.org
+            )
+        }
+
+        let defaults = self.config.org_branch_protection_defaults(&repo.org);
+        for protection in &mut repo.branch_protections {
+            protection.resolve(defaults);
+        }
+        repo.content_hash = content_hash;
+        Ok(repo)
+    }
+
+    fn load_dir<T, F>(
+        &mut self,
+        layers: &Layers,
+        dir: &Path,
+        nested: bool,
+        f: F,
+    ) -> Result<(), Error>
     where
-        P: AsRef<Path>,
         T: for<'de> Deserialize<'de>,
-        F: Fn(&mut Self, &str, T) -> Result<(), Error>,
-        F: Clone,
+        F: Fn(&mut Self, &Path, T, String) -> Result<(), Error>,
     {
-        for entry in std::fs::read_dir(&dir).with_context(|e| {
-            let dir = dir.as_ref().display();
-            format!("`load_dir` failed to read directory '{}': {}", dir, e)
-        })? {
-            let path = entry?.path();
-            if nested && path.is_dir() {
-                self.load_dir(&path, false, f.clone())?;
-            } else if !nested && path.is_file() && path.extension() == Some(OsStr::new("toml")) {
-                fn dir(path: &PathBuf) -> Option<&str> {
-                    path.parent()?.file_name()?.to_str()
-                }
-                f(self, dir(&path).unwrap(), load_file(&path)?)?;
+        let kind = dir
+            .iter()
+            .next()
+            .and_then(|kind| kind.to_str())
+            .unwrap_or("");
+        for path in layers.files(dir, nested)? {
+            let value = load_file(&path)?;
+            for deprecation in deprecated_fields(DEPRECATIONS, kind, &value) {
+                self.deprecated_fields
+                    .push((path.display().to_string(), deprecation));
             }
+            let content_hash = content_hash(&value)?;
+            let parsed = value
+                .try_into()
+                .with_context(|_| format!("failed to parse {}", path.display()))?;
+            f(self, &path, parsed, content_hash)?;
         }
 
         Ok(())
     }
 
+    /// The deprecated fields used by the files, along with the path of each file.
+    pub(crate) fn deprecated_fields(&self) -> &[(String, &'static Deprecation)] {
+        &self.deprecated_fields
+    }
+
     pub(crate) fn config(&self) -> &Config {
         &self.config
     }
 
+    /// Start a query over the teams and people, see the [`crate::query`] module.
+    pub(crate) fn query(&self) -> Query<'_> {
+        Query::new(self)
+    }
+
     pub(crate) fn lists(&self) -> Result<HashMap<String, List>, Error> {
         let mut lists = HashMap::new();
         for team in self.teams.values() {
@@ -97,6 +262,41 @@ impl Data {
         Ok(lists)
     }
 
+    /// All the mailing lists, with their members fully expanded, deduplicated and sorted. The
+    /// lists of private teams are left out.
+    pub(crate) fn export_lists(&self) -> Result<v1::Lists, Error> {
+        let mut lists = IndexMap::new();
+        for team in self.teams().filter(|team| !team.is_private()) {
+            for list in team.lists(self)? {
+                let mut members = list.emails().to_vec();
+                members.sort();
+                members.dedup();
+                lists.insert(
+                    list.address().to_string(),
+                    v1::List {
+                        address: list.address().to_string(),
+                        members,
+                    },
+                );
+            }
+        }
+        lists.sort_keys();
+        Ok(v1::Lists { lists })
+    }
+
+    /// The mapping between Zulip IDs and GitHub IDs, as published in the v1 API.
+    ///
+    /// If multiple people share a Zulip ID, the last one wins: `validate_unique_zulip_ids`
+    /// reports them as errors.
+    pub(crate) fn zulip_mapping(&self) -> v1::ZulipMapping {
+        let mut users = IndexMap::new();
+        for person in self.query().people().has_zulip_id(true) {
+            users.extend(person.zulip_id().map(|id| (id, person.github_id())));
+        }
+        users.sort_keys();
+        v1::ZulipMapping { users }
+    }
+
     pub(crate) fn list(&self, name: &str) -> Result<Option<List>, Error> {
         let mut lists = self.lists()?;
         Ok(lists.remove(name))
@@ -120,10 +320,74 @@ impl Data {
         self.teams.values()
     }
 
+    /// The names of the ancestors of the team, from its parent up to the top of the hierarchy.
+    ///
+    /// The ancestors of all the teams are computed the first time this is called. An error is
+    /// returned if one of the ancestors doesn't exist, or if the team is a subteam of itself.
+    pub(crate) fn team_ancestors(&self, name: &str) -> Result<Vec<&str>, Error> {
+        let ancestors = self.team_ancestors.get_or_init(|| {
+            self.teams
+                .values()
+                .map(|team| (team.name().to_string(), self.compute_team_ancestors(team)))
+                .collect()
+        });
+        match ancestors.get(name) {
+            Some(Ok(ancestors)) => Ok(ancestors.iter().map(|a| a.as_str()).collect()),
+            Some(Err(err)) => Err(err_msg(err.clone())),
+            None => bail!("team `{}` doesn't exist", name),
+        }
+    }
+
+    fn compute_team_ancestors<'a>(&'a self, mut team: &'a Team) -> Result<Vec<String>, String> {
+        let mut visited = vec![team.name()];
+        let mut ancestors = Vec::new();
+        while let Some(parent) = team.subteam_of() {
+            if visited.contains(&parent) {
+                return Err(format!(
+                    "team `{parent}` is a subteam of itself: {} => {parent}",
+                    visited.join(" => "),
+                ));
+            }
+            let Some(parent) = self.team(parent) else {
+                return Err(self.missing_parent_error(team, parent));
+            };
+            visited.push(parent.name());
+            ancestors.push(parent.name().to_string());
+            team = parent;
+        }
+        Ok(ancestors)
+    }
+
+    /// The error explaining why the parent of the team isn't an active team.
+    pub(crate) fn missing_parent_error(&self, team: &Team, parent: &str) -> String {
+        if self
+            .archived_teams()
+            .any(|archived| archived.name() == parent)
+        {
+            format!(
+                "the parent of team `{}` is archived: `{}`",
+                team.name(),
+                parent
+            )
+        } else if self.draft_teams().any(|draft| draft.name() == parent) {
+            format!(
+                "the parent of team `{}` is a draft team: `{}`",
+                team.name(),
+                parent
+            )
+        } else {
+            format!(
+                "the parent of team `{}` doesn't exist: `{}`",
+                team.name(),
+                parent
+            )
+        }
+    }
+
     pub(crate) fn subteams_of<'a>(
         &'a self,
         team_name: &'a str,
-    ) -> impl Iterator<Item = &Team> + 'a {
+    ) -> impl Iterator<Item = &'a Team> + 'a {
         self.team(team_name).into_iter().flat_map(move |team| {
             self.teams()
                 .filter(move |maybe_subteam| team.is_parent_of(self, maybe_subteam))
@@ -140,7 +404,12 @@ impl Data {
 
     pub(crate) fn active_members(&self) -> Result<HashSet<&str>, Error> {
         let mut active = HashSet::new();
-        for team in self.teams.values().filter(|team| !team.is_alumni_team()) {
+        for team in self
+            .query()
+            .teams()
+            .archived(false)
+            .filter(|team| !team.is_alumni_team())
+        {
             active.extend(team.members(self)?)
         }
         Ok(active)
@@ -150,10 +419,23 @@ impl Data {
         self.repos.values()
     }
 
+    pub(crate) fn repo(&self, org: &str, name: &str) -> Option<&Repo> {
+        self.repos.get(&(org.to_string(), name.to_string()))
+    }
+
+    pub(crate) fn archived_repos(&self) -> impl Iterator<Item = &Repo> {
+        self.archived_repos.iter()
+    }
+
     pub(crate) fn archived_teams(&self) -> impl Iterator<Item = &Team> {
         self.archived_teams.iter()
     }
 
+    /// The teams with `draft = true`, which are left out of `teams` so that they're not synced.
+    pub(crate) fn draft_teams(&self) -> impl Iterator<Item = &Team> {
+        self.draft_teams.iter()
+    }
+
     /// All the configured GitHub teams in the a hashset of (org, team_name) tuples.
     pub(crate) fn github_teams(&self) -> HashSet<(String, String)> {
         let mut result = HashSet::new();
@@ -166,6 +448,97 @@ impl Data {
     }
 }
 
+/// The directories the data is loaded from, see [`Data::load_overlaid`].
+struct Layers<'a> {
+    root: &'a Path,
+    overlay: Option<&'a Path>,
+}
+
+impl Layers<'_> {
+    /// The path to load the file at `relative` from, or `None` if the overlay deletes it.
+    fn file(&self, relative: &Path) -> Result<Option<PathBuf>, Error> {
+        if let Some(overlay) = self.overlay {
+            let path = overlay.join(relative);
+            if path.is_file() {
+                return Ok(if is_empty_file(&path)? {
+                    None
+                } else {
+                    Some(path)
+                });
+            }
+        }
+        Ok(Some(self.root.join(relative)))
+    }
+
+    /// Whether the `relative` directory exists in any of the layers.
+    fn has_dir(&self, relative: &Path) -> bool {
+        self.root.join(relative).is_dir()
+            || self
+                .overlay
+                .is_some_and(|overlay| overlay.join(relative).is_dir())
+    }
+
+    /// The paths to load the TOML files in the `relative` directory from, sorted by their path
+    /// relative to the layer. With `nested`, the files are in the subdirectories of `relative`.
+    fn files(&self, relative: &Path, nested: bool) -> Result<Vec<PathBuf>, Error> {
+        let mut files = BTreeMap::new();
+        let dir = self.root.join(relative);
+        // Directories only present in the overlay are fine.
+        if dir.is_dir() || !self.has_dir(relative) {
+            for path in toml_files(&dir, nested)? {
+                files.insert(path.strip_prefix(self.root)?.to_path_buf(), path);
+            }
+        }
+        if let Some(overlay) = self.overlay {
+            let dir = overlay.join(relative);
+            if dir.is_dir() {
+                for path in toml_files(&dir, nested)? {
+                    let key = path.strip_prefix(overlay)?.to_path_buf();
+                    if is_empty_file(&path)? {
+                        files.remove(&key);
+                    } else {
+                        files.insert(key, path);
+                    }
+                }
+            }
+        }
+        Ok(files.into_values().collect())
+    }
+}
+
+/// The TOML files in `dir`, or in its subdirectories if `nested`.
+///
+/// The `archive` subdirectory is always skipped, as archived files are loaded separately.
+fn toml_files(dir: &Path, nested: bool) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|e| {
+        format!(
+            "`load_dir` failed to read directory '{}': {}",
+            dir.display(),
+            e
+        )
+    })? {
+        let path = entry?.path();
+        if nested && path.is_dir() && path.file_name() != Some(OsStr::new("archive")) {
+            files.extend(toml_files(&path, false)?);
+        } else if !nested && path.is_file() && path.extension() == Some(OsStr::new("toml")) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn is_empty_file(path: &Path) -> Result<bool, Error> {
+    let metadata = std::fs::metadata(path)
+        .with_context(|_| format!("failed to read the metadata of {}", path.display()))?;
+    Ok(metadata.len() == 0)
+}
+
+/// The name of the directory containing the file at `path`.
+fn parent_dir_name(path: &Path) -> Option<&str> {
+    path.parent()?.file_name()?.to_str()
+}
+
 fn load_file<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, Error> {
     let content =
         std::fs::read(path).with_context(|_| format!("failed to read {}", path.display()))?;
@@ -173,3 +546,123 @@ fn load_file<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, Error> {
         .with_context(|_| format!("failed to parse {}", path.display()))?;
     Ok(parsed)
 }
+
+/// Compute the SHA-256 hash of the canonical serialization of a TOML document.
+///
+/// The document is normalized into compact JSON with sorted keys before hashing, so that the hash
+/// only changes when the content changes, and not when the formatting or the key order does.
+fn content_hash(value: &toml::Value) -> Result<String, Error> {
+    let canonical = serde_json::to_string(&serde_json::to_value(value)?)?;
+    Ok(hex::encode(Sha256::digest(canonical.as_bytes())))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{content_hash, Data};
+
+    /// The TOML source of a `config.toml` including `extra`, with the required keys not set by
+    /// `extra` left empty.
+    pub(crate) fn config(extra: &str) -> String {
+        let mut config = String::new();
+        for key in [
+            "allowed-mailing-lists-domains",
+            "allowed-github-orgs",
+            "permissions-bors-repos",
+            "permissions-bools",
+        ] {
+            let prefix = format!("{} =", key);
+            if !extra.lines().any(|l| l.trim_start().starts_with(&prefix)) {
+                config.push_str(&format!("{} = []\n", key));
+            }
+        }
+        config + extra
+    }
+
+    /// The TOML source of a person named after their GitHub username, including `extra`.
+    pub(crate) fn person(github: &str, github_id: usize, extra: &str) -> String {
+        format!(
+            "name = '{0}'\ngithub = '{0}'\ngithub-id = {1}\n{2}",
+            github, github_id, extra
+        )
+    }
+
+    /// The TOML source of a team with the given leads and members, including `extra`. The people
+    /// are set with dotted keys, so `extra` can contain both top-level keys (including other
+    /// `people.*` keys) and tables.
+    pub(crate) fn team(name: &str, leads: &[&str], members: &[&str], extra: &str) -> String {
+        let list = |names: &[&str]| {
+            names
+                .iter()
+                .map(|n| format!("'{}'", n))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        format!(
+            "name = '{}'\npeople.leads = [{}]\npeople.members = [{}]\n{}",
+            name,
+            list(leads),
+            list(members),
+            extra
+        )
+    }
+
+    fn hash(toml: &str) -> String {
+        content_hash(&toml::from_str(toml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_content_hash_ignores_formatting() {
+        assert_eq!(
+            hash("name = 'foo'\n[people]\nleads = []\nmembers = ['a', 'b']\n"),
+            hash("# comment\nname = \"foo\"\n\n[people]\nmembers = [\"a\", \"b\"]\nleads = []\n"),
+        );
+    }
+
+    #[test]
+    fn test_content_hash_changes_with_content() {
+        assert_ne!(
+            hash("name = 'foo'\n[people]\nleads = []\nmembers = ['a', 'b']\n"),
+            hash("name = 'foo'\n[people]\nleads = []\nmembers = ['a', 'c']\n"),
+        );
+    }
+
+    #[test]
+    fn test_team_ancestors() {
+        let subteam = |name: &str, parent: &str| {
+            team(name, &[], &[], &format!("subteam-of = '{}'\n", parent))
+        };
+        let teams = [
+            team("top", &[], &[], ""),
+            subteam("middle", "top"),
+            subteam("bottom", "middle"),
+            subteam("orphan", "missing"),
+            subteam("adopted", "old"),
+            subteam("cycle-a", "cycle-b"),
+            subteam("cycle-b", "cycle-a"),
+        ];
+        let mut data = Data::from_toml(
+            &config(""),
+            &[],
+            &teams.iter().map(|t| t.as_str()).collect::<Vec<_>>(),
+        );
+        data.archived_teams
+            .push(toml::from_str(&team("old", &[], &[], "")).unwrap());
+
+        assert!(data.team_ancestors("top").unwrap().is_empty());
+        assert_eq!(data.team_ancestors("middle").unwrap(), ["top"]);
+        assert_eq!(data.team_ancestors("bottom").unwrap(), ["middle", "top"]);
+        assert_eq!(
+            data.team_ancestors("orphan").unwrap_err().to_string(),
+            "the parent of team `orphan` doesn't exist: `missing`"
+        );
+        assert_eq!(
+            data.team_ancestors("adopted").unwrap_err().to_string(),
+            "the parent of team `adopted` is archived: `old`"
+        );
+        assert_eq!(
+            data.team_ancestors("cycle-a").unwrap_err().to_string(),
+            "team `cycle-a` is a subteam of itself: cycle-a => cycle-b => cycle-a"
+        );
+        assert!(data.team_ancestors("missing").is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! Compute the effective changes between two checkouts of the team repository.
+//!
+//! Adding someone to a subteam can change the members of GitHub teams, mailing lists, Zulip
+//! groups and permissions all over the place. Rather than comparing the TOML files, the diff
+//! resolves the state of both checkouts and compares the resolved state.
+
+use crate::data::Data;
+use crate::schema::{Permissions, ZulipGroupMember};
+use failure::Error;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+#[derive(serde::Serialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ChangeKind {
+    TeamMember,
+    GithubTeam,
+    MailingList,
+    ZulipGroup,
+    Permission,
+}
+
+#[derive(serde::Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Action {
+    Added,
+    Removed,
+}
+
+/// A single change in the resolved state, like a person being added to a mailing list.
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+pub(crate) struct Change {
+    pub(crate) kind: ChangeKind,
+    pub(crate) action: Action,
+    /// The team, GitHub team, list address, Zulip group or permission that changed.
+    pub(crate) target: String,
+    /// The person (or email address, for mailing lists) that was added or removed.
+    pub(crate) person: String,
+    /// Why the person has (or had) the permission, only present for permissions.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) via: Vec<String>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (added, removed, what) = match self.kind {
+            ChangeKind::TeamMember => ("added to", "removed from", "team"),
+            ChangeKind::GithubTeam => ("added to", "removed from", "GitHub team"),
+            ChangeKind::MailingList => ("added to", "removed from", "mailing list"),
+            ChangeKind::ZulipGroup => ("added to", "removed from", "Zulip group"),
+            ChangeKind::Permission => ("gained", "lost", "permission"),
+        };
+        let action = match self.action {
+            Action::Added => added,
+            Action::Removed => removed,
+        };
+        write!(f, "{} {} {} {}", self.person, action, what, self.target)?;
+        if !self.via.is_empty() {
+            write!(f, " via {}", self.via.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The resolved state of a checkout.
+///
+/// Entries are keyed by kind, target and a stable identifier of the person (the GitHub ID for
+/// GitHub teams and the Zulip ID for Zulip groups, so that renames are not reported as changes).
+#[derive(Default)]
+struct State {
+    entries: BTreeMap<(ChangeKind, String, String), Entry>,
+}
+
+struct Entry {
+    person: String,
+    via: BTreeSet<String>,
+}
+
+impl State {
+    fn resolve(data: &Data) -> Result<Self, Error> {
+        let mut state = State::default();
+
+        for team in data.teams() {
+            for member in team.members(data)? {
+                state.add(ChangeKind::TeamMember, team.name(), member, member, None);
+            }
+            for github in team.github_teams(data)? {
+                let target = format!("{}/{}", github.org, github.name);
+                for (login, id) in &github.members {
+                    state.add(
+                        ChangeKind::GithubTeam,
+                        &target,
+                        &id.to_string(),
+                        login,
+                        None,
+                    );
+                }
+            }
+            for list in team.lists(data)? {
+                for email in list.emails() {
+                    state.add(ChangeKind::MailingList, list.address(), email, email, None);
+                }
+            }
+            for group in team.zulip_groups(data)? {
+                for member in group.members() {
+                    let (key, person) = match member {
+                        ZulipGroupMember::MemberWithId { github, zulip_id } => {
+                            (zulip_id.to_string(), github.clone())
+                        }
+                        ZulipGroupMember::JustId(zulip_id) => {
+                            (zulip_id.to_string(), format!("#{}", zulip_id))
+                        }
+                        ZulipGroupMember::MemberWithoutId { github } => {
+                            (github.clone(), github.clone())
+                        }
+                    };
+                    state.add(ChangeKind::ZulipGroup, group.name(), &key, &person, None);
+                }
+            }
+        }
+
+        for permission in Permissions::available(data.config()) {
+            for person in data.people() {
+                if person.permissions().has(&permission) {
+                    let via = "direct grant".to_string();
+                    let github = person.github();
+                    state.add(
+                        ChangeKind::Permission,
+                        &permission,
+                        github,
+                        github,
+                        Some(via),
+                    );
+                }
+            }
+            for team in data.teams() {
+                if team.permissions().has(&permission) {
+                    for member in team.members(data)? {
+                        let via = format!("team {}", team.name());
+                        state.add(
+                            ChangeKind::Permission,
+                            &permission,
+                            member,
+                            member,
+                            Some(via),
+                        );
+                    }
+                }
+                if team.leads_permissions().has(&permission) {
+                    for lead in team.leads() {
+                        let via = format!("leads of team {}", team.name());
+                        state.add(ChangeKind::Permission, &permission, lead, lead, Some(via));
+                    }
+                }
+            }
+        }
+
+        Ok(state)
+    }
+
+    fn add(
+        &mut self,
+        kind: ChangeKind,
+        target: &str,
+        key: &str,
+        person: &str,
+        via: Option<String>,
+    ) {
+        let entry = self
+            .entries
+            .entry((kind, target.to_string(), key.to_string()))
+            .or_insert_with(|| Entry {
+                person: person.to_string(),
+                via: BTreeSet::new(),
+            });
+        entry.via.extend(via);
+    }
+}
+
+/// Compute the changes to the resolved state between the `old` and the `new` data.
+pub(crate) fn diff(old: &Data, new: &Data) -> Result<Vec<Change>, Error> {
+    let old = State::resolve(old)?;
+    let new = State::resolve(new)?;
+
+    let changes_between = |from: &State, to: &State, action| {
+        from.entries
+            .iter()
+            .filter(|(key, _)| !to.entries.contains_key(key))
+            .map(move |((kind, target, _), entry)| Change {
+                kind: *kind,
+                action,
+                target: target.clone(),
+                person: entry.person.clone(),
+                via: entry.via.iter().cloned().collect(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut changes = changes_between(&old, &new, Action::Removed);
+    changes.extend(changes_between(&new, &old, Action::Added));
+    changes.sort_by(|a, b| (a.kind, &a.target, &a.person).cmp(&(b.kind, &b.target, &b.person)));
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::{config, person, team};
+
+    fn change(kind: ChangeKind, action: Action, target: &str, person: &str) -> Change {
+        Change {
+            kind,
+            action,
+            target: target.into(),
+            person: person.into(),
+            via: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_ignores_renames() -> Result<(), Error> {
+        let data = |github: &str, github_id: usize, zulip_id: usize| {
+            Data::from_toml(
+                &config("allowed-github-orgs = ['org']\n"),
+                &[&person(
+                    github,
+                    github_id,
+                    &format!("zulip-id = {}\n", zulip_id),
+                )],
+                &[&team(
+                    "team",
+                    &[],
+                    &[github],
+                    "[[github]]\n\
+                     orgs = ['org']\n\
+                     [[zulip-groups]]\n\
+                     name = 'T-team'\n",
+                )],
+            )
+        };
+        // The GitHub team and the Zulip group are keyed by the GitHub and Zulip IDs, which don't
+        // change when someone is renamed.
+        assert_eq!(
+            diff(&data("old", 1, 10), &data("new", 1, 10))?,
+            [
+                change(ChangeKind::TeamMember, Action::Added, "team", "new"),
+                change(ChangeKind::TeamMember, Action::Removed, "team", "old"),
+            ]
+        );
+        // While changing the IDs is reported as a change, with the removal first.
+        assert_eq!(
+            diff(&data("foo", 1, 10), &data("foo", 2, 20))?,
+            [
+                change(ChangeKind::GithubTeam, Action::Removed, "org/team", "foo"),
+                change(ChangeKind::GithubTeam, Action::Added, "org/team", "foo"),
+                change(ChangeKind::ZulipGroup, Action::Removed, "T-team", "foo"),
+                change(ChangeKind::ZulipGroup, Action::Added, "T-team", "foo"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_permissions_via() -> Result<(), Error> {
+        let config = config("permissions-bools = ['crater']\n");
+        let people = [
+            person("direct", 1, "[permissions]\ncrater = true\n"),
+            person("member", 2, ""),
+            person("lead", 3, ""),
+        ];
+        let people = people.iter().map(|p| p.as_str()).collect::<Vec<_>>();
+        let old = Data::from_toml(&config, &[], &[]);
+        let new = Data::from_toml(
+            &config,
+            &people,
+            &[
+                &team(
+                    "a",
+                    &[],
+                    &["member", "lead"],
+                    "[permissions]\ncrater = true\n",
+                ),
+                &team(
+                    "b",
+                    &["lead"],
+                    &["lead"],
+                    "[leads-permissions]\ncrater = true\n",
+                ),
+            ],
+        );
+
+        let permissions = diff(&old, &new)?
+            .into_iter()
+            .filter(|change| change.kind == ChangeKind::Permission)
+            .map(|change| (change.person, change.via))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            permissions,
+            [
+                ("direct".to_string(), vec!["direct grant".to_string()]),
+                (
+                    "lead".to_string(),
+                    vec!["leads of team b".to_string(), "team a".to_string()]
+                ),
+                ("member".to_string(), vec!["team a".to_string()]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_order_and_output() -> Result<(), Error> {
+        let data = |members: &[&str], crater: bool| {
+            let people = ["a", "b", "c"]
+                .iter()
+                .enumerate()
+                .map(|(id, name)| person(name, id, ""))
+                .collect::<Vec<_>>();
+            Data::from_toml(
+                &config("permissions-bools = ['crater']\n"),
+                &people.iter().map(|p| p.as_str()).collect::<Vec<_>>(),
+                &[&team(
+                    "x",
+                    &[],
+                    members,
+                    &format!("[permissions]\ncrater = {}\n", crater),
+                )],
+            )
+        };
+        let changes = diff(&data(&["b"], false), &data(&["a", "c"], true))?;
+
+        // Sorted by kind, target and person, whatever the action.
+        assert_eq!(
+            changes.iter().map(|c| c.to_string()).collect::<Vec<_>>(),
+            [
+                "a added to team x",
+                "b removed from team x",
+                "c added to team x",
+                "a gained permission crater via team x",
+                "c gained permission crater via team x",
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&changes[..2])?,
+            r#"[{"kind":"team-member","action":"added","target":"x","person":"a"},{"kind":"team-member","action":"removed","target":"x","person":"b"}]"#
+        );
+        assert_eq!(
+            serde_json::to_string(&changes[3])?,
+            r#"{"kind":"permission","action":"added","target":"crater","person":"a","via":["team x"]}"#
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+//! Apply the GitHub username changes detected by `validate_github_usernames` to the data.
+//!
+//! The files are edited with `toml_edit`, so their formatting and comments are preserved.
+
+use crate::data::Data;
+use crate::github::GitHubApi;
+use crate::schema::Person;
+use failure::{bail, format_err, Error, ResultExt};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use toml_edit::{Document, Item, Value};
+
+/// The people whose GitHub username changed, with their new username, sorted by old username.
+pub(crate) fn renamed_people<'a>(
+    data: &'a Data,
+    github: &GitHubApi,
+) -> Result<Vec<(&'a Person, String)>, Error> {
+    let people = data
+        .people()
+        .map(|p| (p.github_id(), p))
+        .collect::<HashMap<_, _>>();
+    let mut renamed = github
+        .usernames(&people.keys().cloned().collect::<Vec<_>>())?
+        .into_iter()
+        .filter(|(id, name)| people[id].github() != name)
+        .map(|(id, name)| (people[&id], name))
+        .collect::<Vec<_>>();
+    renamed.sort_by_key(|(person, _)| person.github());
+    Ok(renamed)
+}
+
+/// Rename the people whose GitHub username changed, updating all the references to them in the
+/// checkout located at `root`.
+pub(crate) fn fix_usernames(
+    data: &Data,
+    github: &GitHubApi,
+    root: &Path,
+    force: bool,
+) -> Result<(), Error> {
+    if !force {
+        ensure_clean_working_tree(root)?;
+    }
+
+    let renamed = renamed_people(data, github)?;
+    if renamed.is_empty() {
+        println!("no GitHub username changed");
+        return Ok(());
+    }
+    let renames = renamed
+        .iter()
+        .map(|(person, new)| (person.github().to_string(), new.clone()))
+        .collect::<HashMap<_, _>>();
+
+    for (person, new) in &renamed {
+        println!("user `{}` changed username to `{}`", person.github(), new);
+        let old_path = root
+            .join("people")
+            .join(format!("{}.toml", person.file_name()));
+        let new_path = root.join("people").join(format!("{}.toml", new));
+        edit_file(&old_path, |doc| {
+            if let Some(value) = doc.get_mut("github").and_then(|item| item.as_value_mut()) {
+                replace_value(value, new);
+            }
+        })?;
+        if old_path != new_path {
+            std::fs::rename(&old_path, &new_path).with_context(|_| {
+                format!(
+                    "failed to rename {} to {}",
+                    old_path.display(),
+                    new_path.display()
+                )
+            })?;
+            println!("  renamed {} to {}", old_path.display(), new_path.display());
+        }
+    }
+
+    for path in toml_files(&root.join("teams"), false)? {
+        edit_file(&path, |doc| rename_in_team(doc, &renames))?;
+    }
+    for path in toml_files(&root.join("repos"), true)? {
+        edit_file(&path, |doc| rename_in_repo(doc, &renames))?;
+    }
+    Ok(())
+}
+
+fn ensure_clean_working_tree(root: &Path) -> Result<(), Error> {
+    // An empty path passed to `-C` leaves the current directory unchanged.
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["status", "--porcelain"])
+        .output()
+        .context("failed to run `git status`")?;
+    if !output.status.success() {
+        bail!(
+            "`git status` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    if !output.stdout.is_empty() {
+        bail!("the working tree is dirty: commit or stash your changes, or pass --force");
+    }
+    Ok(())
+}
+
+/// The TOML files in the directory, including the ones in the subdirectories if `nested`, and the
+/// archived ones.
+fn toml_files(dir: &Path, nested: bool) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .with_context(|_| format!("failed to read directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            if path.file_name() == Some(OsStr::new("archive")) {
+                files.extend(toml_files(&path, nested)?);
+            } else if nested {
+                files.extend(toml_files(&path, false)?);
+            }
+        } else if path.extension() == Some(OsStr::new("toml")) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Edit the TOML file in place, printing its path if it changed.
+fn edit_file(path: &Path, edit: impl FnOnce(&mut Document)) -> Result<(), Error> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|_| format!("failed to read {}", path.display()))?;
+    let mut doc = content
+        .parse::<Document>()
+        .map_err(|e| format_err!("failed to parse {}: {}", path.display(), e))?;
+    edit(&mut doc);
+    let edited = doc.to_string();
+    if edited != content {
+        std::fs::write(path, edited)
+            .with_context(|_| format!("failed to write {}", path.display()))?;
+        println!("  updated {}", path.display());
+    }
+    Ok(())
+}
+
+fn rename_in_team(doc: &mut Document, renames: &HashMap<String, String>) {
+    if let Some(people) = doc.get_mut("people") {
+        for key in &["leads", "members", "alumni"] {
+            rename_in_array(people.get_mut(key), renames);
+        }
+    }
+    rename_in_array(doc.get_mut("list-only-members"), renames);
+    if let Some(rfcbot) = doc.get_mut("rfcbot") {
+        rename_in_array(rfcbot.get_mut("exclude-members"), renames);
+    }
+    for (key, fields) in &[
+        ("lists", &["extra-people"][..]),
+        ("zulip-groups", &["extra-people", "excluded-people"][..]),
+    ] {
+        let tables = doc
+            .get_mut(key)
+            .and_then(|item| item.as_array_of_tables_mut());
+        for table in tables.into_iter().flat_map(|tables| tables.iter_mut()) {
+            for field in *fields {
+                rename_in_array(table.get_mut(field), renames);
+            }
+        }
+    }
+}
+
+fn rename_in_repo(doc: &mut Document, renames: &HashMap<String, String>) {
+    let individuals = doc
+        .get_mut("access")
+        .and_then(|access| access.get_mut("individuals"))
+        .and_then(|item| item.as_table_like_mut());
+    if let Some(individuals) = individuals {
+        for (old, new) in renames {
+            if let Some(permission) = individuals.remove(old) {
+                individuals.insert(new, permission);
+            }
+        }
+    }
+    let codeowners = doc
+        .get_mut("codeowners")
+        .and_then(|item| item.as_table_like_mut());
+    for (_, owners) in codeowners.into_iter().flat_map(|owners| owners.iter_mut()) {
+        rename_in_array(owners.get_mut("people"), renames);
+    }
+}
+
+fn rename_in_array(item: Option<&mut Item>, renames: &HashMap<String, String>) {
+    if let Some(array) = item.and_then(|item| item.as_array_mut()) {
+        for value in array.iter_mut() {
+            if let Some(new) = value.as_str().and_then(|name| renames.get(name)) {
+                replace_value(value, new);
+            }
+        }
+    }
+}
+
+/// Replace the value with a string, keeping the whitespace and comments around it.
+fn replace_value(value: &mut Value, new: &str) {
+    let decor = value.decor().clone();
+    *value = Value::from(new);
+    *value.decor_mut() = decor;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_preserves_formatting() {
+        let renames = vec![("old".to_string(), "new".to_string())]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let mut team: Document = "name = \"foo\"\n\
+             \n\
+             [people]\n\
+             leads = [\"old\"]\n\
+             members = [\n    \"other\",\n    \"old\", # the lead\n]\n\
+             \n\
+             [[lists]]\n\
+             address = \"foo@example.com\"\n\
+             extra-people = [\"old\"]\n"
+            .parse()
+            .unwrap();
+        rename_in_team(&mut team, &renames);
+        assert_eq!(
+            team.to_string(),
+            "name = \"foo\"\n\
+             \n\
+             [people]\n\
+             leads = [\"new\"]\n\
+             members = [\n    \"other\",\n    \"new\", # the lead\n]\n\
+             \n\
+             [[lists]]\n\
+             address = \"foo@example.com\"\n\
+             extra-people = [\"new\"]\n"
+        );
+
+        let mut repo: Document = "[access.individuals]\n\
+             old = \"write\"\n\
+             \n\
+             [codeowners]\n\
+             \"/src/\" = { teams = [\"foo\"], people = [\"old\", \"other\"] }\n\
+             \n\
+             [codeowners.\"/docs/\"]\n\
+             people = [\"old\"] # the docs\n"
+            .parse()
+            .unwrap();
+        rename_in_repo(&mut repo, &renames);
+        assert_eq!(
+            repo.to_string(),
+            "[access.individuals]\n\
+             new = \"write\"\n\
+             \n\
+             [codeowners]\n\
+             \"/src/\" = { teams = [\"foo\"], people = [\"new\", \"other\"] }\n\
+             \n\
+             [codeowners.\"/docs/\"]\n\
+             people = [\"new\"] # the docs\n"
+        );
+    }
+}
@@ -1,12 +1,36 @@
+use crate::cache::DiskCache;
 use failure::{bail, Error};
-use reqwest::blocking::{Client, ClientBuilder, RequestBuilder};
+use log::warn;
+use rayon::prelude::*;
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response};
 use reqwest::header::{self, HeaderValue};
-use reqwest::Method;
+use reqwest::{Method, StatusCode};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 
 static API_BASE: &str = "https://api.github.com/";
 static TOKEN_VAR: &str = "GITHUB_TOKEN";
+static MAX_ATTEMPTS_VAR: &str = "GITHUB_MAX_ATTEMPTS";
+
+/// How many times a request is attempted before giving up, unless overridden by the
+/// `GITHUB_MAX_ATTEMPTS` environment variable.
+const DEFAULT_MAX_ATTEMPTS: u32 = 4;
+/// The delay before the first retry, doubled after every failed attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+/// The longest we're willing to wait before a retry, even if GitHub asks for more.
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// How many user IDs are resolved by each GraphQL request in `GitHubApi::usernames`.
+const USERNAMES_BATCH_SIZE: usize = 100;
+/// How many of those requests can be in flight at the same time.
+const USERNAMES_CONCURRENCY: usize = 4;
+/// How many repositories are looked up by each GraphQL request in `GitHubApi::repo_statuses`.
+const REPOS_BATCH_SIZE: usize = 50;
+/// How long the accounts stored in the on-disk cache are trusted before fetching them again.
+const ACCOUNTS_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
 
 #[derive(serde::Deserialize)]
 pub(crate) struct User {
@@ -26,6 +50,8 @@ struct GraphResult<T> {
 #[derive(serde::Deserialize)]
 struct GraphError {
     message: String,
+    #[serde(rename = "type")]
+    kind: Option<String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -33,19 +59,74 @@ struct GraphNodes<T> {
     nodes: Vec<Option<T>>,
 }
 
+/// A response cached by the GitHub client, along with its `ETag`.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct CachedResponse {
+    etag: String,
+    body: String,
+}
+
 pub(crate) struct GitHubApi {
     http: Client,
     token: Option<String>,
+    base_url: String,
+    cache: Mutex<HashMap<String, CachedResponse>>,
+    cache_hits: AtomicUsize,
+    cache_misses: AtomicUsize,
+    disk_cache: Option<DiskCache>,
+    accounts: Mutex<HashMap<usize, Option<Account>>>,
+    max_attempts: u32,
+    retry_base_delay: Duration,
 }
 
 impl GitHubApi {
     pub(crate) fn new() -> Self {
+        let token = std::env::var(TOKEN_VAR).ok();
         GitHubApi {
             http: ClientBuilder::new()
                 .user_agent(crate::USER_AGENT)
                 .build()
                 .unwrap(),
-            token: std::env::var(TOKEN_VAR).ok(),
+            disk_cache: DiskCache::open(token.as_deref()),
+            token,
+            base_url: API_BASE.to_string(),
+            cache: Mutex::new(HashMap::new()),
+            cache_hits: AtomicUsize::new(0),
+            cache_misses: AtomicUsize::new(0),
+            accounts: Mutex::new(HashMap::new()),
+            max_attempts: std::env::var(MAX_ATTEMPTS_VAR)
+                .ok()
+                .and_then(|attempts| attempts.parse().ok())
+                .filter(|&attempts| attempts > 0)
+                .unwrap_or(DEFAULT_MAX_ATTEMPTS),
+            retry_base_delay: RETRY_BASE_DELAY,
+        }
+    }
+
+    /// A client authenticated with a dummy token, sending its requests to the given server
+    /// without any on-disk cache.
+    #[cfg(test)]
+    pub(crate) fn mocked(base_url: String) -> Self {
+        let mut github = GitHubApi::new();
+        github.disk_cache = None;
+        github.base_url = base_url;
+        github.token = Some("token".into());
+        github
+    }
+
+    /// The number of cache hits and misses of the conditional requests made so far.
+    pub(crate) fn cache_stats(&self) -> (usize, usize) {
+        (
+            self.cache_hits.load(Ordering::Relaxed),
+            self.cache_misses.load(Ordering::Relaxed),
+        )
+    }
+
+    fn absolute_url<'a>(&self, url: &'a str) -> Cow<'a, str> {
+        if url.starts_with("https://") {
+            Cow::Borrowed(url)
+        } else {
+            Cow::Owned(format!("{}{}", self.base_url, url))
         }
     }
 
@@ -55,11 +136,7 @@ impl GitHubApi {
         method: Method,
         url: &str,
     ) -> Result<RequestBuilder, Error> {
-        let url = if url.starts_with("https://") {
-            Cow::Borrowed(url)
-        } else {
-            Cow::Owned(format!("{}{}", API_BASE, url))
-        };
+        let url = self.absolute_url(url);
         if require_auth {
             self.require_auth()?;
         }
@@ -74,7 +151,108 @@ impl GitHubApi {
         Ok(req)
     }
 
-    fn graphql<R, V>(&self, query: &str, variables: V) -> Result<R, Error>
+    /// Send the request, retrying it with exponential backoff on server errors, rate limits and
+    /// network errors.
+    ///
+    /// The `Retry-After` header is honored when GitHub sends it. Once all the attempts failed,
+    /// the last response (or error) is returned, so callers see the final failure.
+    fn send(&self, req: RequestBuilder) -> Result<Response, Error> {
+        let mut attempt = 1;
+        loop {
+            let retry = match req.try_clone() {
+                Some(retry) if attempt < self.max_attempts => retry,
+                // Requests with a streaming body can't be retried.
+                _ => return Ok(req.send()?),
+            };
+            let delay = match retry.send() {
+                Ok(resp) if is_transient(&resp) => {
+                    let delay = retry_after(&resp).unwrap_or_else(|| self.backoff(attempt));
+                    warn!(
+                        "GitHub replied with {} to {}, retrying in {}s (attempt {}/{})",
+                        resp.status(),
+                        resp.url(),
+                        delay.as_secs(),
+                        attempt,
+                        self.max_attempts
+                    );
+                    delay
+                }
+                Ok(resp) => return Ok(resp),
+                Err(err) if err.is_timeout() || err.is_connect() || err.is_request() => {
+                    let delay = self.backoff(attempt);
+                    warn!(
+                        "request to GitHub failed ({}), retrying in {}s (attempt {}/{})",
+                        err,
+                        delay.as_secs(),
+                        attempt,
+                        self.max_attempts
+                    );
+                    delay
+                }
+                Err(err) => return Err(err.into()),
+            };
+            std::thread::sleep(delay.min(RETRY_MAX_DELAY));
+            attempt += 1;
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.retry_base_delay * 2u32.saturating_pow(attempt - 1)
+    }
+
+    /// Perform a GET request, reusing the previous response if the resource didn't change.
+    ///
+    /// The `ETag` of every response is stored, and sent back in the `If-None-Match` header of
+    /// the following requests to the same URL. GitHub replies with a `304 Not Modified` if the
+    /// resource didn't change, which doesn't count against the rate limit.
+    ///
+    /// The responses are also stored in the on-disk cache, so that the following runs of the
+    /// tool can make conditional requests too.
+    fn get<T>(&self, require_auth: bool, url: &str) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let disk_key = self.absolute_url(url);
+        let cached = self.cache.lock().unwrap().get(url).cloned().or_else(|| {
+            self.disk_cache
+                .as_ref()
+                .and_then(|disk| disk.get("responses", &disk_key, None))
+        });
+
+        let mut req = self.prepare(require_auth, Method::GET, url)?;
+        if let Some(cached) = &cached {
+            req = req.header(header::IF_NONE_MATCH, cached.etag.as_str());
+        }
+        let resp = self.send(req)?;
+
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(serde_json::from_str(&cached.body)?);
+            }
+        }
+
+        let resp = resp.error_for_status()?;
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+        let etag = resp
+            .headers()
+            .get(header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(String::from);
+        let body = resp.text()?;
+        let parsed = serde_json::from_str(&body)?;
+        if let Some(etag) = etag {
+            let cached = CachedResponse { etag, body };
+            if let Some(disk) = &self.disk_cache {
+                disk.put("responses", &disk_key, &cached);
+            }
+            self.cache.lock().unwrap().insert(url.to_string(), cached);
+        }
+        Ok(parsed)
+    }
+
+    /// Perform a GraphQL request, returning the errors alongside the (possibly partial) data.
+    fn graphql_result<R, V>(&self, query: &str, variables: V) -> Result<GraphResult<R>, Error>
     where
         R: serde::de::DeserializeOwned,
         V: serde::Serialize,
@@ -84,19 +262,10 @@ impl GitHubApi {
             query: &'a str,
             variables: V,
         }
-        let res: GraphResult<R> = self
+        let req = self
             .prepare(true, Method::POST, "graphql")?
-            .json(&Request { query, variables })
-            .send()?
-            .error_for_status()?
-            .json()?;
-        if let Some(error) = res.errors.get(0) {
-            bail!("graphql error: {}", error.message);
-        } else if let Some(data) = res.data {
-            Ok(data)
-        } else {
-            bail!("missing graphql data");
-        }
+            .json(&Request { query, variables });
+        Ok(self.send(req)?.error_for_status()?.json()?)
     }
 
     pub(crate) fn require_auth(&self) -> Result<(), Error> {
@@ -107,19 +276,76 @@ impl GitHubApi {
     }
 
     pub(crate) fn user(&self, login: &str) -> Result<User, Error> {
-        Ok(self
-            .prepare(false, Method::GET, &format!("users/{}", login))?
-            .send()?
-            .error_for_status()?
-            .json()?)
+        self.get(false, &format!("users/{}", login))
     }
 
+    /// The current username of the users with the given IDs.
+    ///
+    /// Fails if one of the accounts can't be resolved anymore, which usually means it was
+    /// deleted.
     pub(crate) fn usernames(&self, ids: &[usize]) -> Result<HashMap<usize, String>, Error> {
+        self.accounts(ids)?
+            .into_iter()
+            .map(|(id, account)| match account {
+                Some(account) => Ok((id, account.login)),
+                None => bail!(
+                    "failed to resolve user id {}\n\
+                     Check if the user has possibly deleted their account.",
+                    id
+                ),
+            })
+            .collect()
+    }
+
+    /// The status of the accounts with the given IDs, or `None` for the accounts that can't be
+    /// resolved anymore (because they were deleted or suspended).
+    ///
+    /// The accounts are memoized for the whole run, so that multiple checks can look them up
+    /// without repeating the requests. The accounts fetched in the last [`ACCOUNTS_CACHE_TTL`]
+    /// are read from the on-disk cache.
+    pub(crate) fn accounts(&self, ids: &[usize]) -> Result<HashMap<usize, Option<Account>>, Error> {
+        let mut result = HashMap::new();
+        let mut missing = Vec::new();
+        {
+            let memo = self.accounts.lock().unwrap();
+            for &id in ids {
+                let cached = memo.get(&id).cloned().or_else(|| {
+                    self.disk_cache.as_ref().and_then(|disk| {
+                        disk.get("accounts", &id.to_string(), Some(ACCOUNTS_CACHE_TTL))
+                    })
+                });
+                match cached {
+                    Some(account) => {
+                        result.insert(id, account);
+                    }
+                    None => missing.push(id),
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let fetched = self.fetch_accounts(&missing)?;
+            if let Some(disk) = &self.disk_cache {
+                for (id, account) in &fetched {
+                    disk.put("accounts", &id.to_string(), account);
+                }
+            }
+            result.extend(fetched);
+        }
+        self.accounts
+            .lock()
+            .unwrap()
+            .extend(result.iter().map(|(id, account)| (*id, account.clone())));
+        Ok(result)
+    }
+
+    fn fetch_accounts(&self, ids: &[usize]) -> Result<HashMap<usize, Option<Account>>, Error> {
         #[derive(serde::Deserialize)]
         #[serde(rename_all = "camelCase")]
-        struct Usernames {
-            database_id: usize,
-            login: String,
+        struct Node {
+            #[serde(rename = "__typename")]
+            typename: String,
+            login: Option<String>,
         }
         #[derive(serde::Serialize)]
         struct Params {
@@ -128,68 +354,87 @@ impl GitHubApi {
         static QUERY: &str = "
             query($ids: [ID!]!) {
                 nodes(ids: $ids) {
+                    __typename
                     ... on User {
-                        databaseId
+                        login
+                    }
+                    ... on Organization {
                         login
                     }
                 }
             }
         ";
 
-        let cant_resolve = |e: &Error| e.to_string().contains("Could not resolve to a node");
-
-        let mut result = HashMap::new();
-        for chunk in ids.chunks(100) {
-            let res: GraphNodes<Usernames> = match self.graphql(
+        let fetch_chunk = |chunk: &[usize]| -> Result<Vec<(usize, Option<Account>)>, Error> {
+            let res: GraphResult<GraphNodes<Node>> = self.graphql_result(
                 QUERY,
                 Params {
                     ids: chunk.iter().map(|id| user_node_id(*id)).collect(),
                 },
-            ) {
-                Ok(res) => res,
-                Err(e) => {
-                    if cant_resolve(&e) {
-                        // This error happens when a user is deleted. Provide
-                        // a more helpful error message to pinpoint it:
-                        for id in chunk {
-                            if let Err(inner_e) = self.graphql::<GraphNodes<Usernames>, Params>(
-                                QUERY,
-                                Params {
-                                    ids: vec![user_node_id(*id)],
-                                },
-                            ) {
-                                if cant_resolve(&inner_e) {
-                                    bail!(
-                                        "failed to resolve user id {}: {}\n\
-                                        Check if the user has possibly deleted their account.",
-                                        id,
-                                        e
-                                    );
-                                } else {
-                                    bail!(
-                                        "failed to check resolve error: {}\n\
-                                        Original error: {}",
-                                        inner_e,
-                                        e
-                                    );
-                                }
-                            }
-                        }
-                    }
-                    return Err(e);
-                }
+            )?;
+            // Accounts that can't be resolved are returned as `null`, with a `NOT_FOUND` error.
+            if let Some(error) = res
+                .errors
+                .iter()
+                .find(|e| e.kind.as_deref() != Some("NOT_FOUND"))
+            {
+                bail!("graphql error: {}", error.message);
+            }
+            let nodes = match res.data {
+                Some(data) if data.nodes.len() == chunk.len() => data.nodes,
+                Some(_) => bail!("GitHub returned an unexpected number of accounts"),
+                None => bail!("missing graphql data"),
             };
-            for node in res.nodes.into_iter().flatten() {
-                result.insert(node.database_id, node.login);
+            Ok(chunk
+                .iter()
+                .zip(nodes)
+                .map(|(id, node)| {
+                    let account = node.and_then(|node| {
+                        Some(Account {
+                            login: node.login?,
+                            kind: node.typename,
+                        })
+                    });
+                    (*id, account)
+                })
+                .collect())
+        };
+
+        // Fetch the batches concurrently, but with a bounded number of requests in flight to
+        // avoid hitting GitHub's secondary rate limits.
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(USERNAMES_CONCURRENCY)
+            .build()?;
+        let chunks = ids.chunks(USERNAMES_BATCH_SIZE).collect::<Vec<_>>();
+        let batches = pool.install(|| {
+            chunks
+                .par_iter()
+                .map(|chunk| fetch_chunk(chunk))
+                .collect::<Vec<_>>()
+        });
+
+        let mut result = HashMap::new();
+        let mut failures = Vec::new();
+        for batch in batches {
+            match batch {
+                Ok(accounts) => result.extend(accounts),
+                Err(err) => failures.push(err.to_string()),
             }
         }
+        if !failures.is_empty() {
+            bail!(
+                "failed to fetch {} out of {} batches of accounts:\n{}",
+                failures.len(),
+                chunks.len(),
+                failures.join("\n")
+            );
+        }
         Ok(result)
     }
 
     pub(crate) fn repo(&self, org: &str, repo: &str) -> Result<Option<Repo>, Error> {
-        let resp = self
-            .prepare(true, Method::GET, &format!("repos/{}/{}", org, repo))?
-            .send()?;
+        let req = self.prepare(true, Method::GET, &format!("repos/{}/{}", org, repo))?;
+        let resp = self.send(req)?;
         match resp.status() {
             reqwest::StatusCode::OK => Ok(Some(resp.json()?)),
             reqwest::StatusCode::NOT_FOUND => Ok(None),
@@ -197,22 +442,138 @@ impl GitHubApi {
         }
     }
 
+    /// The status on GitHub of each of the `(org, name)` repos, or `None` for the repos that
+    /// don't exist (or that the token can't access).
+    ///
+    /// The repos are looked up in batches with GraphQL, which follows renames: the status of a
+    /// renamed repo has its new name.
+    pub(crate) fn repo_statuses(
+        &self,
+        repos: &[(&str, &str)],
+    ) -> Result<Vec<Option<RepoStatus>>, Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Node {
+            name_with_owner: String,
+            is_archived: bool,
+        }
+
+        let mut statuses = Vec::with_capacity(repos.len());
+        for chunk in repos.chunks(REPOS_BATCH_SIZE) {
+            let mut query = String::from("query {\n");
+            for (i, (org, name)) in chunk.iter().enumerate() {
+                query.push_str(&format!(
+                    "  repo{}: repository(owner: {}, name: {}) {{ nameWithOwner isArchived }}\n",
+                    i,
+                    serde_json::to_string(org)?,
+                    serde_json::to_string(name)?
+                ));
+            }
+            query.push('}');
+
+            let res: GraphResult<HashMap<String, Option<Node>>> =
+                self.graphql_result(&query, serde_json::json!({}))?;
+            // Missing repos are reported as `NOT_FOUND` errors, with a `null` node.
+            if let Some(error) = res
+                .errors
+                .iter()
+                .find(|e| e.kind.as_deref() != Some("NOT_FOUND"))
+            {
+                bail!("graphql error: {}", error.message);
+            }
+            let mut nodes = match res.data {
+                Some(nodes) => nodes,
+                None => bail!("missing graphql data"),
+            };
+            for i in 0..chunk.len() {
+                statuses.push(nodes.remove(&format!("repo{}", i)).flatten().map(|node| {
+                    RepoStatus {
+                        name_with_owner: node.name_with_owner,
+                        archived: node.is_archived,
+                    }
+                }));
+            }
+        }
+        Ok(statuses)
+    }
+
+    /// The names of the check runs and commit statuses reported on the given git reference.
+    ///
+    /// Returns an empty set if the repo is empty or the reference doesn't exist.
+    pub(crate) fn check_names(
+        &self,
+        org: &str,
+        repo: &str,
+        reference: &str,
+    ) -> Result<HashSet<String>, Error> {
+        #[derive(serde::Deserialize)]
+        struct CheckRuns {
+            check_runs: Vec<CheckRun>,
+        }
+        #[derive(serde::Deserialize)]
+        struct CheckRun {
+            name: String,
+        }
+        #[derive(serde::Deserialize)]
+        struct CombinedStatus {
+            statuses: Vec<Status>,
+        }
+        #[derive(serde::Deserialize)]
+        struct Status {
+            context: String,
+        }
+
+        let req = self.prepare(
+            true,
+            Method::GET,
+            &format!(
+                "repos/{}/{}/commits/{}/check-runs?per_page=100",
+                org, repo, reference
+            ),
+        )?;
+        let resp = self.send(req)?;
+        let check_runs: CheckRuns = match resp.status() {
+            // GitHub replies with 409 Conflict for empty repos.
+            StatusCode::NOT_FOUND | StatusCode::CONFLICT | StatusCode::UNPROCESSABLE_ENTITY => {
+                return Ok(HashSet::new())
+            }
+            _ => resp.error_for_status()?.json()?,
+        };
+        let status: CombinedStatus = self.get(
+            true,
+            &format!("repos/{}/{}/commits/{}/status", org, repo, reference),
+        )?;
+
+        Ok(check_runs
+            .check_runs
+            .into_iter()
+            .map(|run| run.name)
+            .chain(status.statuses.into_iter().map(|status| status.context))
+            .collect())
+    }
+
+    /// The webhooks of the repo, or `None` if the token can't access them.
+    pub(crate) fn repo_hooks(&self, org: &str, repo: &str) -> Result<Option<Vec<Hook>>, Error> {
+        let req = self.prepare(
+            true,
+            Method::GET,
+            &format!("repos/{}/{}/hooks?per_page=100", org, repo),
+        )?;
+        let resp = self.send(req)?;
+        match resp.status() {
+            StatusCode::NOT_FOUND | StatusCode::FORBIDDEN => Ok(None),
+            _ => Ok(Some(resp.error_for_status()?.json()?)),
+        }
+    }
+
     /// Get all teams for the rust-lang org
     pub(crate) fn teams(&self) -> Result<Vec<GitHubTeam>, Error> {
-        Ok(self
-            .prepare(true, Method::GET, "orgs/rust-lang/teams?per_page=100")?
-            .send()?
-            .error_for_status()?
-            .json()?)
+        self.get(true, "orgs/rust-lang/teams?per_page=100")
     }
 
     /// Get all users who have not yet accepted the invitation
     pub(crate) fn pending_org_invites(&self) -> Result<Vec<User>, Error> {
-        Ok(self
-            .prepare(true, Method::GET, "orgs/rust-lang/invitations?per_page=100")?
-            .send()?
-            .error_for_status()?
-            .json()?)
+        self.get(true, "orgs/rust-lang/invitations?per_page=100")
     }
 
     /// Get all team members for the team with the given id
@@ -220,15 +581,10 @@ impl GitHubApi {
         let mut members = Vec::new();
         let mut page_num = 1;
         loop {
-            let page: Vec<GitHubMember> = self
-                .prepare(
-                    true,
-                    Method::GET,
-                    &format!("teams/{}/members?per_page=100&page={}", id, page_num),
-                )?
-                .send()?
-                .error_for_status()?
-                .json()?;
+            let page: Vec<GitHubMember> = self.get(
+                true,
+                &format!("teams/{}/members?per_page=100&page={}", id, page_num),
+            )?;
             let len = page.len();
             members.extend(page);
             if len < 100 {
@@ -239,11 +595,53 @@ impl GitHubApi {
         Ok(members)
     }
 
+    /// The outside collaborators of the repo, who have access to it without being members of
+    /// the org, or `None` if the repo doesn't exist.
+    pub(crate) fn outside_collaborators(
+        &self,
+        org: &str,
+        repo: &str,
+    ) -> Result<Option<Vec<RepoCollaborator>>, Error> {
+        self.get_all_pages(&format!(
+            "repos/{}/{}/collaborators?affiliation=outside",
+            org, repo
+        ))
+    }
+
+    /// The members of the org, or `None` if the org doesn't exist.
+    pub(crate) fn org_members(&self, org: &str) -> Result<Option<Vec<GitHubMember>>, Error> {
+        self.get_all_pages(&format!("orgs/{}/members?filter=all", org))
+    }
+
+    /// Get all the pages of a list, or `None` if the list doesn't exist. The url must already
+    /// have a query string.
+    fn get_all_pages<T>(&self, url: &str) -> Result<Option<Vec<T>>, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut items = Vec::new();
+        for page_num in 1.. {
+            let req = self.prepare(
+                true,
+                Method::GET,
+                &format!("{}&per_page=100&page={}", url, page_num),
+            )?;
+            let resp = self.send(req)?;
+            if resp.status() == StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            let page: Vec<T> = resp.error_for_status()?.json()?;
+            let len = page.len();
+            items.extend(page);
+            if len < 100 {
+                break;
+            }
+        }
+        Ok(Some(items))
+    }
+
     pub(crate) fn repo_teams(&self, org: &str, repo: &str) -> Result<Vec<Team>, Error> {
-        let resp = self
-            .prepare(true, Method::GET, &format!("repos/{}/{}/teams", org, repo))?
-            .send()?;
-        Ok(resp.error_for_status()?.json()?)
+        self.get(true, &format!("repos/{}/{}/teams", org, repo))
     }
 
     pub(crate) fn repo_collaborators(
@@ -251,26 +649,17 @@ impl GitHubApi {
         org: &str,
         repo: &str,
     ) -> Result<Vec<RepoCollaborator>, Error> {
-        let resp = self
-            .prepare(
-                true,
-                Method::GET,
-                &format!("repos/{org}/{repo}/collaborators?affiliation=direct"),
-            )?
-            .send()?;
-
-        Ok(resp.error_for_status()?.json()?)
+        self.get(
+            true,
+            &format!("repos/{org}/{repo}/collaborators?affiliation=direct"),
+        )
     }
 
     pub(crate) fn protected_branches(&self, org: &str, repo: &str) -> Result<Vec<Branch>, Error> {
-        let resp = self
-            .prepare(
-                true,
-                Method::GET,
-                &format!("repos/{}/{}/branches?protected=true", org, repo),
-            )?
-            .send()?;
-        Ok(resp.error_for_status()?.json()?)
+        self.get(
+            true,
+            &format!("repos/{}/{}/branches?protected=true", org, repo),
+        )
     }
 
     pub(crate) fn branch_protection(
@@ -279,14 +668,10 @@ impl GitHubApi {
         repo: &str,
         branch: &str,
     ) -> Result<BranchProtection, Error> {
-        let resp = self
-            .prepare(
-                true,
-                Method::GET,
-                &format!("repos/{}/{}/branches/{}/protection", org, repo, branch),
-            )?
-            .send()?;
-        Ok(resp.error_for_status()?.json()?)
+        self.get(
+            true,
+            &format!("repos/{}/{}/branches/{}/protection", org, repo, branch),
+        )
     }
 }
 
@@ -294,6 +679,14 @@ fn user_node_id(id: usize) -> String {
     base64::encode(format!("04:User{}", id))
 }
 
+/// The status of a GitHub account, returned by [`GitHubApi::accounts`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Account {
+    pub(crate) login: String,
+    /// The type of the account, like `User` or `Organization`.
+    pub(crate) kind: String,
+}
+
 #[derive(Debug, serde::Deserialize)]
 pub(crate) struct GitHubTeam {
     pub(crate) id: usize,
@@ -310,6 +703,26 @@ pub(crate) struct GitHubMember {
 #[derive(serde::Deserialize, Debug)]
 pub(crate) struct Repo {
     pub(crate) description: Option<String>,
+    pub(crate) default_branch: String,
+}
+
+/// The status of a repository on GitHub, returned by `GitHubApi::repo_statuses`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct RepoStatus {
+    /// The current `org/name` of the repo.
+    pub(crate) name_with_owner: String,
+    pub(crate) archived: bool,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct Hook {
+    pub(crate) config: HookConfig,
+    pub(crate) events: Vec<String>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct HookConfig {
+    pub(crate) url: Option<String>,
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -330,13 +743,6 @@ impl Permission {
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Eq, PartialEq, Copy, Clone)]
-#[serde(rename_all = "snake_case")]
-pub(crate) enum TeamPrivacy {
-    Closed,
-    Secret,
-}
-
 #[derive(serde::Deserialize, Debug)]
 pub(crate) struct Branch {
     pub(crate) name: String,
@@ -373,6 +779,25 @@ pub(crate) struct Permissions {
     admin: bool,
 }
 
+/// Whether the response is an error that might go away if the request is retried: server errors,
+/// and the primary and secondary rate limits.
+fn is_transient(resp: &Response) -> bool {
+    let status = resp.status();
+    let rate_limited = status == StatusCode::FORBIDDEN
+        && (resp.headers().contains_key(header::RETRY_AFTER)
+            || resp
+                .headers()
+                .get("x-ratelimit-remaining")
+                .is_some_and(|remaining| remaining == "0"));
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS || rate_limited
+}
+
+/// The delay requested by the `Retry-After` header, if it's a number of seconds.
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let seconds = resp.headers().get(header::RETRY_AFTER)?.to_str().ok()?;
+    Some(Duration::from_secs(seconds.trim().parse().ok()?))
+}
+
 impl Permissions {
     pub(crate) fn highest(&self) -> &str {
         if self.admin {
@@ -388,3 +813,239 @@ impl Permissions {
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serve the provided responses, one per connection, and return the received requests.
+    pub(crate) fn serve(responses: Vec<String>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                    request.push_str(&line.to_lowercase());
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_conditional_requests() -> Result<(), Error> {
+        let body = r#"{"id":1,"login":"ghost","name":null,"email":null}"#;
+        let ok = format!(
+            "HTTP/1.1 200 OK\r\netag: \"abc\"\r\ncontent-length: {}\r\n\
+             connection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let not_modified =
+            "HTTP/1.1 304 Not Modified\r\netag: \"abc\"\r\nconnection: close\r\n\r\n".to_string();
+        let (url, server) = serve(vec![ok, not_modified]);
+
+        let github = GitHubApi::mocked(url);
+        let first = github.user("ghost")?;
+        let second = github.user("ghost")?;
+        assert_eq!((first.id, first.login), (second.id, second.login));
+        assert_eq!(github.cache_stats(), (1, 1));
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"abc\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_disk_cache() -> Result<(), Error> {
+        let body = r#"{"data": {"nodes": [{"__typename": "User", "login": "ghost"}]}}"#;
+        let ok = format!(
+            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let (url, server) = serve(vec![ok]);
+        let dir = std::env::temp_dir().join(format!("rust-team-github-{}", std::process::id()));
+
+        // The second instance reads the username from the cache written by the first one,
+        // without sending any request.
+        for _ in 0..2 {
+            let mut github = GitHubApi::mocked(url.clone());
+            github.disk_cache = Some(DiskCache::new(dir.clone()));
+            let usernames = github.usernames(&[1])?;
+            assert_eq!(usernames.get(&1).map(|s| s.as_str()), Some("ghost"));
+        }
+        assert_eq!(server.join().unwrap().len(), 1);
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_accounts() -> Result<(), Error> {
+        let body = r#"{
+            "data": {
+                "nodes": [
+                    {"__typename": "User", "login": "ghost"},
+                    null,
+                    {"__typename": "Organization", "login": "org"}
+                ]
+            },
+            "errors": [{"type": "NOT_FOUND", "message": "Could not resolve to a node"}]
+        }"#;
+        let ok = format!(
+            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let (url, server) = serve(vec![ok]);
+
+        let github = GitHubApi::mocked(url);
+        let accounts = github.accounts(&[1, 2, 3])?;
+        assert_eq!(accounts[&1].as_ref().map(|a| a.kind.as_str()), Some("User"));
+        assert_eq!(accounts[&2], None);
+        assert_eq!(
+            accounts[&3].as_ref().map(|a| a.kind.as_str()),
+            Some("Organization")
+        );
+
+        // The accounts are memoized, and the unresolved ones are an error of the usernames.
+        let usernames = github.usernames(&[1])?;
+        assert_eq!(usernames[&1], "ghost");
+        let err = github.usernames(&[1, 2]).unwrap_err().to_string();
+        assert!(err.starts_with("failed to resolve user id 2\n"), "{}", err);
+        assert_eq!(server.join().unwrap().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_outside_collaborators() -> Result<(), Error> {
+        let page = |logins: Vec<String>| {
+            let body = serde_json::to_string(
+                &logins
+                    .iter()
+                    .map(|login| {
+                        serde_json::json!({
+                            "login": login,
+                            "permissions": {
+                                "triage": true,
+                                "push": true,
+                                "maintain": false,
+                                "admin": false,
+                            },
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+            format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        };
+        let not_found = "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+        let (url, server) = serve(vec![
+            page((0..100).map(|i| format!("user-{}", i)).collect()),
+            page(vec!["last".into()]),
+            not_found.to_string(),
+        ]);
+
+        let github = GitHubApi::mocked(url);
+        let collaborators = github.outside_collaborators("org", "repo")?.unwrap();
+        assert_eq!(collaborators.len(), 101);
+        assert_eq!(collaborators[100].name, "last");
+        assert_eq!(collaborators[100].permissions.highest(), "write");
+        assert!(github.outside_collaborators("org", "missing")?.is_none());
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].contains("affiliation=outside&per_page=100&page=1 "));
+        assert!(requests[1].contains("affiliation=outside&per_page=100&page=2 "));
+        Ok(())
+    }
+
+    #[test]
+    fn test_repo_statuses() -> Result<(), Error> {
+        let body = r#"{
+            "data": {
+                "repo0": {"nameWithOwner": "org/renamed", "isArchived": false},
+                "repo1": null,
+                "repo2": {"nameWithOwner": "org/old", "isArchived": true}
+            },
+            "errors": [{"type": "NOT_FOUND", "message": "Could not resolve to a Repository"}]
+        }"#;
+        let ok = format!(
+            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let (url, server) = serve(vec![ok]);
+
+        let github = GitHubApi::mocked(url);
+        let statuses = github.repo_statuses(&[("org", "name"), ("org", "gone"), ("org", "old")])?;
+        assert_eq!(
+            statuses,
+            vec![
+                Some(RepoStatus {
+                    name_with_owner: "org/renamed".into(),
+                    archived: false
+                }),
+                None,
+                Some(RepoStatus {
+                    name_with_owner: "org/old".into(),
+                    archived: true
+                }),
+            ]
+        );
+        assert!(server.join().unwrap()[0].starts_with("post /graphql"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_retries() -> Result<(), Error> {
+        let body = r#"{"id":1,"login":"ghost","name":null,"email":null}"#;
+        let ok = format!(
+            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let unavailable = "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\n\
+                           connection: close\r\n\r\n"
+            .to_string();
+        let rate_limited = "HTTP/1.1 403 Forbidden\r\nretry-after: 0\r\ncontent-length: 0\r\n\
+                            connection: close\r\n\r\n"
+            .to_string();
+
+        // Transient failures are retried transparently.
+        let (url, server) = serve(vec![unavailable.clone(), rate_limited, ok]);
+        let mut github = GitHubApi::mocked(url);
+        github.max_attempts = 3;
+        github.retry_base_delay = Duration::from_millis(1);
+        assert_eq!(github.user("ghost")?.login, "ghost");
+        assert_eq!(server.join().unwrap().len(), 3);
+
+        // Once all the attempts failed, the last error is returned.
+        let (url, server) = serve(vec![unavailable.clone(), unavailable]);
+        github.base_url = url;
+        github.max_attempts = 2;
+        let err = github.user("ghost").err().unwrap();
+        assert!(err.to_string().contains("503"), "{}", err);
+        assert_eq!(server.join().unwrap().len(), 2);
+        Ok(())
+    }
+}
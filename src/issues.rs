@@ -0,0 +1,76 @@
+//! Generation of Markdown issue drafts out of validation errors.
+//!
+//! Some errors can't be fixed in a single PR (for example people missing their Discord ID), and
+//! are better tracked with an issue for each affected team. Errors are attributed to the first
+//! team whose name is quoted in their message.
+
+use crate::data::Data;
+use crate::validate::{Report, ValidationError};
+use failure::{Error, ResultExt};
+use indexmap::IndexMap;
+use std::path::Path;
+
+/// The name of the draft for errors that couldn't be attributed to any team.
+const UNATTRIBUTED: &str = "unattributed";
+
+/// Write an issue draft for each team with errors of `check` in the report, returning how many
+/// drafts were written.
+pub(crate) fn export(
+    data: &Data,
+    report: &Report,
+    check: &str,
+    dest: &Path,
+) -> Result<usize, Error> {
+    let mut by_team: IndexMap<&str, Vec<&ValidationError>> = IndexMap::new();
+    for error in report.errors.iter().filter(|e| e.check == check) {
+        let team = attributed_team(data, &error.message).unwrap_or(UNATTRIBUTED);
+        by_team.entry(team).or_default().push(error);
+    }
+    by_team.sort_keys();
+
+    std::fs::create_dir_all(dest)
+        .with_context(|_| format!("failed to create {}", dest.display()))?;
+    for (team, errors) in &by_team {
+        let path = dest.join(format!("{}.md", team));
+        std::fs::write(&path, render(data, check, team, errors))
+            .with_context(|_| format!("failed to write {}", path.display()))?;
+    }
+    Ok(by_team.len())
+}
+
+/// The first team whose name is quoted in the message, either with backticks or quotes.
+fn attributed_team<'a>(data: &'a Data, message: &str) -> Option<&'a str> {
+    message
+        .split(['`', '"', '\''])
+        .skip(1)
+        .step_by(2)
+        .find_map(|quoted| data.team(quoted).map(|team| team.name()))
+}
+
+fn render(data: &Data, check: &str, team: &str, errors: &[&ValidationError]) -> String {
+    let mut draft = if team == UNATTRIBUTED {
+        format!(
+            "# Fix `{}` errors\n\nThe `{}` check found these problems:\n\n",
+            check, check
+        )
+    } else {
+        format!(
+            "# Fix `{}` errors in team `{}`\n\nThe `{}` check found these problems with the `{}` \
+             team:\n\n",
+            check, team, check, team
+        )
+    };
+    for error in errors {
+        draft.push_str(&format!("- {}\n", error.message));
+    }
+    draft.push_str("\n## How to fix\n\n");
+    match data.config().issue_fix_steps(check) {
+        Some(steps) => draft.push_str(steps.trim_end()),
+        None => draft.push_str(&format!(
+            "Update the data in this repository until `cargo run check --only {}` passes.",
+            check
+        )),
+    }
+    draft.push('\n');
+    draft
+}
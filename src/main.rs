@@ -1,38 +1,134 @@
 #![allow(clippy::new_ret_no_self, clippy::redundant_closure)]
 
+mod bisect;
+mod cache;
 mod data;
+mod diff;
+mod fix_usernames;
 #[macro_use]
 mod permissions;
 mod check_synced;
+mod codeowners;
 mod github;
+mod issues;
+mod query;
 mod schema;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 mod static_api;
 mod validate;
+mod why;
 mod zulip;
 
 const USER_AGENT: &str = "https://github.com/rust-lang/team (infra@rust-lang.org)";
 
 use data::Data;
-use schema::{Email, Team, TeamKind};
+use schema::{Email, Permissions, Team, TeamKind, ZulipGroupMember};
 
 use failure::{err_msg, Error};
+use indexmap::IndexMap;
 use log::{error, info, warn};
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf, str::FromStr};
 use structopt::StructOpt;
 
+#[derive(Debug, Clone, Copy)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            other => failure::bail!("unknown output format: {}", other),
+        }
+    }
+}
+
 #[derive(structopt::StructOpt)]
 #[structopt(name = "team", about = "manage the rust team members")]
+struct Opts {
+    #[structopt(
+        long = "data-dir",
+        env = "TEAM_DATA_DIR",
+        help = "the directory containing the data (defaults to the current directory)"
+    )]
+    data_dir: Option<PathBuf>,
+    #[structopt(
+        long = "overlay",
+        help = "experimental: a directory whose files replace or add to the ones in the data \
+                directory, with empty files deleting them"
+    )]
+    overlay: Option<PathBuf>,
+    #[structopt(
+        long = "cache",
+        help = "read and write the on-disk cache of the API responses"
+    )]
+    cache: bool,
+    #[structopt(
+        long = "refresh-cache",
+        help = "ignore the cached API responses, replacing them with fresh ones (implies --cache)"
+    )]
+    refresh_cache: bool,
+    #[structopt(subcommand)]
+    cli: Cli,
+}
+
+#[derive(structopt::StructOpt)]
 enum Cli {
     #[structopt(name = "check", help = "check if the configuration is correct")]
     Check {
-        #[structopt(long = "strict", help = "fail if optional checks are not executed")]
+        #[structopt(
+            long = "strict",
+            help = "fail if optional checks are not executed, and report the CI checks GitHub \
+                    never saw as errors"
+        )]
         strict: bool,
+        #[structopt(long = "warnings-as-errors", help = "fail if there are warnings")]
+        warnings_as_errors: bool,
+        #[structopt(
+            long = "fail-fast",
+            help = "stop at the first check reporting errors, without running the other ones"
+        )]
+        fail_fast: bool,
+        #[structopt(
+            long = "require",
+            multiple = true,
+            possible_values = &["github", "zulip"],
+            help = "fail if the checks relying on these APIs can't be executed (with --strict, \
+                    only these APIs are required instead of all of them)"
+        )]
+        require: Vec<crate::validate::Api>,
         #[structopt(
             long = "skip",
             multiple = true,
             help = "skip one or more validation steps"
         )]
         skip: Vec<String>,
+        #[structopt(
+            long = "only",
+            multiple = true,
+            help = "only run one or more validation steps (minus the skipped ones)"
+        )]
+        only: Vec<String>,
+        #[structopt(long = "list-checks", help = "list all the available validation steps")]
+        list_checks: bool,
+        #[structopt(
+            long = "format",
+            default_value = "text",
+            possible_values = &["text", "junit", "json"],
+            help = "the format to report the validation results in"
+        )]
+        format: crate::validate::ReportFormat,
+        #[structopt(
+            long = "write-stubs",
+            help = "print the `access.individuals` entries to add for the unknown outside collaborators"
+        )]
+        write_stubs: bool,
     },
     #[structopt(
         name = "add-person",
@@ -44,8 +140,20 @@ enum Cli {
         help = "add a new repo config from an existing GitHub repo"
     )]
     AddRepo { org: String, name: String },
+    #[structopt(
+        name = "fix-usernames",
+        help = "apply the GitHub username changes to the people, teams and repos"
+    )]
+    FixUsernames {
+        #[structopt(long = "force", help = "run even if the working tree is dirty")]
+        force: bool,
+    },
     #[structopt(name = "static-api", help = "generate the static API")]
-    StaticApi { dest: String },
+    StaticApi {
+        dest: String,
+        #[structopt(long = "gzip", help = "also write a gzip-compressed v1/all.json.gz")]
+        gzip: bool,
+    },
     #[structopt(name = "show-person", help = "print information about a person")]
     ShowPerson { github_username: String },
     #[structopt(name = "dump-teams", help = "Lists all teams")]
@@ -68,8 +176,20 @@ enum Cli {
         #[structopt(long = "only-leads", help = "whether to list only leads of the team")]
         only_leads: bool,
     },
-    #[structopt(name = "dump-team", help = "print the members of a team")]
-    DumpTeam { name: String },
+    #[structopt(
+        name = "dump-team",
+        help = "print a team with its members, lists, groups and permissions fully resolved"
+    )]
+    DumpTeam {
+        name: String,
+        #[structopt(
+            long = "format",
+            default_value = "text",
+            possible_values = &["text", "json"],
+            help = "the output format"
+        )]
+        format: OutputFormat,
+    },
     #[structopt(name = "dump-list", help = "print all the emails in a list")]
     DumpList { name: String },
     #[structopt(
@@ -91,6 +211,117 @@ enum Cli {
         help = "checked whether a particular resource is synced"
     )]
     CheckSynced,
+    #[structopt(
+        name = "diff",
+        help = "print the changes to members, lists, groups and permissions between two checkouts"
+    )]
+    Diff {
+        old_path: PathBuf,
+        new_path: PathBuf,
+        #[structopt(
+            long = "format",
+            default_value = "text",
+            possible_values = &["text", "json"],
+            help = "the output format"
+        )]
+        format: OutputFormat,
+    },
+    #[structopt(
+        name = "explain",
+        help = "explain how to fix the errors of a check, given its name or an error message"
+    )]
+    Explain { query: String },
+    #[structopt(
+        name = "bisect-check",
+        help = "find the first commit where the data started failing a check"
+    )]
+    BisectCheck {
+        check: String,
+        #[structopt(long = "good", help = "a commit where the check passes")]
+        good: String,
+        #[structopt(
+            long = "bad",
+            default_value = "HEAD",
+            help = "a commit where the check fails"
+        )]
+        bad: String,
+    },
+    #[structopt(
+        name = "why-member",
+        help = "explain why a person is a member of a team"
+    )]
+    WhyMember { person: String, team: String },
+    #[structopt(
+        name = "why-permission",
+        help = "explain why a person has a permission"
+    )]
+    WhyPermission { person: String, permission: String },
+    #[structopt(
+        name = "codeowners",
+        help = "print a CODEOWNERS file mirroring the access to a repo"
+    )]
+    Codeowners {
+        #[structopt(help = "the repo, as `org/name`")]
+        repo: String,
+        #[structopt(
+            long = "format",
+            default_value = "text",
+            possible_values = &["text", "json"],
+            help = "the output format"
+        )]
+        format: OutputFormat,
+    },
+    #[structopt(name = "report", help = "generate a report out of the data")]
+    Report(ReportCommand),
+    #[structopt(name = "export", help = "export the data in another form")]
+    Export(ExportCommand),
+}
+
+#[derive(structopt::StructOpt)]
+enum ExportCommand {
+    #[structopt(
+        name = "issues",
+        help = "write a Markdown issue draft for each team with errors of a check"
+    )]
+    Issues {
+        #[structopt(long = "check", help = "the check whose errors are exported")]
+        check: String,
+        #[structopt(
+            long = "dest",
+            default_value = "issues",
+            help = "the directory to write the drafts to"
+        )]
+        dest: PathBuf,
+    },
+    #[cfg(feature = "sqlite")]
+    #[structopt(
+        name = "sqlite",
+        help = "write all the data to a SQLite database for ad-hoc querying"
+    )]
+    Sqlite {
+        #[structopt(
+            long = "out",
+            default_value = "team.db",
+            help = "the database to write, replaced if it exists"
+        )]
+        out: PathBuf,
+    },
+}
+
+#[derive(structopt::StructOpt)]
+enum ReportCommand {
+    #[structopt(
+        name = "elections",
+        help = "list the teams with elections due in the next days"
+    )]
+    Elections {
+        #[structopt(
+            long = "days",
+            default_value = "30",
+            help = "how many days ahead to look for elections"
+        )]
+        days: u64,
+    },
 }
 
 fn main() {
@@ -114,17 +345,59 @@ fn main() {
 }
 
 fn run() -> Result<(), Error> {
-    let cli = Cli::from_args();
-    let data = Data::load()?;
+    let Opts {
+        data_dir,
+        overlay,
+        cache,
+        refresh_cache,
+        cli,
+    } = Opts::from_args();
+    if cache || refresh_cache {
+        crate::cache::enable();
+    }
+    if refresh_cache {
+        crate::cache::refresh();
+    }
+    let root = data_dir.unwrap_or_default();
+    let data = Data::load_overlaid(&root, overlay.as_deref())?;
+    // The commands editing the data would otherwise write to the base directory files that the
+    // overlay is shadowing.
+    let ensure_no_overlay = || match overlay {
+        Some(_) => Err(err_msg("this command can't be used with --overlay")),
+        None => Ok(()),
+    };
     match cli {
-        Cli::Check { strict, skip } => {
-            crate::validate::validate(
-                &data,
+        Cli::Check {
+            strict,
+            warnings_as_errors,
+            fail_fast,
+            require,
+            skip,
+            only,
+            list_checks,
+            format,
+            write_stubs,
+        } => {
+            if list_checks {
+                for name in crate::validate::check_names() {
+                    println!("{}", name);
+                }
+                return Ok(());
+            }
+            let skip = skip.iter().map(|s| s.as_ref()).collect::<Vec<_>>();
+            let only = only.iter().map(|s| s.as_ref()).collect::<Vec<_>>();
+            let options = crate::validate::CheckOptions {
                 strict,
-                &skip.iter().map(|s| s.as_ref()).collect::<Vec<_>>(),
-            )?;
+                fail_fast,
+                require: &require,
+                skip: &skip,
+                only: &only,
+                write_stubs,
+            };
+            crate::validate::validate(&data, &options, warnings_as_errors, format)?;
         }
         Cli::AddPerson { ref github_name } => {
+            ensure_no_overlay()?;
             #[derive(serde::Serialize)]
             #[serde(rename_all = "kebab-case")]
             struct PersonToAdd<'a> {
@@ -144,7 +417,7 @@ fn run() -> Result<(), Error> {
                 failure::bail!("person already in the repo: {}", github_name);
             }
 
-            let file = format!("people/{}.toml", github_name);
+            let file = root.join("people").join(format!("{}.toml", github_name));
             std::fs::write(
                 &file,
                 toml::to_string_pretty(&PersonToAdd {
@@ -164,9 +437,10 @@ fn run() -> Result<(), Error> {
                 .as_bytes(),
             )?;
 
-            info!("written data to {}", file);
+            info!("written data to {}", file.display());
         }
         Cli::AddRepo { org, name } => {
+            ensure_no_overlay()?;
             #[derive(serde::Serialize, Debug)]
             #[serde(rename_all = "kebab-case")]
             struct AccessToAdd {
@@ -250,12 +524,17 @@ fn run() -> Result<(), Error> {
                 access: AccessToAdd { teams, individuals },
                 branch: branches,
             };
-            let file = format!("repos/{org}/{name}.toml");
+            let file = root.join("repos").join(&org).join(format!("{name}.toml"));
             std::fs::write(file, toml::to_string_pretty(&repo)?.as_bytes())?;
         }
-        Cli::StaticApi { ref dest } => {
+        Cli::FixUsernames { force } => {
+            ensure_no_overlay()?;
+            let github = github::GitHubApi::new();
+            crate::fix_usernames::fix_usernames(&data, &github, &root, force)?;
+        }
+        Cli::StaticApi { ref dest, gzip } => {
             let dest = PathBuf::from(dest);
-            let generator = crate::static_api::Generator::new(&dest, &data)?;
+            let generator = crate::static_api::Generator::new(&dest, &data)?.gzip(gzip);
             generator.generate()?;
         }
         Cli::ShowPerson {
@@ -279,13 +558,11 @@ fn run() -> Result<(), Error> {
 
             println!("teams:");
             let mut teams: Vec<_> = data
+                .query()
                 .teams()
-                .filter_map(|team| match team.contains_person(&data, person) {
-                    Ok(true) => Some(Ok(team)),
-                    Ok(false) => None,
-                    Err(e) => Some(Err(e)),
-                })
-                .collect::<Result<_, _>>()?;
+                .archived(false)
+                .with_member(person.github())?
+                .collect();
             teams.sort_by_key(|team| team.name());
             if teams.is_empty() {
                 println!("  (none)");
@@ -365,9 +642,20 @@ fn run() -> Result<(), Error> {
             }
         }
 
-        Cli::DumpTeam { ref name } => {
-            let team = data.team(name).ok_or_else(|| err_msg("unknown team"))?;
-            dump_team_members(team, &data, false, 0)?;
+        Cli::DumpTeam { ref name, format } => {
+            let team = data.team(name).ok_or_else(|| {
+                let suggestions = similar_names(name, data.teams().map(|team| team.name()));
+                if suggestions.is_empty() {
+                    err_msg(format!("unknown team: {}", name))
+                } else {
+                    err_msg(format!(
+                        "unknown team: {} (did you mean {}?)",
+                        name,
+                        suggestions.join(", ")
+                    ))
+                }
+            })?;
+            dump_team(team, &data, format)?;
         }
         Cli::DumpList { ref name } => {
             let list = data.list(name)?.ok_or_else(|| err_msg("unknown list"))?;
@@ -399,8 +687,10 @@ fn run() -> Result<(), Error> {
             if !crate::schema::Permissions::available(data.config()).contains(name) {
                 failure::bail!("unknown permission: {}", name);
             }
-            let mut allowed = crate::permissions::allowed_people(&data, name)?
-                .into_iter()
+            let mut allowed = data
+                .query()
+                .people()
+                .with_permission(name)?
                 .map(|person| person.github())
                 .collect::<Vec<_>>();
             allowed.sort_unstable();
@@ -433,11 +723,303 @@ fn run() -> Result<(), Error> {
             );
         }
         Cli::CheckSynced => check_synced::check(&data)?,
+        Cli::Export(ExportCommand::Issues {
+            ref check,
+            ref dest,
+        }) => {
+            let options = crate::validate::CheckOptions {
+                only: &[check.as_str()],
+                ..crate::validate::CheckOptions::default()
+            };
+            let report = crate::validate::run_checks(&data, &options)?;
+            let drafts = crate::issues::export(&data, &report, check, dest)?;
+            info!("written {} issue drafts to {}", drafts, dest.display());
+        }
+        #[cfg(feature = "sqlite")]
+        Cli::Export(ExportCommand::Sqlite { ref out }) => {
+            crate::sqlite::export(&data, out)?;
+            info!("written the data to {}", out.display());
+        }
+        Cli::Diff {
+            ref old_path,
+            ref new_path,
+            format,
+        } => {
+            let old = Data::load_from(old_path)?;
+            let new = Data::load_from(new_path)?;
+            let changes = crate::diff::diff(&old, &new)?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&changes)?),
+                OutputFormat::Text => {
+                    if changes.is_empty() {
+                        println!("no changes");
+                    }
+                    for change in &changes {
+                        println!("{}", change);
+                    }
+                }
+            }
+        }
+        Cli::Explain { ref query } => {
+            crate::validate::explain(&data, query)?;
+        }
+        Cli::WhyMember {
+            ref person,
+            ref team,
+        } => {
+            let why = crate::why::why_member(&data, person, team)?;
+            if why.membership.is_empty() {
+                println!("{} is not a member of team {}", person, team);
+            } else {
+                println!("{} is a member of team {}:", person, team);
+                for chain in &why.membership {
+                    println!("- {}", chain.join(", and "));
+                }
+            }
+            for extra in &why.extra {
+                println!("- {}", extra);
+            }
+        }
+        Cli::WhyPermission {
+            ref person,
+            ref permission,
+        } => {
+            let chains = crate::why::why_permission(&data, person, permission)?;
+            if chains.is_empty() {
+                println!("{} doesn't have permission {}", person, permission);
+            } else {
+                println!("{} has permission {}:", person, permission);
+                for chain in &chains {
+                    println!("- {}", chain.join(", and "));
+                }
+            }
+        }
+        Cli::Codeowners { ref repo, format } => {
+            let rules = crate::codeowners::codeowners(&data, repo)?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&rules)?),
+                OutputFormat::Text => print!("{}", crate::codeowners::render(repo, &rules)),
+            }
+        }
+        Cli::BisectCheck {
+            ref check,
+            ref good,
+            ref bad,
+        } => {
+            ensure_no_overlay()?;
+            let bisection = crate::bisect::bisect_check(&root, check, good, bad)?;
+            if !bisection.unknown.is_empty() {
+                println!(
+                    "the data couldn't be loaded at these commits, any of them might be the first \
+                     one failing the check:"
+                );
+                for commit in &bisection.unknown {
+                    println!("  {}", commit);
+                }
+            }
+            println!("first commit failing {}: {}", check, bisection.first_bad);
+            for error in &bisection.errors {
+                println!("  {}", error);
+            }
+        }
+        Cli::Report(ReportCommand::Elections { days }) => {
+            let today = chrono::Local::now().date_naive();
+            let until = today + chrono::Days::new(days);
+            let mut due = Vec::new();
+            for team in data.teams() {
+                if let Some(election) = team.election() {
+                    let next_election = election.next_election()?;
+                    if next_election <= until {
+                        due.push((next_election, team, election));
+                    }
+                }
+            }
+            due.sort_by_key(|(date, team, _)| (*date, team.name()));
+            if due.is_empty() {
+                println!("no elections due in the next {} days", days);
+            }
+            for (date, team, election) in due {
+                println!(
+                    "{}: {} (term of {} months, electorate: {}){}",
+                    date,
+                    team.name(),
+                    election.term_months,
+                    election.electorate,
+                    if date < today { " [overdue]" } else { "" }
+                );
+            }
+        }
     }
 
     Ok(())
 }
 
+/// The names among `candidates` close enough to `name` to likely be what the user meant, closest
+/// first.
+pub(crate) fn similar_names<'a>(
+    name: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let max_distance = std::cmp::max(2, name.len() / 3);
+    let mut similar = candidates
+        .map(|candidate| (strsim::levenshtein(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    similar.sort_unstable();
+    similar.into_iter().take(3).map(|(_, name)| name).collect()
+}
+
+fn dump_team(team: &Team, data: &Data, format: OutputFormat) -> Result<(), Error> {
+    #[derive(serde::Serialize)]
+    struct ResolvedTeam<'a> {
+        name: &'a str,
+        kind: String,
+        subteam_of: Option<&'a str>,
+        members: Vec<ResolvedMember<'a>>,
+        leads: Vec<&'a str>,
+        alumni: Vec<&'a str>,
+        github_teams: Vec<String>,
+        zulip_groups: IndexMap<String, Vec<String>>,
+        lists: IndexMap<String, Vec<String>>,
+        permissions: Vec<String>,
+        leads_permissions: Vec<String>,
+        inherited_permissions: IndexMap<&'a str, Vec<String>>,
+    }
+    #[derive(serde::Serialize)]
+    struct ResolvedMember<'a> {
+        github: &'a str,
+        // Either "explicit" or "included".
+        source: &'static str,
+    }
+
+    let granted = |permissions: &Permissions| {
+        Permissions::available(data.config())
+            .into_iter()
+            .filter(|permission| permissions.has(permission))
+            .collect::<Vec<_>>()
+    };
+
+    let mut members = team
+        .members(data)?
+        .into_iter()
+        .map(|github| ResolvedMember {
+            github,
+            source: if team.explicit_members().iter().any(|m| m == github) {
+                "explicit"
+            } else {
+                "included"
+            },
+        })
+        .collect::<Vec<_>>();
+    members.sort_unstable_by_key(|member| member.github);
+    let mut leads = team.leads().into_iter().collect::<Vec<_>>();
+    leads.sort_unstable();
+    let mut alumni = team.alumni().iter().map(|a| a.as_str()).collect::<Vec<_>>();
+    alumni.sort_unstable();
+
+    let mut zulip_groups = IndexMap::new();
+    for group in team.zulip_groups(data)? {
+        let mut members = group
+            .members()
+            .iter()
+            .map(|member| match member {
+                ZulipGroupMember::MemberWithId { github, zulip_id } => {
+                    format!("{} ({})", github, zulip_id)
+                }
+                ZulipGroupMember::JustId(zulip_id) => format!("#{}", zulip_id),
+                ZulipGroupMember::MemberWithoutId { github } => {
+                    format!("{} (no Zulip ID)", github)
+                }
+            })
+            .collect::<Vec<_>>();
+        members.sort_unstable();
+        zulip_groups.insert(group.name().to_string(), members);
+    }
+
+    let mut lists = IndexMap::new();
+    for list in team.lists(data)? {
+        let mut emails = list.emails().to_vec();
+        emails.sort_unstable();
+        lists.insert(list.address().to_string(), emails);
+    }
+
+    let mut inherited_permissions = IndexMap::new();
+    let mut parent = team.subteam_of();
+    while let Some(parent_name) = parent {
+        let parent_team = data
+            .team(parent_name)
+            .ok_or_else(|| err_msg(format!("missing parent team {}", parent_name)))?;
+        inherited_permissions.insert(parent_team.name(), granted(parent_team.permissions()));
+        parent = parent_team.subteam_of();
+    }
+
+    let resolved = ResolvedTeam {
+        name: team.name(),
+        kind: team.kind().to_string(),
+        subteam_of: team.subteam_of(),
+        members,
+        leads,
+        alumni,
+        github_teams: team
+            .github_teams(data)?
+            .iter()
+            .map(|github| format!("{}/{}", github.org, github.name))
+            .collect(),
+        zulip_groups,
+        lists,
+        permissions: granted(team.permissions()),
+        leads_permissions: granted(team.leads_permissions()),
+        inherited_permissions,
+    };
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&resolved)?),
+        OutputFormat::Text => {
+            fn section<T: std::fmt::Display>(title: &str, items: &[T]) {
+                println!("{}:", title);
+                if items.is_empty() {
+                    println!("  (none)");
+                }
+                for item in items {
+                    println!("  - {}", item);
+                }
+            }
+
+            println!("-- {} ({}) --", resolved.name, resolved.kind);
+            if let Some(parent) = resolved.subteam_of {
+                println!("parent team: {}", parent);
+            }
+            println!();
+            section(
+                "members",
+                &resolved
+                    .members
+                    .iter()
+                    .map(|member| format!("{} ({})", member.github, member.source))
+                    .collect::<Vec<_>>(),
+            );
+            section("leads", &resolved.leads);
+            section("alumni", &resolved.alumni);
+            section("github teams", &resolved.github_teams);
+            for (name, members) in &resolved.zulip_groups {
+                section(&format!("zulip group {}", name), members);
+            }
+            for (address, emails) in &resolved.lists {
+                section(&format!("list {}", address), emails);
+            }
+            section("permissions", &resolved.permissions);
+            section("leads permissions", &resolved.leads_permissions);
+            for (parent, permissions) in &resolved.inherited_permissions {
+                section(
+                    &format!("permissions inherited from {}", parent),
+                    permissions,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
 fn dump_team_members(
     team: &Team,
     data: &Data,
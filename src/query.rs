@@ -0,0 +1,311 @@
+//! Composable queries over the loaded [`Data`].
+//!
+//! Reports and checks often need things like "the active working groups `foo` is a member of".
+//! Rather than hand-rolling the loop every time, build a query with [`Data::query`] and narrow it
+//! down with the combinators below. Most combinators are lazy iterator adapters: nothing is
+//! evaluated until the query is iterated or collected.
+//!
+//! The combinators that need to resolve team members (`with_member`, `with_permission` and
+//! `in_team`) resolve them when they're called instead, and return an error if a team's members
+//! can't be resolved.
+//!
+//! The examples use the data from `tests::test_queries`, which runs the same assertions: rustdoc
+//! doesn't run doctests in a binary crate, so they're marked `ignore`.
+
+use crate::data::Data;
+use crate::permissions::allowed_people;
+use crate::schema::{Person, Team, TeamKind};
+use failure::Error;
+use std::collections::HashSet;
+
+/// Entry point of the query API, returned by [`Data::query`].
+#[derive(Copy, Clone)]
+pub(crate) struct Query<'a> {
+    data: &'a Data,
+}
+
+impl<'a> Query<'a> {
+    pub(crate) fn new(data: &'a Data) -> Self {
+        Query { data }
+    }
+
+    /// All the teams, both active and archived.
+    ///
+    /// ```ignore
+    /// let wgs = data
+    ///     .query()
+    ///     .teams()
+    ///     .kind(TeamKind::WorkingGroup)
+    ///     .with_member("foo")?
+    ///     .archived(false)
+    ///     .map(|team| team.name())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(wgs, ["wg-foo"]);
+    /// ```
+    pub(crate) fn teams(self) -> TeamQuery<'a, impl Iterator<Item = (&'a Team, bool)>> {
+        TeamQuery {
+            data: self.data,
+            iter: self
+                .data
+                .teams()
+                .map(|team| (team, false))
+                .chain(self.data.archived_teams().map(|team| (team, true))),
+        }
+    }
+
+    /// All the people.
+    ///
+    /// ```ignore
+    /// assert_eq!(data.query().people().count(), 2);
+    /// ```
+    pub(crate) fn people(self) -> PersonQuery<'a, impl Iterator<Item = &'a Person>> {
+        PersonQuery {
+            data: self.data,
+            iter: self.data.people(),
+        }
+    }
+}
+
+/// A query over teams, created by [`Query::teams`].
+///
+/// Iterating over it yields the matching teams.
+pub(crate) struct TeamQuery<'a, I> {
+    data: &'a Data,
+    // The boolean is whether the team is archived.
+    iter: I,
+}
+
+impl<'a, I> TeamQuery<'a, I>
+where
+    I: Iterator<Item = (&'a Team, bool)>,
+{
+    fn retain<F>(self, mut f: F) -> TeamQuery<'a, impl Iterator<Item = (&'a Team, bool)>>
+    where
+        F: FnMut(&'a Team, bool) -> bool,
+    {
+        TeamQuery {
+            data: self.data,
+            iter: self.iter.filter(move |&(team, archived)| f(team, archived)),
+        }
+    }
+
+    /// Only keep the teams of the given kind.
+    ///
+    /// ```ignore
+    /// assert_eq!(data.query().teams().kind(TeamKind::WorkingGroup).count(), 1);
+    /// ```
+    pub(crate) fn kind(
+        self,
+        kind: TeamKind,
+    ) -> TeamQuery<'a, impl Iterator<Item = (&'a Team, bool)>> {
+        self.retain(move |team, _| team.kind() == kind)
+    }
+
+    /// Only keep archived teams (`true`) or active teams (`false`).
+    ///
+    /// ```ignore
+    /// assert_eq!(data.query().teams().archived(true).count(), 0);
+    /// ```
+    pub(crate) fn archived(
+        self,
+        archived: bool,
+    ) -> TeamQuery<'a, impl Iterator<Item = (&'a Team, bool)>> {
+        self.retain(move |_, is_archived| is_archived == archived)
+    }
+
+    /// Only keep the teams the person with the given GitHub username is a member of.
+    ///
+    /// ```ignore
+    /// assert_eq!(data.query().teams().with_member("bar")?.count(), 1);
+    /// ```
+    pub(crate) fn with_member(
+        self,
+        github: &str,
+    ) -> Result<TeamQuery<'a, impl Iterator<Item = (&'a Team, bool)>>, Error> {
+        let mut teams = Vec::new();
+        for (team, archived) in self.iter {
+            if team.members(self.data)?.contains(github) {
+                teams.push((team, archived));
+            }
+        }
+        Ok(TeamQuery {
+            data: self.data,
+            iter: teams.into_iter(),
+        })
+    }
+}
+
+impl<'a, I> Iterator for TeamQuery<'a, I>
+where
+    I: Iterator<Item = (&'a Team, bool)>,
+{
+    type Item = &'a Team;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(team, _)| team)
+    }
+}
+
+/// A query over people, created by [`Query::people`].
+///
+/// Iterating over it yields the matching people.
+pub(crate) struct PersonQuery<'a, I> {
+    data: &'a Data,
+    iter: I,
+}
+
+impl<'a, I> PersonQuery<'a, I>
+where
+    I: Iterator<Item = &'a Person>,
+{
+    fn retain<F>(self, f: F) -> PersonQuery<'a, impl Iterator<Item = &'a Person>>
+    where
+        F: FnMut(&&'a Person) -> bool,
+    {
+        PersonQuery {
+            data: self.data,
+            iter: self.iter.filter(f),
+        }
+    }
+
+    /// Only keep the people with the given permission, either granted to them directly or
+    /// through one of the active teams they're a member (or lead) of.
+    ///
+    /// ```ignore
+    /// // `bar` through the leads of `team`, `foo` through `wg-foo`.
+    /// assert_eq!(data.query().people().with_permission("crater")?.count(), 2);
+    /// ```
+    pub(crate) fn with_permission(
+        self,
+        permission: &str,
+    ) -> Result<PersonQuery<'a, impl Iterator<Item = &'a Person>>, Error> {
+        let allowed: HashSet<&str> = allowed_people(self.data, permission)?
+            .into_iter()
+            .map(|person| person.github())
+            .collect();
+        Ok(self.retain(move |person| allowed.contains(person.github())))
+    }
+
+    /// Only keep the members of the team with the given name.
+    ///
+    /// ```ignore
+    /// assert_eq!(data.query().people().in_team("team")?.count(), 2);
+    /// assert_eq!(data.query().people().in_team("missing")?.count(), 0);
+    /// ```
+    pub(crate) fn in_team(
+        self,
+        team: &str,
+    ) -> Result<PersonQuery<'a, impl Iterator<Item = &'a Person>>, Error> {
+        let members = match self.data.team(team) {
+            Some(team) => team.members(self.data)?,
+            None => HashSet::new(),
+        };
+        Ok(self.retain(move |person| members.contains(person.github())))
+    }
+
+    /// Only keep the people with (`true`) or without (`false`) a Zulip ID.
+    ///
+    /// ```ignore
+    /// assert_eq!(data.query().people().has_zulip_id(false).count(), 1);
+    /// ```
+    pub(crate) fn has_zulip_id(
+        self,
+        has_zulip_id: bool,
+    ) -> PersonQuery<'a, impl Iterator<Item = &'a Person>> {
+        self.retain(move |person| person.zulip_id().is_some() == has_zulip_id)
+    }
+}
+
+impl<'a, I> Iterator for PersonQuery<'a, I>
+where
+    I: Iterator<Item = &'a Person>,
+{
+    type Item = &'a Person;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::{config, person, team};
+
+    #[test]
+    fn test_queries() -> Result<(), Error> {
+        let data = Data::from_toml(
+            &config("permissions-bools = ['crater', 'perf']\n"),
+            &[
+                &person("foo", 1, "email = 'foo@example.com'\nzulip-id = 1\n"),
+                &person("bar", 2, "[permissions]\nperf = true\n"),
+            ],
+            &[
+                &team(
+                    "wg-foo",
+                    &[],
+                    &["foo"],
+                    "kind = 'working-group'\n[permissions]\ncrater = true\n",
+                ),
+                &team(
+                    "team",
+                    &["bar"],
+                    &["foo", "bar"],
+                    "[leads-permissions]\ncrater = true\n",
+                ),
+            ],
+        );
+        fn names<'a>(people: impl Iterator<Item = &'a Person>) -> Vec<&'a str> {
+            let mut names = people.map(|p| p.github()).collect::<Vec<_>>();
+            names.sort_unstable();
+            names
+        }
+
+        let wgs = data
+            .query()
+            .teams()
+            .kind(TeamKind::WorkingGroup)
+            .with_member("foo")?
+            .archived(false)
+            .map(|team| team.name())
+            .collect::<Vec<_>>();
+        assert_eq!(wgs, ["wg-foo"]);
+        assert_eq!(data.query().teams().with_member("bar")?.count(), 1);
+        assert_eq!(data.query().teams().archived(true).count(), 0);
+        assert_eq!(data.query().teams().kind(TeamKind::WorkingGroup).count(), 1);
+        assert_eq!(data.query().people().count(), 2);
+
+        // Through a team, through the leads of a team, and directly.
+        assert_eq!(
+            names(data.query().people().with_permission("crater")?),
+            ["bar", "foo"]
+        );
+        assert_eq!(
+            names(data.query().people().with_permission("perf")?),
+            ["bar"]
+        );
+        assert_eq!(
+            names(data.query().people().in_team("team")?),
+            ["bar", "foo"]
+        );
+        assert_eq!(names(data.query().people().has_zulip_id(false)), ["bar"]);
+        assert_eq!(data.query().people().in_team("missing")?.count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_queries_report_unresolvable_members() {
+        let data = Data::from_toml(
+            &config(""),
+            &[&person("foo", 1, "")],
+            &[&team(
+                "team",
+                &[],
+                &["foo"],
+                "people.included-teams = ['missing']\n",
+            )],
+        );
+        assert!(data.query().teams().with_member("foo").is_err());
+        assert!(data.query().people().in_team("team").is_err());
+    }
+}
@@ -1,7 +1,11 @@
 use crate::data::Data;
 pub(crate) use crate::permissions::Permissions;
+use chrono::{Months, NaiveDate};
 use failure::{bail, err_msg, Error};
+use indexmap::IndexMap;
 use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
 
 #[derive(serde_derive::Deserialize, Debug)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
@@ -10,6 +14,34 @@ pub(crate) struct Config {
     allowed_github_orgs: HashSet<String>,
     permissions_bors_repos: HashSet<String>,
     permissions_bools: HashSet<String>,
+    #[serde(default)]
+    critical_repos: HashSet<String>,
+    #[serde(default)]
+    repos_without_admin: HashSet<String>,
+    #[serde(default)]
+    alumni_with_permissions: HashSet<String>,
+    list_extra_teams_max_missing_emails: Option<f64>,
+    draft_teams_max_age_days: Option<i64>,
+    #[serde(default)]
+    allowed_empty_teams: HashSet<String>,
+    #[serde(default)]
+    issue_fix_steps: HashMap<String, String>,
+    #[serde(default)]
+    custom_repo_roles: HashSet<String>,
+    #[serde(default)]
+    bot_webhooks: HashMap<String, BotWebhook>,
+    #[serde(default)]
+    org_branch_protection_defaults: HashMap<String, BranchProtectionDefaults>,
+    #[serde(default)]
+    allowed_roles: HashSet<String>,
+    #[serde(default)]
+    static_api_size_budgets: SizeBudgets,
+    #[serde(default)]
+    canonical_naming_exceptions: HashSet<String>,
+    #[serde(default)]
+    marker_teams_with_resources: HashSet<String>,
+    #[serde(default)]
+    project_groups_outside_teams: HashSet<String>,
 }
 
 impl Config {
@@ -28,6 +60,135 @@ impl Config {
     pub(crate) fn permissions_bools(&self) -> &HashSet<String> {
         &self.permissions_bools
     }
+
+    /// The maximum fraction of members without an email address a team can have to be included
+    /// in a list through `extra-teams`.
+    pub(crate) fn list_extra_teams_max_missing_emails(&self) -> f64 {
+        self.list_extra_teams_max_missing_emails.unwrap_or(0.25)
+    }
+
+    /// How long a team can stay a draft before it should be promoted or removed.
+    pub(crate) fn draft_teams_max_age_days(&self) -> i64 {
+        self.draft_teams_max_age_days.unwrap_or(90)
+    }
+
+    /// Teams that are allowed not to have any members.
+    pub(crate) fn allowed_empty_teams(&self) -> &HashSet<String> {
+        &self.allowed_empty_teams
+    }
+
+    /// Repos (in the `org/name` format) with stricter requirements on who can administer them.
+    pub(crate) fn critical_repos(&self) -> &HashSet<String> {
+        &self.critical_repos
+    }
+
+    /// Repos (in the `org/name` format) allowed not to grant admin access to anyone.
+    pub(crate) fn repos_without_admin(&self) -> &HashSet<String> {
+        &self.repos_without_admin
+    }
+
+    /// Alumni allowed to keep the permissions granted in their person file.
+    pub(crate) fn alumni_with_permissions(&self) -> &HashSet<String> {
+        &self.alumni_with_permissions
+    }
+
+    /// The roles people can have, independent of team membership.
+    pub(crate) fn allowed_roles(&self) -> &HashSet<String> {
+        &self.allowed_roles
+    }
+
+    /// The custom repository roles defined in the GitHub organizations.
+    pub(crate) fn custom_repo_roles(&self) -> &HashSet<String> {
+        &self.custom_repo_roles
+    }
+
+    /// The webhook the bot needs on the repos it's enabled on.
+    pub(crate) fn bot_webhook(&self, bot: &Bot) -> Option<&BotWebhook> {
+        self.bot_webhooks.get(bot.name())
+    }
+
+    /// The defaults of the branch protections of the repos in the org.
+    pub(crate) fn org_branch_protection_defaults(
+        &self,
+        org: &str,
+    ) -> Option<&BranchProtectionDefaults> {
+        self.org_branch_protection_defaults.get(org)
+    }
+
+    /// Teams using canonical naming that are allowed to have names differing from their slug.
+    pub(crate) fn canonical_naming_exceptions(&self) -> &HashSet<String> {
+        &self.canonical_naming_exceptions
+    }
+
+    /// Marker teams allowed to declare GitHub teams and mailing lists, as they predate the rule.
+    pub(crate) fn marker_teams_with_resources(&self) -> &HashSet<String> {
+        &self.marker_teams_with_resources
+    }
+
+    /// Project groups allowed to be subteams of something else than a team, as they predate the
+    /// rule.
+    pub(crate) fn project_groups_outside_teams(&self) -> &HashSet<String> {
+        &self.project_groups_outside_teams
+    }
+
+    /// The maximum sizes of the documents emitted by the static API.
+    pub(crate) fn static_api_size_budgets(&self) -> &SizeBudgets {
+        &self.static_api_size_budgets
+    }
+
+    /// The Markdown snippet explaining how to fix the errors of a check, used in issue drafts.
+    pub(crate) fn issue_fix_steps(&self, check: &str) -> Option<&str> {
+        self.issue_fix_steps.get(check).map(|s| s.as_str())
+    }
+}
+
+#[derive(serde_derive::Deserialize, Debug)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct BotWebhook {
+    pub(crate) url: Option<String>,
+    pub(crate) events: Vec<String>,
+}
+
+#[derive(serde_derive::Deserialize, Debug)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct BranchProtectionDefaults {
+    #[serde(default)]
+    pub(crate) ci_checks: Vec<String>,
+    #[serde(default)]
+    pub(crate) dismiss_stale_review: bool,
+}
+
+#[derive(serde_derive::Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct SizeBudgets {
+    #[serde(default)]
+    pub(crate) on_exceeded: BudgetAction,
+    /// The budget in bytes of each document, keyed by its path (like `v1/teams.json`).
+    #[serde(default)]
+    pub(crate) endpoints: HashMap<String, usize>,
+}
+
+#[derive(serde_derive::Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum BudgetAction {
+    #[default]
+    Error,
+    Warn,
+}
+
+/// Slugify the name like GitHub does for team names: lowercase, with every run of characters
+/// other than ASCII letters and digits replaced by a single dash.
+pub(crate) fn github_slug(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(slug.trim_end_matches('-').len());
+    slug
 }
 
 // This is an enum to allow two kinds of values for the email field:
@@ -62,9 +223,19 @@ pub(crate) struct Person {
     irc: Option<String>,
     #[serde(default)]
     email: EmailField,
+    list_email: Option<String>,
+    #[serde(default)]
+    infra_managed_email: bool,
     discord_id: Option<usize>,
     #[serde(default)]
     permissions: Permissions,
+    #[serde(default)]
+    roles: Vec<String>,
+    pronouns: Option<String>,
+    #[serde(skip)]
+    content_hash: String,
+    #[serde(skip)]
+    file_name: String,
 }
 
 impl Person {
@@ -93,7 +264,7 @@ impl Person {
         }
     }
 
-    pub(crate) fn email(&self) -> Email {
+    pub(crate) fn email(&self) -> Email<'_> {
         match &self.email {
             EmailField::Disabled(false) => Email::Disabled,
             EmailField::Disabled(true) => Email::Missing,
@@ -102,6 +273,27 @@ impl Person {
         }
     }
 
+    /// The email address subscribed to the mailing lists: the `list-email` if it's set, or the
+    /// public `email` otherwise.
+    pub(crate) fn list_email(&self) -> Email<'_> {
+        match (&self.list_email, self.email()) {
+            (_, Email::Disabled) => Email::Disabled,
+            (Some(addr), _) => Email::Present(addr.as_str()),
+            (None, email) => email,
+        }
+    }
+
+    /// Whether the person set a `list-email`, separate from the public `email`.
+    pub(crate) fn has_list_email(&self) -> bool {
+        self.list_email.is_some()
+    }
+
+    /// Whether the person's email address is managed by the infrastructure team, and thus allowed
+    /// to be on one of the mailing lists domains.
+    pub(crate) fn infra_managed_email(&self) -> bool {
+        self.infra_managed_email
+    }
+
     pub(crate) fn discord_id(&self) -> Option<usize> {
         self.discord_id
     }
@@ -110,17 +302,49 @@ impl Person {
         &self.permissions
     }
 
+    pub(crate) fn roles(&self) -> &[String] {
+        &self.roles
+    }
+
+    pub(crate) fn pronouns(&self) -> Option<&str> {
+        self.pronouns.as_deref()
+    }
+
+    pub(crate) fn content_hash(&self) -> &str {
+        &self.content_hash
+    }
+
+    pub(crate) fn set_content_hash(&mut self, content_hash: String) {
+        self.content_hash = content_hash;
+    }
+
+    /// The name of the file the person is defined in, without the `.toml` extension.
+    pub(crate) fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub(crate) fn set_file_name(&mut self, file_name: String) {
+        self.file_name = file_name;
+    }
+
     pub(crate) fn validate(&self) -> Result<(), Error> {
         if let EmailField::Disabled(true) = &self.email {
             bail!("`email = true` is not valid (for person {})", self.github);
         }
+        if let (EmailField::Disabled(false), Some(_)) = (&self.email, &self.list_email) {
+            bail!(
+                "`list-email` can't be set with `email = false` (for person {})",
+                self.github
+            );
+        }
         Ok(())
     }
 }
 
-#[derive(serde_derive::Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(serde_derive::Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum TeamKind {
+    #[default]
     Team,
     WorkingGroup,
     ProjectGroup,
@@ -142,19 +366,87 @@ impl std::fmt::Display for TeamKind {
     }
 }
 
-impl Default for TeamKind {
-    fn default() -> Self {
-        Self::Team
+/// Whether a team is shown on the website.
+#[derive(serde_derive::Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Visibility {
+    #[default]
+    Public,
+    /// Synced to GitHub and Zulip like any other team, but left out of the website-facing parts
+    /// of the static API.
+    Private,
+}
+
+/// Why the members of a team are members, recorded while resolving them.
+#[derive(Default)]
+struct Provenance<'a> {
+    /// The steps leading from the team being resolved to the included team currently resolved.
+    steps: Vec<String>,
+    /// Every chain of steps leading to each member.
+    chains: HashMap<&'a str, Vec<Vec<String>>>,
+}
+
+impl<'a> Provenance<'a> {
+    /// Enter an included team through the step, which must be popped once it's resolved.
+    fn enter(&mut self, step: String) -> &mut Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Record that the person is a member because of the step, after the current ones.
+    fn record(&mut self, person: &'a str, step: String) {
+        let mut chain = self.steps.clone();
+        chain.push(step);
+        self.chains.entry(person).or_default().push(chain);
+    }
+}
+
+/// The error resolving the members of a team that includes, possibly through other included
+/// teams, a team that doesn't exist.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct MissingIncludedTeam {
+    /// The chain of included teams, from the team being resolved to the missing one.
+    pub(crate) path: Vec<String>,
+}
+
+impl MissingIncludedTeam {
+    /// The team directly including the missing team, and the missing team: the same for every
+    /// team whose resolution fails because of it.
+    pub(crate) fn root_cause(&self) -> (&str, &str) {
+        match self.path.as_slice() {
+            [.., includer, missing] => (includer, missing),
+            _ => unreachable!("the path contains at least two teams"),
+        }
     }
 }
 
+impl fmt::Display for MissingIncludedTeam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (includer, missing) = self.root_cause();
+        write!(
+            f,
+            "team '{}' includes members from non-existent team '{}'",
+            includer, missing
+        )?;
+        if self.path.len() > 2 {
+            write!(f, " (through {})", self.path.join(" => "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MissingIncludedTeam {}
+
 #[derive(serde_derive::Deserialize, Debug)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub(crate) struct Team {
     name: String,
     #[serde(default)]
     kind: TeamKind,
+    #[serde(default)]
+    visibility: Visibility,
     subteam_of: Option<String>,
+    top_level: Option<bool>,
     people: TeamPeople,
     #[serde(default)]
     permissions: Permissions,
@@ -169,6 +461,19 @@ pub(crate) struct Team {
     #[serde(default)]
     zulip_groups: Vec<RawZulipGroup>,
     discord_roles: Option<Vec<DiscordRole>>,
+    election: Option<ElectionData>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    created_at: Option<toml::value::Datetime>,
+    #[serde(default)]
+    use_canonical_naming: bool,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    list_only_members: Vec<String>,
+    #[serde(skip)]
+    slug: String,
+    #[serde(skip)]
+    content_hash: String,
 }
 
 impl Team {
@@ -180,10 +485,82 @@ impl Team {
         self.kind
     }
 
+    /// Whether the team is left out of the website-facing parts of the static API.
+    pub(crate) fn is_private(&self) -> bool {
+        self.visibility == Visibility::Private
+    }
+
     pub(crate) fn subteam_of(&self) -> Option<&str> {
         self.subteam_of.as_deref()
     }
 
+    /// The canonical slug of the team, derived from its name like GitHub derives team slugs.
+    pub(crate) fn slug(&self) -> &str {
+        &self.slug
+    }
+
+    /// Whether the team is a draft, which is validated but not synced anywhere.
+    pub(crate) fn is_draft(&self) -> bool {
+        self.draft
+    }
+
+    /// Whether the GitHub team, Zulip group and Discord role names default to the slug.
+    pub(crate) fn uses_canonical_naming(&self) -> bool {
+        self.use_canonical_naming
+    }
+
+    /// The explicit `team-name`s of the team's GitHub teams.
+    pub(crate) fn github_team_names(&self) -> impl Iterator<Item = &str> {
+        self.github
+            .iter()
+            .filter_map(|github| github.team_name.as_deref())
+    }
+
+    /// The canonical name of the team's Zulip group.
+    pub(crate) fn canonical_zulip_group(&self) -> String {
+        format!("T-{}", self.slug)
+    }
+
+    /// Compute the slug of the team, and apply it to the names that weren't set explicitly when
+    /// the team uses canonical naming.
+    pub(crate) fn resolve_names(&mut self) -> Result<(), Error> {
+        self.slug = github_slug(&self.name);
+        let canonical_zulip_group = self.canonical_zulip_group();
+        for group in &mut self.zulip_groups {
+            group.name = match (&group.raw_name, self.use_canonical_naming) {
+                (Some(name), _) => name.clone(),
+                (None, true) => canonical_zulip_group.clone(),
+                (None, false) => bail!(
+                    "a Zulip group of team `{}` has no name (set it, or set \
+                     `use-canonical-naming = true`)",
+                    self.name
+                ),
+            };
+        }
+        for role in self.discord_roles.iter_mut().flatten() {
+            role.name = match (&role.raw_name, self.use_canonical_naming) {
+                (Some(name), _) => name.clone(),
+                (None, true) => self.slug.clone(),
+                (None, false) => bail!(
+                    "a Discord role of team `{}` has no name (set it, or set \
+                     `use-canonical-naming = true`)",
+                    self.name
+                ),
+            };
+        }
+        Ok(())
+    }
+
+    /// Whether the team is at the top of the hierarchy, defaulting to whether it has no parent.
+    pub(crate) fn top_level(&self) -> bool {
+        self.top_level.unwrap_or(self.subteam_of.is_none())
+    }
+
+    /// The `top-level` flag of the team, only if it was explicitly set.
+    pub(crate) fn explicit_top_level(&self) -> Option<bool> {
+        self.top_level
+    }
+
     // Return's whether the provided team is a subteam of this team
     pub(crate) fn is_parent_of<'a>(&'a self, data: &'a Data, subteam: &Team) -> bool {
         let mut visited = Vec::new();
@@ -230,36 +607,122 @@ impl Team {
         self.discord_roles.as_ref()
     }
 
+    pub(crate) fn election(&self) -> Option<&ElectionData> {
+        self.election.as_ref()
+    }
+
+    /// When the team was founded, if known.
+    pub(crate) fn created_at(&self) -> Result<Option<NaiveDate>, Error> {
+        self.created_at
+            .as_ref()
+            .map(|created_at| parse_date("created-at", created_at))
+            .transpose()
+    }
+
     pub(crate) fn members<'a>(&'a self, data: &'a Data) -> Result<HashSet<&'a str>, Error> {
+        self.members_inner(data, &mut Vec::new(), None)
+    }
+
+    /// Why the person is a member of the team: every chain of steps leading from this team to an
+    /// entry listing the person, or nothing if they aren't a member.
+    pub(crate) fn membership_provenance(
+        &self,
+        data: &Data,
+        person: &str,
+    ) -> Result<Vec<Vec<String>>, Error> {
+        let mut provenance = Provenance::default();
+        self.members_inner(data, &mut Vec::new(), Some(&mut provenance))?;
+        let mut chains = provenance.chains.remove(person).unwrap_or_default();
+        chains.sort();
+        chains.dedup();
+        Ok(chains)
+    }
+
+    /// Resolve the members of the team, where `path` is the chain of teams whose members are
+    /// currently being resolved, used to detect teams including each other. Why each member was
+    /// added is recorded in `provenance`, if any.
+    fn members_inner<'a>(
+        &'a self,
+        data: &'a Data,
+        path: &mut Vec<&'a str>,
+        mut provenance: Option<&mut Provenance<'a>>,
+    ) -> Result<HashSet<&'a str>, Error> {
+        if let Some(pos) = path.iter().position(|t| *t == self.name) {
+            bail!(
+                "teams include each other's members: {} => {}",
+                path[pos..].join(" => "),
+                self.name
+            );
+        }
+        path.push(&self.name);
+
         let mut members: HashSet<_> = self.people.members.iter().map(|s| s.as_str()).collect();
+        if let Some(provenance) = provenance.as_deref_mut() {
+            for member in &members {
+                provenance.record(
+                    member,
+                    format!(
+                        "`{}` is listed in the members of team `{}`",
+                        member, self.name
+                    ),
+                );
+            }
+        }
 
         for team in &self.people.included_teams {
-            let team = data.team(team).ok_or_else(|| {
-                err_msg(format!(
-                    "team '{}' includes members from non-existent team '{}'",
-                    self.name(),
-                    team
-                ))
+            let team = data.team(team).ok_or_else(|| MissingIncludedTeam {
+                path: path
+                    .iter()
+                    .map(|t| t.to_string())
+                    .chain(std::iter::once(team.clone()))
+                    .collect(),
             })?;
-            members.extend(team.members(data)?);
+            let step = format!(
+                "team `{}` includes the members of team `{}`",
+                self.name, team.name
+            );
+            members.extend(team.members_inner(
+                data,
+                path,
+                provenance.as_deref_mut().map(|p| p.enter(step)),
+            )?);
+            if let Some(provenance) = provenance.as_deref_mut() {
+                provenance.steps.pop();
+            }
         }
-        let mut include_leads = |kind| {
+        for (include, kind, what) in [
+            (self.people.include_team_leads, TeamKind::Team, "teams"),
+            (
+                self.people.include_wg_leads,
+                TeamKind::WorkingGroup,
+                "working groups",
+            ),
+            (
+                self.people.include_project_group_leads,
+                TeamKind::ProjectGroup,
+                "project groups",
+            ),
+        ] {
+            if !include {
+                continue;
+            }
             for team in data.teams() {
                 if team.name != self.name && team.kind == kind {
                     for lead in team.leads() {
                         members.insert(lead);
+                        if let Some(provenance) = provenance.as_deref_mut() {
+                            provenance.record(
+                                lead,
+                                format!(
+                                    "team `{}` includes the leads of all the {}, and `{}` leads \
+                                     team `{}`",
+                                    self.name, what, lead, team.name
+                                ),
+                            );
+                        }
                     }
                 }
             }
-        };
-        if self.people.include_team_leads {
-            include_leads(TeamKind::Team);
-        }
-        if self.people.include_wg_leads {
-            include_leads(TeamKind::WorkingGroup);
-        }
-        if self.people.include_project_group_leads {
-            include_leads(TeamKind::ProjectGroup);
         }
 
         if self.people.include_all_team_members {
@@ -271,28 +734,52 @@ impl Team {
                 {
                     continue;
                 }
-                members.extend(team.members(data)?);
+                let step = format!(
+                    "team `{}` includes the members of all the teams, like team `{}`",
+                    self.name, team.name
+                );
+                members.extend(team.members_inner(
+                    data,
+                    path,
+                    provenance.as_deref_mut().map(|p| p.enter(step)),
+                )?);
+                if let Some(provenance) = provenance.as_deref_mut() {
+                    provenance.steps.pop();
+                }
             }
         }
         if self.is_alumni_team() {
             let active_members = data.active_members()?;
-            let alumni = data
-                .teams()
-                .chain(data.archived_teams())
-                .flat_map(|t| t.alumni())
-                .map(|a| a.as_str());
-            let mut members_of_archived_teams = HashSet::new();
-
+            let mut alumni = Vec::new();
+            for t in data.teams().chain(data.archived_teams()) {
+                for person in t.alumni() {
+                    alumni.push((person.as_str(), "an alumni of team", t.name()));
+                }
+            }
             for t in data.archived_teams() {
-                members_of_archived_teams.extend(t.members(data)?);
+                for person in t.members(data)? {
+                    alumni.push((person, "a member of archived team", t.name()));
+                }
             }
 
-            members.extend(
-                alumni
-                    .chain(members_of_archived_teams)
-                    .filter(|person| !active_members.contains(person)),
-            )
+            for (person, what, team) in alumni {
+                if active_members.contains(person) {
+                    continue;
+                }
+                members.insert(person);
+                if let Some(provenance) = provenance.as_deref_mut() {
+                    provenance.record(
+                        person,
+                        format!(
+                            "team `{}` includes all the alumni, and `{}` is {} `{}`",
+                            self.name, person, what, team
+                        ),
+                    );
+                }
+            }
         }
+
+        path.pop();
         Ok(members)
     }
 
@@ -300,6 +787,11 @@ impl Team {
         &self.people.alumni
     }
 
+    /// The people added to all the mailing lists of the team, without being members of it.
+    pub(crate) fn list_only_members(&self) -> &[String] {
+        &self.list_only_members
+    }
+
     pub(crate) fn raw_lists(&self) -> &[TeamList] {
         &self.lists
     }
@@ -322,7 +814,7 @@ impl Team {
                     members.extend(subteam.members(data)?);
                 }
             }
-            for person in &raw_list.extra_people {
+            for person in raw_list.extra_people.iter().chain(&self.list_only_members) {
                 members.insert(person.as_str());
             }
             for team in &raw_list.extra_teams {
@@ -331,12 +823,18 @@ impl Team {
                     .ok_or_else(|| err_msg(format!("team {} is missing", team)))?;
                 members.extend(team.members(data)?);
             }
+            for team in &raw_list.extra_team_leads {
+                let team = data
+                    .team(team)
+                    .ok_or_else(|| err_msg(format!("team {} is missing", team)))?;
+                members.extend(team.leads());
+            }
 
             for member in members.iter() {
                 let member = data
                     .person(member)
                     .ok_or_else(|| err_msg(format!("member {} is missing", member)))?;
-                if let Email::Present(email) = member.email() {
+                if let Email::Present(email) = member.list_email() {
                     list.emails.push(email.to_string());
                 }
             }
@@ -361,6 +859,7 @@ impl Team {
                 name: raw_group.name.clone(),
                 includes_team_members: raw_group.include_team_members,
                 members: Vec::new(),
+                collapsed_zulip_ids: Vec::new(),
             };
 
             let mut members = if raw_group.include_team_members {
@@ -393,8 +892,22 @@ impl Team {
                 };
                 group.members.push(member);
             }
+            // People can be added both through their GitHub username and their Zulip ID: only keep
+            // the former, which carries more information.
+            let mut zulip_ids = group
+                .members
+                .iter()
+                .filter_map(|member| match member {
+                    ZulipGroupMember::MemberWithId { zulip_id, .. } => Some(*zulip_id),
+                    _ => None,
+                })
+                .collect::<HashSet<_>>();
             for &extra in &raw_group.extra_zulip_ids {
-                group.members.push(ZulipGroupMember::JustId(extra));
+                if zulip_ids.insert(extra) {
+                    group.members.push(ZulipGroupMember::JustId(extra));
+                } else {
+                    group.collapsed_zulip_ids.push(extra);
+                }
             }
             groups.push(group);
         }
@@ -427,13 +940,19 @@ impl Team {
                 );
             }
             members.sort_unstable();
-            let name = github.team_name.as_deref().unwrap_or(&self.name);
+            let default_name = if self.use_canonical_naming {
+                &self.slug
+            } else {
+                &self.name
+            };
+            let name = github.team_name.as_deref().unwrap_or(default_name);
 
             for org in &github.orgs {
                 result.push(GitHubTeam {
                     org: org.as_str(),
                     name,
                     members: members.clone(),
+                    repo_admin: github.repo_admin,
                 });
             }
         }
@@ -458,17 +977,24 @@ impl Team {
         &self.people.members
     }
 
-    pub(crate) fn contains_person(&self, data: &Data, person: &Person) -> Result<bool, Error> {
-        let members = self.members(data)?;
-        Ok(members.contains(person.github()))
+    pub(crate) fn content_hash(&self) -> &str {
+        &self.content_hash
+    }
+
+    pub(crate) fn set_content_hash(&mut self, content_hash: String) {
+        self.content_hash = content_hash;
     }
 }
 
 #[derive(serde_derive::Deserialize, Debug)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub(crate) struct DiscordRole {
-    name: String,
+    #[serde(rename = "name")]
+    raw_name: Option<String>,
     color: Option<String>,
+    /// The name with the canonical default applied, set when the data is loaded.
+    #[serde(skip)]
+    name: String,
 }
 
 impl DiscordRole {
@@ -476,21 +1002,69 @@ impl DiscordRole {
         &self.name
     }
 
+    /// The name of the role, only if it was explicitly set.
+    pub(crate) fn explicit_name(&self) -> Option<&str> {
+        self.raw_name.as_deref()
+    }
+
     pub(crate) fn color(&self) -> Option<&str> {
         self.color.as_ref().map(|s| &s[..])
     }
-}
 
-#[derive(Eq, PartialEq, Debug)]
-pub(crate) struct DiscordTeam {
-    pub(crate) members: Vec<usize>,
+    /// The color as a `#RRGGBB` hex code, resolving the named colors.
+    ///
+    /// Returns `None` if there is no color, and an error if the color is invalid.
+    pub(crate) fn hex_color(&self) -> Result<Option<String>, Error> {
+        let color = match self.color() {
+            Some(color) => color,
+            None => return Ok(None),
+        };
+        let hex = color.strip_prefix('#').unwrap_or("");
+        if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(Some(color.to_lowercase()));
+        }
+        match DISCORD_COLOR_NAMES.iter().find(|(name, _)| *name == color) {
+            Some((_, hex)) => Ok(Some(hex.to_string())),
+            None => bail!(
+                "invalid color `{}`: expected `#RRGGBB` or one of {}",
+                color,
+                DISCORD_COLOR_NAMES
+                    .iter()
+                    .map(|(name, _)| format!("`{}`", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
 }
 
+/// The colors of Discord's role color picker, which can be used by name.
+const DISCORD_COLOR_NAMES: &[(&str, &str)] = &[
+    ("teal", "#1abc9c"),
+    ("dark-teal", "#11806a"),
+    ("green", "#2ecc71"),
+    ("dark-green", "#1f8b4c"),
+    ("blue", "#3498db"),
+    ("dark-blue", "#206694"),
+    ("purple", "#9b59b6"),
+    ("dark-purple", "#71368a"),
+    ("magenta", "#e91e63"),
+    ("dark-magenta", "#ad1457"),
+    ("gold", "#f1c40f"),
+    ("dark-gold", "#c27c0e"),
+    ("orange", "#e67e22"),
+    ("dark-orange", "#a84300"),
+    ("red", "#e74c3c"),
+    ("dark-red", "#992d22"),
+];
+
 #[derive(Eq, PartialEq)]
 pub(crate) struct GitHubTeam<'a> {
     pub(crate) org: &'a str,
     pub(crate) name: &'a str,
     pub(crate) members: Vec<(&'a str, usize)>,
+    /// Whether repos are allowed to grant admin access to the team.
+    pub(crate) repo_admin: bool,
 }
 
 impl std::cmp::PartialOrd for GitHubTeam<'_> {
@@ -533,6 +1107,8 @@ struct GitHubData {
     orgs: Vec<String>,
     #[serde(default)]
     extra_teams: Vec<String>,
+    #[serde(default)]
+    repo_admin: bool,
 }
 
 #[derive(serde_derive::Deserialize, Debug)]
@@ -561,8 +1137,10 @@ pub(crate) struct WebsiteData {
     discord_invite: Option<String>,
     discord_name: Option<String>,
     zulip_stream: Option<String>,
-    #[serde(default)]
-    weight: i64,
+    zulip_stream_id: Option<u64>,
+    matrix_room: Option<String>,
+    weight: Option<i64>,
+    membership: Option<MembershipData>,
 }
 
 impl WebsiteData {
@@ -575,6 +1153,11 @@ impl WebsiteData {
     }
 
     pub(crate) fn weight(&self) -> i64 {
+        self.weight.unwrap_or(0)
+    }
+
+    /// The weight of the team, only if it was explicitly set.
+    pub(crate) fn explicit_weight(&self) -> Option<i64> {
         self.weight
     }
 
@@ -590,7 +1173,7 @@ impl WebsiteData {
         self.repo.as_deref()
     }
 
-    pub(crate) fn discord(&self) -> Option<DiscordInvite> {
+    pub(crate) fn discord(&self) -> Option<DiscordInvite<'_>> {
         if let (Some(url), Some(channel)) = (&self.discord_invite, &self.discord_name) {
             Some(DiscordInvite {
                 url: url.as_ref(),
@@ -604,6 +1187,103 @@ impl WebsiteData {
     pub(crate) fn zulip_stream(&self) -> Option<&str> {
         self.zulip_stream.as_deref()
     }
+
+    /// The ID of the Zulip stream, preferred over its name as it doesn't change on renames.
+    pub(crate) fn zulip_stream_id(&self) -> Option<u64> {
+        self.zulip_stream_id
+    }
+
+    /// Whether the team has a Zulip stream, either by name or by ID.
+    pub(crate) fn has_zulip_stream(&self) -> bool {
+        self.zulip_stream.is_some() || self.zulip_stream_id.is_some()
+    }
+
+    pub(crate) fn matrix_room(&self) -> Option<&str> {
+        self.matrix_room.as_deref()
+    }
+
+    pub(crate) fn membership(&self) -> Option<&MembershipData> {
+        self.membership.as_ref()
+    }
+}
+
+/// Whether the team is looking for new members, and how to join it.
+#[derive(serde_derive::Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct MembershipData {
+    pub(crate) open: bool,
+    #[serde(default)]
+    pub(crate) how_to_join: String,
+    pub(crate) contact: Option<MembershipContact>,
+}
+
+#[derive(serde_derive::Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum MembershipContact {
+    Zulip,
+    Email,
+}
+
+#[derive(serde_derive::Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct ElectionData {
+    pub(crate) term_months: u32,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    next_election: toml::value::Datetime,
+    pub(crate) electorate: Electorate,
+}
+
+impl ElectionData {
+    pub(crate) fn next_election(&self) -> Result<NaiveDate, Error> {
+        parse_date("next-election", &self.next_election)
+    }
+
+    pub(crate) fn status(&self, today: NaiveDate) -> Result<ElectionStatus, Error> {
+        Ok(election_status(
+            self.next_election()?,
+            self.term_months,
+            today,
+        ))
+    }
+}
+
+#[derive(serde_derive::Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Electorate {
+    Members,
+    MembersAndAlumni,
+}
+
+impl std::fmt::Display for Electorate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Electorate::Members => write!(f, "members"),
+            Electorate::MembersAndAlumni => write!(f, "members and alumni"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum ElectionStatus {
+    /// The election date is in the past.
+    Overdue,
+    /// The election is scheduled within a term from now.
+    Scheduled,
+    /// The election is scheduled more than a term from now.
+    BeyondTerm,
+}
+
+fn election_status(next_election: NaiveDate, term_months: u32, today: NaiveDate) -> ElectionStatus {
+    if next_election < today {
+        ElectionStatus::Overdue
+    } else if today
+        .checked_add_months(Months::new(term_months))
+        .is_some_and(|end_of_term| next_election > end_of_term)
+    {
+        ElectionStatus::BeyondTerm
+    } else {
+        ElectionStatus::Scheduled
+    }
 }
 
 #[derive(serde_derive::Deserialize, Debug)]
@@ -620,11 +1300,17 @@ pub(crate) struct TeamList {
     pub(crate) extra_emails: Vec<String>,
     #[serde(default)]
     pub(crate) extra_teams: Vec<String>,
+    #[serde(default)]
+    pub(crate) extra_team_leads: Vec<String>,
 }
 
 #[derive(serde_derive::Deserialize, Debug)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub(crate) struct RawZulipGroup {
+    #[serde(rename = "name")]
+    pub(crate) raw_name: Option<String>,
+    /// The name with the canonical default applied, set when the data is loaded.
+    #[serde(skip)]
     pub(crate) name: String,
     #[serde(default = "default_true")]
     pub(crate) include_team_members: bool,
@@ -659,6 +1345,7 @@ pub(crate) struct ZulipGroup {
     name: String,
     includes_team_members: bool,
     members: Vec<ZulipGroupMember>,
+    collapsed_zulip_ids: Vec<usize>,
 }
 
 impl ZulipGroup {
@@ -674,15 +1361,154 @@ impl ZulipGroup {
     pub(crate) fn members(&self) -> &[ZulipGroupMember] {
         &self.members
     }
+
+    /// The `extra-zulip-ids` that were dropped because the person was already in the group.
+    pub(crate) fn collapsed_zulip_ids(&self) -> &[usize] {
+        &self.collapsed_zulip_ids
+    }
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum ZulipGroupMember {
     MemberWithId { github: String, zulip_id: usize },
     JustId(usize),
     MemberWithoutId { github: String },
 }
 
+impl ZulipGroupMember {
+    /// The Zulip ID of the member, which is what the static API exposes.
+    pub(crate) fn zulip_id(&self) -> Option<usize> {
+        match self {
+            ZulipGroupMember::MemberWithId { zulip_id, .. } => Some(*zulip_id),
+            ZulipGroupMember::JustId(zulip_id) => Some(*zulip_id),
+            ZulipGroupMember::MemberWithoutId { .. } => None,
+        }
+    }
+}
+
+/// A field of the TOML files scheduled for removal.
+///
+/// Using a deprecated field is a warning of the `check` command, and becomes an error after the
+/// deadline, giving contributors time to migrate before the field stops being supported. Loading
+/// the data never fails because of a deprecation, so the other commands keep working.
+#[derive(Debug)]
+pub(crate) struct Deprecation {
+    /// The directory of the files the field is in, like `people` or `teams`.
+    pub(crate) dir: &'static str,
+    /// The path of the field in the files, with its segments separated by dots.
+    pub(crate) field: &'static str,
+    /// What to do instead of using the field.
+    pub(crate) replacement: &'static str,
+    /// The date (`YYYY-MM-DD`) after which using the field is an error.
+    pub(crate) deadline: &'static str,
+}
+
+pub(crate) static DEPRECATIONS: &[Deprecation] = &[Deprecation {
+    dir: "people",
+    field: "irc",
+    replacement: "remove it, the project doesn't use IRC anymore",
+    deadline: "2027-04-01",
+}];
+
+impl Deprecation {
+    fn deadline(&self) -> NaiveDate {
+        NaiveDate::parse_from_str(self.deadline, "%Y-%m-%d").expect("invalid deprecation deadline")
+    }
+
+    /// Check the use of the field in `file`, returning a warning before the deadline and an
+    /// error after it.
+    pub(crate) fn check(&self, file: &str, today: NaiveDate) -> Result<String, Error> {
+        if today > self.deadline() {
+            bail!(
+                "{}: the `{}` field was removed on {}: {}",
+                file,
+                self.field,
+                self.deadline,
+                self.replacement
+            );
+        }
+        Ok(format!(
+            "{}: the `{}` field is deprecated and will be removed on {}: {}",
+            file, self.field, self.deadline, self.replacement
+        ))
+    }
+
+    /// Whether the field is present in the document, looking into the arrays of tables.
+    fn is_used_in(&self, value: &toml::Value) -> bool {
+        fn contains(value: &toml::Value, path: &[&str]) -> bool {
+            match (value, path) {
+                (_, []) => true,
+                (toml::Value::Table(table), [key, rest @ ..]) => {
+                    table.get(*key).is_some_and(|value| contains(value, rest))
+                }
+                (toml::Value::Array(array), _) => array.iter().any(|value| contains(value, path)),
+                _ => false,
+            }
+        }
+        contains(value, &self.field.split('.').collect::<Vec<_>>())
+    }
+}
+
+/// The deprecated fields used by the TOML document of a file in the data directory `dir`.
+pub(crate) fn deprecated_fields<'a>(
+    deprecations: &'a [Deprecation],
+    dir: &str,
+    value: &toml::Value,
+) -> Vec<&'a Deprecation> {
+    deprecations
+        .iter()
+        .filter(|deprecation| deprecation.dir == dir && deprecation.is_used_in(value))
+        .collect()
+}
+
+/// Deserialize a TOML datetime, also accepting its string form: the files are parsed into a
+/// `toml::Value` first, which hands out its datetimes as strings.
+fn deserialize_datetime<'de, D>(deserializer: D) -> Result<toml::value::Datetime, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct DatetimeVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for DatetimeVisitor {
+        type Value = toml::value::Datetime;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a TOML datetime")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            value.parse().map_err(E::custom)
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            serde::Deserialize::deserialize(serde::de::value::MapAccessDeserializer::new(map))
+        }
+    }
+
+    deserializer.deserialize_any(DatetimeVisitor)
+}
+
+fn deserialize_optional_datetime<'de, D>(
+    deserializer: D,
+) -> Result<Option<toml::value::Datetime>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_datetime(deserializer).map(Some)
+}
+
+/// Parse a TOML date, which must not have a time.
+fn parse_date(field: &str, value: &toml::value::Datetime) -> Result<NaiveDate, Error> {
+    let raw = value.to_string();
+    NaiveDate::parse_from_str(&raw, "%Y-%m-%d").map_err(|_| {
+        err_msg(format!(
+            "`{}` must be a date without a time, found {}",
+            field, raw
+        ))
+    })
+}
+
 fn default_true() -> bool {
     true
 }
@@ -701,9 +1527,17 @@ pub(crate) struct Repo {
     pub access: RepoAccess,
     #[serde(default)]
     pub branch_protections: Vec<BranchProtection>,
+    /// How the PRs of the repo are merged, replacing the `bors` entry of `bots` over time.
+    #[serde(default)]
+    pub auto_merge: Option<AutoMerge>,
+    /// The owners of specific paths in the generated CODEOWNERS, by pattern, in file order.
+    #[serde(default)]
+    pub codeowners: IndexMap<String, CodeOwners>,
+    #[serde(skip)]
+    pub content_hash: String,
 }
 
-#[derive(serde_derive::Deserialize, Debug, Clone)]
+#[derive(serde_derive::Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum Bot {
     Bors,
@@ -713,6 +1547,83 @@ pub(crate) enum Bot {
     Rfcbot,
 }
 
+impl Bot {
+    /// The name of the bot, as used in the TOML files.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Bot::Bors => "bors",
+            Bot::Highfive => "highfive",
+            Bot::Rustbot => "rustbot",
+            Bot::RustTimer => "rust-timer",
+            Bot::Rfcbot => "rfcbot",
+        }
+    }
+}
+
+#[derive(serde_derive::Deserialize, Debug)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct CodeOwners {
+    /// GitHub team names, like in `access.teams`.
+    #[serde(default)]
+    pub teams: Vec<String>,
+    /// GitHub usernames, like in `access.individuals`.
+    #[serde(default)]
+    pub people: Vec<String>,
+}
+
+// This is an enum to allow both forms of the auto-merge field:
+//   auto-merge = "bors"
+//   auto-merge = { kind = "merge-queue", required-checks = ["CI"] }
+#[derive(serde_derive::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "AutoMergeField")]
+pub(crate) enum AutoMerge {
+    Bors,
+    /// GitHub merge queues, with the checks that must pass before merging.
+    MergeQueue {
+        required_checks: Vec<String>,
+    },
+    None,
+}
+
+#[derive(serde_derive::Deserialize)]
+#[serde(untagged)]
+enum AutoMergeField {
+    Keyword(String),
+    #[serde(rename_all = "kebab-case")]
+    Table {
+        kind: String,
+        #[serde(default)]
+        required_checks: Vec<String>,
+    },
+}
+
+impl TryFrom<AutoMergeField> for AutoMerge {
+    type Error = String;
+
+    fn try_from(field: AutoMergeField) -> Result<Self, String> {
+        let (kind, required_checks) = match field {
+            AutoMergeField::Keyword(kind) => (kind, Vec::new()),
+            AutoMergeField::Table {
+                kind,
+                required_checks,
+            } => (kind, required_checks),
+        };
+        match kind.as_str() {
+            "merge-queue" => Ok(AutoMerge::MergeQueue { required_checks }),
+            _ if !required_checks.is_empty() => Err(format!(
+                "invalid auto-merge `{}`: only merge queues have required checks",
+                kind
+            )),
+            "bors" => Ok(AutoMerge::Bors),
+            "none" => Ok(AutoMerge::None),
+            other => Err(format!(
+                "invalid auto-merge `{}`: expected \"bors\", \"merge-queue\" or \"none\"",
+                other
+            )),
+        }
+    }
+}
+
 #[derive(serde_derive::Deserialize, Debug)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub(crate) struct RepoAccess {
@@ -721,21 +1632,313 @@ pub(crate) struct RepoAccess {
     pub individuals: HashMap<String, RepoPermission>,
 }
 
-#[derive(serde_derive::Deserialize, Debug)]
-#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+#[derive(serde_derive::Deserialize, Debug, PartialEq, Eq)]
+#[serde(from = "String")]
 pub(crate) enum RepoPermission {
     Triage,
     Write,
     Maintain,
     Admin,
+    /// A custom repository role, which must be listed in the config's `custom-repo-roles`.
+    Custom(String),
+}
+
+impl From<String> for RepoPermission {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "triage" => RepoPermission::Triage,
+            "write" => RepoPermission::Write,
+            "maintain" => RepoPermission::Maintain,
+            "admin" => RepoPermission::Admin,
+            _ => RepoPermission::Custom(name),
+        }
+    }
 }
 
 #[derive(serde_derive::Deserialize, Debug)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub(crate) struct BranchProtection {
     pub pattern: String,
-    #[serde(default)]
+    #[serde(default, rename = "ci-checks")]
+    pub raw_ci_checks: CiChecks,
+    #[serde(default, rename = "dismiss-stale-review")]
+    pub raw_dismiss_stale_review: Option<bool>,
+    /// Whether the protection is synchronized, or only documented because something else (like
+    /// release automation) manages it.
+    #[serde(default = "default_true")]
+    pub managed: bool,
+    /// The CI checks with the org defaults applied, set when the data is loaded.
+    #[serde(skip)]
     pub ci_checks: Vec<String>,
-    #[serde(default)]
+    /// Whether to dismiss stale reviews with the org defaults applied, set when the data is
+    /// loaded.
+    #[serde(skip)]
     pub dismiss_stale_review: bool,
 }
+
+impl BranchProtection {
+    /// Apply the defaults of the org to the fields the protection doesn't set explicitly.
+    pub(crate) fn resolve(&mut self, defaults: Option<&BranchProtectionDefaults>) {
+        self.ci_checks = match (&self.raw_ci_checks, defaults) {
+            (CiChecks::Explicit(checks), _) => checks.clone(),
+            // Unmanaged protections only document the checks they list.
+            (CiChecks::Omitted, _) if !self.managed => Vec::new(),
+            (CiChecks::Omitted, Some(defaults)) | (CiChecks::Inherit, Some(defaults)) => {
+                defaults.ci_checks.clone()
+            }
+            // Inheriting without defaults is reported by `validate`.
+            (CiChecks::Omitted, None) | (CiChecks::Inherit, None) => Vec::new(),
+        };
+        self.dismiss_stale_review = self
+            .raw_dismiss_stale_review
+            .or_else(|| defaults.map(|d| d.dismiss_stale_review))
+            .unwrap_or(false);
+    }
+}
+
+// This is an enum to allow three kinds of values for the ci-checks field:
+//   (field omitted)
+//   ci-checks = "inherit"
+//   ci-checks = ["CI"]
+#[derive(serde_derive::Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(try_from = "CiChecksField")]
+pub(crate) enum CiChecks {
+    /// Use the org defaults if there are any, or no checks otherwise.
+    #[default]
+    Omitted,
+    /// Use the org defaults, which must exist.
+    Inherit,
+    Explicit(Vec<String>),
+}
+
+#[derive(serde_derive::Deserialize)]
+#[serde(untagged)]
+enum CiChecksField {
+    Keyword(String),
+    Explicit(Vec<String>),
+}
+
+impl TryFrom<CiChecksField> for CiChecks {
+    type Error = String;
+
+    fn try_from(field: CiChecksField) -> Result<Self, String> {
+        match field {
+            CiChecksField::Keyword(keyword) if keyword == "inherit" => Ok(CiChecks::Inherit),
+            CiChecksField::Keyword(other) => Err(format!(
+                "invalid ci-checks `{}`: expected a list of checks or \"inherit\"",
+                other
+            )),
+            CiChecksField::Explicit(checks) => Ok(CiChecks::Explicit(checks)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::{config, team};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_deprecations() {
+        for deprecation in DEPRECATIONS {
+            deprecation.deadline();
+        }
+
+        let deprecations = &[Deprecation {
+            dir: "teams",
+            field: "zulip-groups.extra-emails",
+            replacement: "use `extra-people` instead",
+            deadline: "2027-01-01",
+        }];
+        let team: toml::Value = toml::from_str(
+            "name = \"foo\"\n\
+             [[zulip-groups]]\n\
+             name = \"T-foo\"\n\
+             [[zulip-groups]]\n\
+             name = \"T-foo-leads\"\n\
+             extra-emails = [\"foo@example.com\"]\n",
+        )
+        .unwrap();
+        let warnings = |dir, value, today| {
+            deprecated_fields(deprecations, dir, value)
+                .into_iter()
+                .map(|deprecation| deprecation.check("teams/foo.toml", date(today)))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        assert_eq!(
+            warnings("teams", &team, "2027-01-01").unwrap(),
+            [
+                "teams/foo.toml: the `zulip-groups.extra-emails` field is deprecated and will be \
+              removed on 2027-01-01: use `extra-people` instead"
+            ]
+        );
+        assert_eq!(
+            warnings("teams", &team, "2027-01-02")
+                .unwrap_err()
+                .to_string(),
+            "teams/foo.toml: the `zulip-groups.extra-emails` field was removed on 2027-01-01: \
+             use `extra-people` instead"
+        );
+        // The field is only deprecated in the files of the directory.
+        assert!(warnings("people", &team, "2027-01-02").unwrap().is_empty());
+        // Files not using the field are fine after the deadline.
+        let unused: toml::Value = toml::from_str("name = \"foo\"\n").unwrap();
+        assert!(warnings("teams", &unused, "2027-01-02").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_branch_protection_defaults() {
+        let defaults: BranchProtectionDefaults =
+            toml::from_str("ci-checks = ['CI']\ndismiss-stale-review = true\n").unwrap();
+        let resolve = |toml: &str, defaults| {
+            let mut protection: BranchProtection = toml::from_str(toml).unwrap();
+            protection.resolve(defaults);
+            (protection.ci_checks, protection.dismiss_stale_review)
+        };
+
+        // Inheritance, both explicit and implicit.
+        let inherited = (vec!["CI".to_string()], true);
+        assert_eq!(
+            resolve("pattern = 'master'\nci-checks = 'inherit'", Some(&defaults)),
+            inherited
+        );
+        assert_eq!(resolve("pattern = 'master'", Some(&defaults)), inherited);
+        assert_eq!(
+            resolve("pattern = 'master'\nmanaged = false", Some(&defaults)),
+            (Vec::new(), true)
+        );
+
+        // Overrides.
+        assert_eq!(
+            resolve(
+                "pattern = 'master'\nci-checks = ['Test']\ndismiss-stale-review = false",
+                Some(&defaults)
+            ),
+            (vec!["Test".to_string()], false)
+        );
+        assert_eq!(
+            resolve("pattern = 'master'\nci-checks = []", Some(&defaults)),
+            (Vec::new(), true)
+        );
+
+        // No defaults in the org.
+        assert_eq!(resolve("pattern = 'master'", None), (Vec::new(), false));
+        assert_eq!(
+            resolve("pattern = 'master'\nci-checks = ['Test']", None),
+            (vec!["Test".to_string()], false)
+        );
+
+        assert!(
+            toml::from_str::<BranchProtection>("pattern = 'master'\nci-checks = 'CI'").is_err()
+        );
+    }
+
+    #[test]
+    fn test_github_slug() {
+        assert_eq!(github_slug("compiler"), "compiler");
+        assert_eq!(github_slug("WG Async Foundations"), "wg-async-foundations");
+        assert_eq!(github_slug("crates.io  on-call!"), "crates-io-on-call");
+        assert_eq!(
+            github_slug("--leading_and_trailing--"),
+            "leading-and-trailing"
+        );
+    }
+
+    #[test]
+    fn test_canonical_naming() {
+        let parse = |toml: &str| {
+            let mut team: Team = toml::from_str(&team(
+                "Foo Bar",
+                &[],
+                &[],
+                &format!(
+                    "{}\n[[github]]\norgs = ['org']\n[[zulip-groups]]\n[[discord-roles]]\n",
+                    toml
+                ),
+            ))
+            .unwrap();
+            team.resolve_names().map(|_| team)
+        };
+
+        let canonical = parse("use-canonical-naming = true").unwrap();
+        assert_eq!(canonical.slug(), "foo-bar");
+        assert_eq!(canonical.raw_zulip_groups()[0].name, "T-foo-bar");
+        assert_eq!(canonical.discord_roles().unwrap()[0].name(), "foo-bar");
+        let data = Data::from_toml(&config(""), &[], &[]);
+        assert_eq!(canonical.github_teams(&data).unwrap()[0].name, "foo-bar");
+
+        // Without opting in, the names must be explicit.
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn test_discord_role_hex_color() {
+        let hex_color = |toml: &str| {
+            toml::from_str::<DiscordRole>(toml)
+                .unwrap()
+                .hex_color()
+                .map_err(|e| e.to_string())
+        };
+        assert_eq!(hex_color("name = 'a'"), Ok(None));
+        assert_eq!(
+            hex_color("name = 'a'\ncolor = '#E91E63'"),
+            Ok(Some("#e91e63".into()))
+        );
+        assert_eq!(
+            hex_color("name = 'a'\ncolor = 'dark-teal'"),
+            Ok(Some("#11806a".into()))
+        );
+        assert!(hex_color("name = 'a'\ncolor = '#ggg000'").is_err());
+        assert!(hex_color("name = 'a'\ncolor = 'e91e63'").is_err());
+        assert!(hex_color("name = 'a'\ncolor = 'fuchsia'").is_err());
+    }
+
+    #[test]
+    fn test_repo_permission_deserialization() {
+        let access: RepoAccess =
+            toml::from_str("[teams]\nfoo = 'write'\nbar = 'admin'\nbaz = 'triage-plus-labels'\n")
+                .unwrap();
+        assert_eq!(access.teams["foo"], RepoPermission::Write);
+        assert_eq!(access.teams["bar"], RepoPermission::Admin);
+        assert_eq!(
+            access.teams["baz"],
+            RepoPermission::Custom("triage-plus-labels".into())
+        );
+    }
+
+    #[test]
+    fn test_election_status_across_year_boundaries() {
+        let today = date("2024-01-02");
+        assert_eq!(
+            election_status(date("2023-12-31"), 12, today),
+            ElectionStatus::Overdue
+        );
+        assert_eq!(
+            election_status(date("2024-01-02"), 12, today),
+            ElectionStatus::Scheduled
+        );
+        assert_eq!(
+            election_status(date("2025-01-02"), 12, today),
+            ElectionStatus::Scheduled
+        );
+        assert_eq!(
+            election_status(date("2025-01-03"), 12, today),
+            ElectionStatus::BeyondTerm
+        );
+
+        let today = date("2023-11-15");
+        assert_eq!(
+            election_status(date("2024-02-15"), 3, today),
+            ElectionStatus::Scheduled
+        );
+        assert_eq!(
+            election_status(date("2024-02-16"), 3, today),
+            ElectionStatus::BeyondTerm
+        );
+    }
+}
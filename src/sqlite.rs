@@ -0,0 +1,337 @@
+//! Export of the data as a SQLite database, for ad-hoc querying.
+//!
+//! The JSON API is great for tools, but makes joins across people, teams and repos awkward. The
+//! database is derived from the loaded [`Data`] only: the members, lists and Zulip groups are
+//! fully resolved, like in the static API. Archived teams and repos aren't exported.
+
+use crate::data::Data;
+use crate::permissions::allowed_people;
+use crate::schema::{Email, RepoPermission, TeamKind, ZulipGroupMember};
+use failure::{Error, ResultExt};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+/// The schema of the exported database.
+///
+/// - `people`: everyone in the `people` directory, keyed by GitHub username.
+/// - `teams`: the active teams, with their kind (as written in the TOML files) and parent.
+/// - `team_members`: the resolved members of each team, including the ones of the included teams.
+/// - `repos` and `repo_access`: the active repos, and the access of GitHub teams and people to
+///   them. Exactly one of `github_team` and `person` is set for each access.
+/// - `permissions`: the permissions each person has, either directly or through their teams.
+/// - `zulip_groups` and `zulip_group_members`: the Zulip groups of each team and their members,
+///   which might not have a GitHub username (`extra-zulip-ids`) or a Zulip ID.
+/// - `lists` and `list_members`: the mailing lists of each team and the addresses they send to.
+pub(crate) const SCHEMA: &str = "
+CREATE TABLE people (
+    github TEXT PRIMARY KEY,
+    github_id INTEGER NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    email TEXT,
+    zulip_id INTEGER,
+    discord_id INTEGER
+);
+
+CREATE TABLE teams (
+    name TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    subteam_of TEXT REFERENCES teams (name) DEFERRABLE INITIALLY DEFERRED,
+    top_level INTEGER NOT NULL
+);
+
+CREATE TABLE team_members (
+    team TEXT NOT NULL REFERENCES teams (name),
+    person TEXT NOT NULL REFERENCES people (github),
+    is_lead INTEGER NOT NULL,
+    PRIMARY KEY (team, person)
+);
+
+CREATE TABLE repos (
+    id INTEGER PRIMARY KEY,
+    org TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    UNIQUE (org, name)
+);
+
+CREATE TABLE repo_access (
+    repo INTEGER NOT NULL REFERENCES repos (id),
+    github_team TEXT,
+    person TEXT REFERENCES people (github),
+    permission TEXT NOT NULL,
+    CHECK ((github_team IS NULL) != (person IS NULL))
+);
+
+CREATE TABLE permissions (
+    person TEXT NOT NULL REFERENCES people (github),
+    permission TEXT NOT NULL,
+    PRIMARY KEY (person, permission)
+);
+
+CREATE TABLE zulip_groups (
+    name TEXT PRIMARY KEY,
+    team TEXT NOT NULL REFERENCES teams (name)
+);
+
+CREATE TABLE zulip_group_members (
+    zulip_group TEXT NOT NULL REFERENCES zulip_groups (name),
+    person TEXT REFERENCES people (github),
+    zulip_id INTEGER
+);
+
+CREATE TABLE lists (
+    address TEXT PRIMARY KEY,
+    team TEXT NOT NULL REFERENCES teams (name)
+);
+
+CREATE TABLE list_members (
+    list TEXT NOT NULL REFERENCES lists (address),
+    email TEXT NOT NULL,
+    PRIMARY KEY (list, email)
+);
+";
+
+/// Write the data to a new SQLite database at `dest`, replacing any existing file.
+pub(crate) fn export(data: &Data, dest: &Path) -> Result<(), Error> {
+    if dest.exists() {
+        std::fs::remove_file(dest)
+            .with_context(|_| format!("failed to remove {}", dest.display()))?;
+    }
+    let mut conn =
+        Connection::open(dest).with_context(|_| format!("failed to create {}", dest.display()))?;
+    write(data, &mut conn)
+}
+
+/// Create the schema and fill it with the data, in a single transaction.
+///
+/// Rows are inserted in a stable order, so that exporting the same data twice produces the same
+/// database.
+fn write(data: &Data, conn: &mut Connection) -> Result<(), Error> {
+    conn.pragma_update(None, "foreign_keys", true)?;
+    let tx = conn.transaction()?;
+    tx.execute_batch(SCHEMA)?;
+
+    let mut people = data.people().collect::<Vec<_>>();
+    people.sort_by_key(|person| person.github());
+    for person in people {
+        let email = match person.email() {
+            Email::Present(email) => Some(email),
+            Email::Missing | Email::Disabled => None,
+        };
+        tx.execute(
+            "INSERT INTO people VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                person.github(),
+                person.github_id(),
+                person.name(),
+                email,
+                person.zulip_id(),
+                person.discord_id(),
+            ],
+        )
+        .with_context(|_| format!("failed to export person `{}`", person.github()))?;
+    }
+
+    let mut teams = data.teams().collect::<Vec<_>>();
+    teams.sort_by_key(|team| team.name());
+    for team in &teams {
+        let context = || format!("failed to export team `{}`", team.name());
+        tx.execute(
+            "INSERT INTO teams VALUES (?1, ?2, ?3, ?4)",
+            params![
+                team.name(),
+                kind_name(team.kind()),
+                team.subteam_of(),
+                team.top_level(),
+            ],
+        )
+        .with_context(|_| context())?;
+
+        let leads = team.leads();
+        let mut members = team.members(data)?.into_iter().collect::<Vec<_>>();
+        members.sort_unstable();
+        for member in members {
+            tx.execute(
+                "INSERT INTO team_members VALUES (?1, ?2, ?3)",
+                params![team.name(), member, leads.contains(member)],
+            )
+            .with_context(|_| context())?;
+        }
+
+        let mut groups = team.zulip_groups(data)?;
+        groups.sort_by(|a, b| a.name().cmp(b.name()));
+        for group in groups {
+            tx.execute(
+                "INSERT INTO zulip_groups VALUES (?1, ?2)",
+                params![group.name(), team.name()],
+            )
+            .with_context(|_| context())?;
+            let mut members = group.members().to_vec();
+            members.sort();
+            for member in members {
+                let person = match &member {
+                    ZulipGroupMember::MemberWithId { github, .. }
+                    | ZulipGroupMember::MemberWithoutId { github } => Some(github),
+                    ZulipGroupMember::JustId(_) => None,
+                };
+                tx.execute(
+                    "INSERT INTO zulip_group_members VALUES (?1, ?2, ?3)",
+                    params![group.name(), person, member.zulip_id()],
+                )
+                .with_context(|_| context())?;
+            }
+        }
+
+        let mut lists = team.lists(data)?;
+        lists.sort_by(|a, b| a.address().cmp(b.address()));
+        for list in lists {
+            tx.execute(
+                "INSERT INTO lists VALUES (?1, ?2)",
+                params![list.address(), team.name()],
+            )
+            .with_context(|_| context())?;
+            let mut emails = list.emails().to_vec();
+            emails.sort();
+            emails.dedup();
+            for email in emails {
+                tx.execute(
+                    "INSERT INTO list_members VALUES (?1, ?2)",
+                    params![list.address(), email],
+                )
+                .with_context(|_| context())?;
+            }
+        }
+    }
+
+    let mut repos = data.repos().collect::<Vec<_>>();
+    repos.sort_by(|a, b| (&a.org, &a.name).cmp(&(&b.org, &b.name)));
+    for repo in repos {
+        let context = || format!("failed to export repo `{}/{}`", repo.org, repo.name);
+        tx.execute(
+            "INSERT INTO repos (org, name, description) VALUES (?1, ?2, ?3)",
+            params![repo.org, repo.name, repo.description],
+        )
+        .with_context(|_| context())?;
+        let id = tx.last_insert_rowid();
+
+        let mut teams = repo.access.teams.iter().collect::<Vec<_>>();
+        teams.sort_by_key(|(name, _)| *name);
+        for (name, permission) in teams {
+            tx.execute(
+                "INSERT INTO repo_access VALUES (?1, ?2, NULL, ?3)",
+                params![id, name, permission_name(permission)],
+            )
+            .with_context(|_| context())?;
+        }
+        let mut individuals = repo.access.individuals.iter().collect::<Vec<_>>();
+        individuals.sort_by_key(|(name, _)| *name);
+        for (name, permission) in individuals {
+            tx.execute(
+                "INSERT INTO repo_access VALUES (?1, NULL, ?2, ?3)",
+                params![id, name, permission_name(permission)],
+            )
+            .with_context(|_| context())?;
+        }
+    }
+
+    let mut permissions = crate::schema::Permissions::available(data.config());
+    permissions.sort();
+    for permission in permissions {
+        let mut people = allowed_people(data, &permission)?;
+        people.sort_by_key(|person| person.github());
+        for person in people {
+            tx.execute(
+                "INSERT INTO permissions VALUES (?1, ?2)",
+                params![person.github(), permission],
+            )
+            .with_context(|_| format!("failed to export permission `{}`", permission))?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// The name of the kind, as written in the TOML files.
+fn kind_name(kind: TeamKind) -> &'static str {
+    match kind {
+        TeamKind::Team => "team",
+        TeamKind::WorkingGroup => "working-group",
+        TeamKind::ProjectGroup => "project-group",
+        TeamKind::MarkerTeam => "marker-team",
+    }
+}
+
+/// The name of the permission, as written in the TOML files.
+fn permission_name(permission: &RepoPermission) -> &str {
+    match permission {
+        RepoPermission::Triage => "triage",
+        RepoPermission::Write => "write",
+        RepoPermission::Maintain => "maintain",
+        RepoPermission::Admin => "admin",
+        RepoPermission::Custom(role) => role,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_fixture() -> Result<(), Error> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("static-api");
+        let data = Data::load_from(&root)?;
+        let mut conn = Connection::open_in_memory()?;
+        write(&data, &mut conn)?;
+
+        let count =
+            |sql: &str| -> Result<i64, Error> { Ok(conn.query_row(sql, [], |row| row.get(0))?) };
+        assert_eq!(
+            count("SELECT COUNT(*) FROM people")?,
+            data.people().count() as i64
+        );
+        assert_eq!(
+            count("SELECT COUNT(*) FROM team_members WHERE team = 'foo'")?,
+            2
+        );
+        assert_eq!(
+            count("SELECT COUNT(*) FROM team_members WHERE team = 'foo' AND is_lead")?,
+            1
+        );
+
+        let mut stmt = conn.prepare(
+            "SELECT permission FROM permissions WHERE person = 'user-0' ORDER BY permission",
+        )?;
+        let permissions = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        // Reviewing with bors implies being allowed to try.
+        assert_eq!(
+            permissions,
+            [
+                "bors.crater.try",
+                "bors.crates-io.review",
+                "bors.crates-io.try",
+                "crater"
+            ]
+        );
+
+        // Every table is consistent with the others.
+        let violations = conn
+            .prepare("PRAGMA foreign_key_check")?
+            .query_map([], |_| Ok(()))?
+            .count();
+        assert_eq!(violations, 0);
+
+        // Exporting the same data twice produces the same database.
+        let dir = std::env::temp_dir().join(format!("rust-team-sqlite-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        export(&data, &dir.join("first.db"))?;
+        export(&data, &dir.join("second.db"))?;
+        assert!(std::fs::read(dir.join("first.db"))? == std::fs::read(dir.join("second.db"))?);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
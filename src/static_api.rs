@@ -1,14 +1,26 @@
 use crate::data::Data;
-use crate::schema::{Bot, Email, Permissions, RepoPermission, TeamKind, ZulipGroupMember};
-use failure::Error;
+use crate::schema::{
+    AutoMerge, Bot, BudgetAction, Electorate, Email, MembershipContact, Permissions, Repo,
+    RepoPermission, Team, TeamKind, WebsiteData, ZulipGroupMember,
+};
+use crate::zulip::ZulipApi;
+use failure::{bail, Error};
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use indexmap::IndexMap;
-use log::info;
+use log::{info, warn};
 use rust_team_data::v1;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::io::Write;
 use std::path::Path;
 
 pub(crate) struct Generator<'a> {
     dest: &'a Path,
     data: &'a Data,
+    gzip: bool,
+    /// The names of the Zulip streams by ID, only fetched if a team refers to a stream.
+    zulip_streams: OnceCell<Result<HashMap<u64, String>, String>>,
 }
 
 impl<'a> Generator<'a> {
@@ -18,101 +30,147 @@ impl<'a> Generator<'a> {
         }
         std::fs::create_dir_all(dest)?;
 
-        Ok(Generator { dest, data })
+        Ok(Generator {
+            dest,
+            data,
+            gzip: false,
+            zulip_streams: OnceCell::new(),
+        })
+    }
+
+    /// Also write a gzip-compressed copy of `v1/all.json`, as `v1/all.json.gz`.
+    pub(crate) fn gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
     }
 
     pub(crate) fn generate(&self) -> Result<(), Error> {
-        self.generate_teams()?;
-        self.generate_repos()?;
-        self.generate_lists()?;
-        self.generate_zulip_groups()?;
-        self.generate_permissions()?;
+        let teams = self.generate_teams()?;
+        let repos = self.generate_repos()?;
+        let lists = self.generate_lists()?;
+        let zulip_groups = self.generate_zulip_groups()?;
+        let permissions = self.generate_permissions()?;
+        let effective_permissions = self.generate_effective_permissions(&permissions)?;
         self.generate_rfcbot()?;
         self.generate_zulip_map()?;
-        self.generate_people()?;
+        let people = self.generate_people(effective_permissions)?;
+        self.generate_hashes()?;
+        self.generate_all(&v1::All {
+            teams: teams.teams,
+            repos: repos.repos,
+            lists: lists.lists,
+            zulip_groups: zulip_groups.groups,
+            permissions,
+            people: people.people,
+        })?;
         Ok(())
     }
 
-    fn generate_repos(&self) -> Result<(), Error> {
+    fn generate_repos(&self) -> Result<v1::Repos, Error> {
         let mut repos: IndexMap<String, Vec<v1::Repo>> = IndexMap::new();
         for r in self.data.repos() {
-            let branch_protections: Vec<_> = r
-                .branch_protections
-                .iter()
-                .map(|b| v1::BranchProtection {
-                    pattern: b.pattern.clone(),
-                    ci_checks: b.ci_checks.clone(),
-                    dismiss_stale_review: b.dismiss_stale_review,
-                })
-                .collect();
-            let repo = v1::Repo {
-                org: r.org.clone(),
-                name: r.name.clone(),
-                description: r.description.clone(),
-                bots: r
-                    .bots
-                    .iter()
-                    .map(|b| match b {
-                        Bot::Bors => v1::Bot::Bors,
-                        Bot::Highfive => v1::Bot::Highfive,
-                        Bot::RustTimer => v1::Bot::RustTimer,
-                        Bot::Rustbot => v1::Bot::Rustbot,
-                        Bot::Rfcbot => v1::Bot::Rfcbot,
-                    })
-                    .collect(),
-                teams: r
-                    .access
-                    .teams
-                    .iter()
-                    .map(|(name, permission)| {
-                        let permission = match permission {
-                            RepoPermission::Admin => v1::RepoPermission::Admin,
-                            RepoPermission::Write => v1::RepoPermission::Write,
-                            RepoPermission::Maintain => v1::RepoPermission::Maintain,
-                            RepoPermission::Triage => v1::RepoPermission::Triage,
-                        };
-                        v1::RepoTeam {
-                            name: name.clone(),
-                            permission,
-                        }
-                    })
-                    .collect(),
-                members: r
-                    .access
-                    .individuals
-                    .iter()
-                    .map(|(name, permission)| {
-                        let permission = match permission {
-                            RepoPermission::Admin => v1::RepoPermission::Admin,
-                            RepoPermission::Write => v1::RepoPermission::Write,
-                            RepoPermission::Maintain => v1::RepoPermission::Maintain,
-                            RepoPermission::Triage => v1::RepoPermission::Triage,
-                        };
-                        v1::RepoMember {
-                            name: name.clone(),
-                            permission,
-                        }
-                    })
-                    .collect(),
-                branch_protections,
-            };
-
+            let repo = self.repo(r);
             self.add(&format!("v1/repos/{}.json", r.name), &repo)?;
             repos.entry(r.org.clone()).or_default().push(repo);
         }
         repos
             .values_mut()
             .for_each(|r| r.sort_by(|r1, r2| r1.name.cmp(&r2.name)));
+        let repos = v1::Repos { repos };
+        self.add("v1/repos.json", &repos)?;
 
-        self.add("v1/repos.json", &v1::Repos { repos })?;
-        Ok(())
+        // Archived repos are only listed, so that consumers can tell them apart from deleted ones.
+        let mut archived: IndexMap<String, Vec<v1::Repo>> = IndexMap::new();
+        for r in self.data.archived_repos() {
+            archived
+                .entry(r.org.clone())
+                .or_default()
+                .push(self.repo(r));
+        }
+        archived.sort_keys();
+        archived
+            .values_mut()
+            .for_each(|r| r.sort_by(|r1, r2| r1.name.cmp(&r2.name)));
+        self.add("v1/repos-archive.json", &v1::Repos { repos: archived })?;
+        Ok(repos)
+    }
+
+    fn repo(&self, r: &Repo) -> v1::Repo {
+        let branch_protections: Vec<_> = r
+            .branch_protections
+            .iter()
+            .map(|b| v1::BranchProtection {
+                pattern: b.pattern.clone(),
+                ci_checks: b.ci_checks.clone(),
+                dismiss_stale_review: b.dismiss_stale_review,
+                managed: b.managed,
+            })
+            .collect();
+        v1::Repo {
+            org: r.org.clone(),
+            name: r.name.clone(),
+            description: r.description.clone(),
+            bots: r.bots.iter().map(bot).collect(),
+            teams: r
+                .access
+                .teams
+                .iter()
+                .filter_map(|(name, permission)| {
+                    Some(v1::RepoTeam {
+                        name: name.clone(),
+                        permission: repo_permission(permission)?,
+                    })
+                })
+                .collect(),
+            members: r
+                .access
+                .individuals
+                .iter()
+                .filter_map(|(name, permission)| {
+                    Some(v1::RepoMember {
+                        name: name.clone(),
+                        permission: repo_permission(permission)?,
+                    })
+                })
+                .collect(),
+            branch_protections,
+            webhooks: r
+                .bots
+                .iter()
+                .filter_map(|b| {
+                    let spec = self.data.config().bot_webhook(b)?;
+                    Some(v1::WebhookSpec {
+                        bot: bot(b),
+                        url: spec.url.clone(),
+                        events: spec.events.clone(),
+                    })
+                })
+                .collect(),
+            content_hash: r.content_hash.clone(),
+            auto_merge: r.auto_merge.as_ref().map(|auto_merge| match auto_merge {
+                AutoMerge::Bors => v1::AutoMerge::Bors,
+                AutoMerge::MergeQueue { required_checks } => v1::AutoMerge::MergeQueue {
+                    required_checks: required_checks.clone(),
+                },
+                AutoMerge::None => v1::AutoMerge::None,
+            }),
+            custom_role_teams: custom_role_access(&r.access.teams),
+            custom_role_members: custom_role_access(&r.access.individuals),
+        }
     }
 
-    fn generate_teams(&self) -> Result<(), Error> {
+    fn generate_teams(&self) -> Result<v1::Teams, Error> {
         let mut teams = IndexMap::new();
 
         for team in self.data.teams() {
             let leads = team.leads();
+            let mut subteams = self
+                .data
+                .teams()
+                .filter(|subteam| !subteam.is_private())
+                .filter(|subteam| subteam.subteam_of() == Some(team.name()))
+                .collect::<Vec<_>>();
+            subteams.sort_by_key(|subteam| subteam.name());
             let mut members = Vec::new();
             for github_name in &team.members(self.data)? {
                 if let Some(person) = self.data.person(github_name) {
@@ -121,6 +179,11 @@ impl<'a> Generator<'a> {
                         github: (*github_name).into(),
                         github_id: person.github_id(),
                         is_lead: leads.contains(github_name),
+                        leads_subteams: subteams
+                            .iter()
+                            .filter(|subteam| subteam.leads().contains(github_name))
+                            .map(|subteam| subteam.name().into())
+                            .collect(),
                     });
                 }
             }
@@ -135,6 +198,7 @@ impl<'a> Generator<'a> {
                         github: github_name.to_string(),
                         github_id: person.github_id(),
                         is_lead: false,
+                        leads_subteams: Vec::new(),
                     });
                 }
             }
@@ -154,6 +218,8 @@ impl<'a> Generator<'a> {
                     TeamKind::MarkerTeam => v1::TeamKind::MarkerTeam,
                 },
                 subteam_of: team.subteam_of().map(|st| st.into()),
+                top_level: team.top_level(),
+                private: team.is_private(),
                 members,
                 alumni,
                 github: Some(v1::TeamGitHub {
@@ -167,32 +233,61 @@ impl<'a> Generator<'a> {
                         .collect::<Vec<_>>(),
                 })
                 .filter(|gh| !gh.teams.is_empty()),
-                website_data: team.website_data().map(|ws| v1::TeamWebsite {
-                    name: ws.name().into(),
-                    description: ws.description().into(),
-                    page: ws.page().unwrap_or_else(|| team.name()).into(),
-                    email: ws.email().map(|e| e.into()),
-                    repo: ws.repo().map(|e| e.into()),
-                    discord: ws.discord().map(|i| v1::DiscordInvite {
-                        channel: i.channel.into(),
-                        url: i.url.into(),
-                    }),
-                    zulip_stream: ws.zulip_stream().map(|s| s.into()),
-                    weight: ws.weight(),
-                }),
+                website_data: team
+                    .website_data()
+                    .map(|ws| -> Result<_, Error> {
+                        Ok(v1::TeamWebsite {
+                            name: ws.name().into(),
+                            description: ws.description().into(),
+                            page: ws.page().unwrap_or_else(|| team.name()).into(),
+                            email: ws.email().map(|e| e.into()),
+                            repo: ws.repo().map(|e| e.into()),
+                            discord: ws.discord().map(|i| v1::DiscordInvite {
+                                channel: i.channel.into(),
+                                url: i.url.into(),
+                            }),
+                            zulip_stream: self.zulip_stream(team, ws)?,
+                            zulip_stream_id: self.zulip_stream_id(team, ws),
+                            matrix_room: ws.matrix_room().map(|s| s.into()),
+                            weight: ws.weight(),
+                            membership: ws.membership().map(|m| v1::TeamMembership {
+                                open: m.open,
+                                how_to_join: m.how_to_join.clone(),
+                                contact: m.contact.map(|contact| match contact {
+                                    MembershipContact::Zulip => v1::MembershipContact::Zulip,
+                                    MembershipContact::Email => v1::MembershipContact::Email,
+                                }),
+                            }),
+                        })
+                    })
+                    .transpose()?,
                 discord: team
                     .discord_roles()
-                    .map(|roles| {
-                        roles
-                            .iter()
-                            .map(|role| v1::TeamDiscord {
-                                name: role.name().into(),
-                                color: role.color().map(String::from),
-                                members: member_discord_ids.clone(),
-                            })
-                            .collect()
+                    .into_iter()
+                    .flatten()
+                    .map(|role| {
+                        Ok(v1::TeamDiscord {
+                            name: role.name().into(),
+                            color: role.hex_color()?,
+                            members: member_discord_ids.clone(),
+                        })
                     })
-                    .unwrap_or_else(Vec::new),
+                    .collect::<Result<_, Error>>()?,
+                election: team
+                    .election()
+                    .map(|election| -> Result<_, Error> {
+                        Ok(v1::Election {
+                            term_months: election.term_months,
+                            next_election: election.next_election()?.to_string(),
+                            electorate: match election.electorate {
+                                Electorate::Members => v1::Electorate::Members,
+                                Electorate::MembersAndAlumni => v1::Electorate::MembersAndAlumni,
+                            },
+                        })
+                    })
+                    .transpose()?,
+                created_at: team.created_at()?.map(|date| date.to_string()),
+                content_hash: team.content_hash().into(),
             };
 
             self.add(&format!("v1/teams/{}.json", team.name()), &team_data)?;
@@ -200,62 +295,92 @@ impl<'a> Generator<'a> {
         }
 
         teams.sort_keys();
-        self.add("v1/teams.json", &v1::Teams { teams })?;
-        Ok(())
+        let teams = v1::Teams { teams };
+        self.add("v1/teams.json", &teams)?;
+        Ok(teams)
     }
 
-    fn generate_lists(&self) -> Result<(), Error> {
-        let mut lists = IndexMap::new();
+    /// The name of the Zulip stream of the team, resolving its ID to the current name.
+    fn zulip_stream(&self, team: &Team, ws: &WebsiteData) -> Result<Option<String>, Error> {
+        if ws.zulip_stream_id().is_none() {
+            return Ok(ws.zulip_stream().map(|s| s.into()));
+        }
+        resolve_zulip_stream(team, ws, self.zulip_streams())
+    }
 
-        for list in self.data.lists()?.values() {
-            let mut members = list.emails().to_vec();
-            members.sort();
-            lists.insert(
-                list.address().to_string(),
-                v1::List {
-                    address: list.address().to_string(),
-                    members,
-                },
-            );
+    /// The ID of the Zulip stream of the team, resolving its name when the Zulip API is
+    /// available. Names that can't be resolved only emit a warning.
+    fn zulip_stream_id(&self, team: &Team, ws: &WebsiteData) -> Option<u64> {
+        if let Some(id) = ws.zulip_stream_id() {
+            return Some(id);
         }
+        // Only fetch the streams if there's a name to resolve.
+        ws.zulip_stream()?;
+        resolve_zulip_stream_id(team, ws, self.zulip_streams())
+    }
 
-        lists.sort_keys();
-        self.add("v1/lists.json", &v1::Lists { lists })?;
-        Ok(())
+    /// The names of the Zulip streams by ID, fetched the first time they're needed.
+    fn zulip_streams(&self) -> &Result<HashMap<u64, String>, String> {
+        self.zulip_streams.get_or_init(|| {
+            let zulip = ZulipApi::new();
+            zulip
+                .require_auth()
+                .and_then(|()| zulip.get_streams())
+                .map(|streams| {
+                    streams
+                        .into_iter()
+                        .map(|stream| (stream.stream_id, stream.name))
+                        .collect()
+                })
+                .map_err(|err| {
+                    warn!("couldn't fetch the Zulip streams: {}", err);
+                    err.to_string()
+                })
+        })
     }
 
-    fn generate_zulip_groups(&self) -> Result<(), Error> {
+    fn generate_lists(&self) -> Result<v1::Lists, Error> {
+        let lists = self.data.export_lists()?;
+        self.add("v1/lists.json", &lists)?;
+        Ok(lists)
+    }
+
+    fn generate_zulip_groups(&self) -> Result<v1::ZulipGroups, Error> {
         let mut groups = IndexMap::new();
 
         for group in self.data.zulip_groups()?.values() {
             let mut members = group.members().to_vec();
             members.sort();
+            let github_usernames = members
+                .iter()
+                .filter_map(|m| match m {
+                    ZulipGroupMember::MemberWithId { github, zulip_id } => {
+                        Some((*zulip_id, github.clone()))
+                    }
+                    _ => None,
+                })
+                .collect();
             groups.insert(
                 group.name().to_string(),
                 v1::ZulipGroup {
                     name: group.name().to_string(),
                     members: members
-                        .into_iter()
-                        .filter_map(|m| match m {
-                            ZulipGroupMember::MemberWithId { zulip_id, .. } => {
-                                Some(v1::ZulipGroupMember::Id(zulip_id))
-                            }
-                            ZulipGroupMember::JustId(zulip_id) => {
-                                Some(v1::ZulipGroupMember::Id(zulip_id))
-                            }
-                            ZulipGroupMember::MemberWithoutId { .. } => None,
-                        })
+                        .iter()
+                        .filter_map(|m| m.zulip_id().map(v1::ZulipGroupMember::Id))
                         .collect(),
+                    github_usernames,
                 },
             );
         }
 
         groups.sort_keys();
-        self.add("v1/zulip-groups.json", &v1::ZulipGroups { groups })?;
-        Ok(())
+        let groups = v1::ZulipGroups { groups };
+        self.add("v1/zulip-groups.json", &groups)?;
+        Ok(groups)
     }
 
-    fn generate_permissions(&self) -> Result<(), Error> {
+    fn generate_permissions(&self) -> Result<IndexMap<String, v1::Permission>, Error> {
+        let mut permissions = IndexMap::new();
         for perm in &Permissions::available(self.data.config()) {
             let allowed = crate::permissions::allowed_people(self.data, perm)?;
             let mut github_users = allowed
@@ -272,16 +397,28 @@ impl<'a> Generator<'a> {
             github_users.sort();
             github_ids.sort_unstable();
             discord_ids.sort_unstable();
+            let permission = v1::Permission {
+                github_users,
+                github_ids,
+                discord_ids,
+            };
             self.add(
                 &format!("v1/permissions/{}.json", perm.replace('-', "_")),
-                &v1::Permission {
-                    github_users,
-                    github_ids,
-                    discord_ids,
-                },
+                &permission,
             )?;
+            permissions.insert(perm.clone(), permission);
         }
-        Ok(())
+        permissions.sort_keys();
+        Ok(permissions)
+    }
+
+    fn generate_effective_permissions(
+        &self,
+        permissions: &IndexMap<String, v1::Permission>,
+    ) -> Result<v1::EffectivePermissions, Error> {
+        let effective = effective_permissions(self.data, permissions);
+        self.add("v1/effective-permissions.json", &effective)?;
+        Ok(effective)
     }
 
     fn generate_rfcbot(&self) -> Result<(), Error> {
@@ -313,25 +450,12 @@ impl<'a> Generator<'a> {
     }
 
     fn generate_zulip_map(&self) -> Result<(), Error> {
-        let mut zulip_people = IndexMap::new();
-
-        for person in self.data.people() {
-            if let Some(zulip_id) = person.zulip_id() {
-                zulip_people.insert(zulip_id, person.github_id());
-            }
-        }
-
-        zulip_people.sort_keys();
-        self.add(
-            "v1/zulip-map.json",
-            &v1::ZulipMapping {
-                users: zulip_people,
-            },
-        )?;
+        let mapping = self.data.zulip_mapping();
+        self.add("v1/zulip-map.json", &mapping)?;
         Ok(())
     }
 
-    fn generate_people(&self) -> Result<(), Error> {
+    fn generate_people(&self, permissions: v1::EffectivePermissions) -> Result<v1::People, Error> {
         let mut people = IndexMap::new();
 
         for person in self.data.people() {
@@ -344,14 +468,146 @@ impl<'a> Generator<'a> {
                         Email::Present(s) => Some(s.into()),
                     },
                     github_id: person.github_id(),
+                    content_hash: person.content_hash().into(),
+                    roles: person.roles().to_vec(),
+                    pronouns: person.pronouns().map(|p| p.into()),
                 },
             );
         }
 
         people.sort_keys();
 
-        self.add("v1/people.json", &v1::People { people })?;
+        let people = v1::People { people };
+        self.add("v1/people.json", &people)?;
+
+        self.generate_people_details(permissions)?;
+
+        Ok(people)
+    }
+
+    fn generate_people_details(&self, permissions: v1::EffectivePermissions) -> Result<(), Error> {
+        let mut teams: HashMap<&str, Vec<v1::PersonTeam>> = HashMap::new();
+        for team in self.data.teams().filter(|team| !team.is_private()) {
+            let leads = team.leads();
+            for member in team.members(self.data)? {
+                teams.entry(member).or_default().push(v1::PersonTeam {
+                    name: team.name().into(),
+                    is_lead: leads.contains(member),
+                });
+            }
+        }
+
+        let mut permissions = permissions.people;
+        let mut repos: HashMap<&str, Vec<v1::PersonRepo>> = HashMap::new();
+        let mut custom_role_repos: HashMap<&str, Vec<v1::PersonCustomRoleRepo>> = HashMap::new();
+        for repo in self.data.repos() {
+            for (name, permission) in &repo.access.individuals {
+                if let RepoPermission::Custom(role) = permission {
+                    custom_role_repos
+                        .entry(name)
+                        .or_default()
+                        .push(v1::PersonCustomRoleRepo {
+                            org: repo.org.clone(),
+                            name: repo.name.clone(),
+                            role: role.clone(),
+                        });
+                } else if let Some(permission) = repo_permission(permission) {
+                    repos.entry(name).or_default().push(v1::PersonRepo {
+                        org: repo.org.clone(),
+                        name: repo.name.clone(),
+                        permission,
+                    });
+                }
+            }
+        }
 
+        for person in self.data.people() {
+            let mut teams = teams.remove(person.github()).unwrap_or_default();
+            teams.sort_by(|a, b| a.name.cmp(&b.name));
+            let permissions = permissions.swap_remove(person.github()).unwrap_or_default();
+            let mut repos = repos.remove(person.github()).unwrap_or_default();
+            repos.sort_by(|a, b| (&a.org, &a.name).cmp(&(&b.org, &b.name)));
+            let mut custom_role_repos = custom_role_repos
+                .remove(person.github())
+                .unwrap_or_default();
+            custom_role_repos.sort_by(|a, b| (&a.org, &a.name).cmp(&(&b.org, &b.name)));
+
+            self.add(
+                &format!("v1/people/{}.json", person.github()),
+                &v1::PersonDetails {
+                    name: person.name().into(),
+                    github: person.github().into(),
+                    github_id: person.github_id(),
+                    email: match person.email() {
+                        Email::Missing | Email::Disabled => None,
+                        Email::Present(s) => Some(s.into()),
+                    },
+                    teams,
+                    permissions,
+                    repos,
+                    custom_role_repos,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn generate_hashes(&self) -> Result<(), Error> {
+        let mut teams = self
+            .data
+            .teams()
+            .map(|team| (team.name().to_string(), team.content_hash().to_string()))
+            .collect::<IndexMap<_, _>>();
+        let mut repos = self
+            .data
+            .repos()
+            .map(|repo| {
+                (
+                    format!("{}/{}", repo.org, repo.name),
+                    repo.content_hash.clone(),
+                )
+            })
+            .collect::<IndexMap<_, _>>();
+        let mut people = self
+            .data
+            .people()
+            .map(|person| {
+                (
+                    person.github().to_string(),
+                    person.content_hash().to_string(),
+                )
+            })
+            .collect::<IndexMap<_, _>>();
+
+        teams.sort_keys();
+        repos.sort_keys();
+        people.sort_keys();
+
+        self.add(
+            "v1/hashes.json",
+            &v1::Hashes {
+                teams,
+                repos,
+                people,
+            },
+        )?;
+        Ok(())
+    }
+
+    fn generate_all(&self, all: &v1::All) -> Result<(), Error> {
+        self.add("v1/all.json", all)?;
+        if self.gzip {
+            let path = "v1/all.json.gz";
+            info!("writing API object {}...", path);
+            let json = std::fs::read(self.dest.join("v1/all.json"))?;
+            let mut encoder = GzEncoder::new(
+                std::fs::File::create(self.dest.join(path))?,
+                Compression::best(),
+            );
+            encoder.write_all(&json)?;
+            encoder.finish()?;
+        }
         Ok(())
     }
 
@@ -364,7 +620,433 @@ impl<'a> Generator<'a> {
             }
         }
         let json = serde_json::to_string_pretty(obj)?;
+        self.check_size_budget(path, &json)?;
         std::fs::write(&dest, json.as_bytes())?;
         Ok(())
     }
+
+    fn check_size_budget(&self, path: &str, json: &str) -> Result<(), Error> {
+        let budgets = self.data.config().static_api_size_budgets();
+        let budget = match budgets.endpoints.get(path) {
+            Some(&budget) if json.len() > budget => budget,
+            _ => return Ok(()),
+        };
+
+        let contributors = largest_entries(&serde_json::from_str(json)?, 5)
+            .into_iter()
+            .map(|(name, size)| format!("{} ({} bytes)", name, size))
+            .collect::<Vec<_>>();
+        let message = format!(
+            "the API document {} is {} bytes, over its budget of {} bytes (largest entries: {})",
+            path,
+            json.len(),
+            budget,
+            contributors.join(", ")
+        );
+        match budgets.on_exceeded {
+            BudgetAction::Error => bail!("{}", message),
+            BudgetAction::Warn => warn!("{}", message),
+        }
+        Ok(())
+    }
+}
+
+/// Resolve the Zulip stream ID of the team to the current name of the stream, falling back to
+/// the `zulip-stream` if the streams couldn't be fetched or the ID is unknown.
+fn resolve_zulip_stream(
+    team: &Team,
+    ws: &WebsiteData,
+    streams: &Result<HashMap<u64, String>, String>,
+) -> Result<Option<String>, Error> {
+    let id = match ws.zulip_stream_id() {
+        Some(id) => id,
+        None => return Ok(ws.zulip_stream().map(|s| s.into())),
+    };
+    let resolved = match streams {
+        Ok(streams) => streams
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| "no stream has this ID".to_string()),
+        Err(err) => Err(err.clone()),
+    };
+    match (resolved, ws.zulip_stream()) {
+        (Ok(name), _) => Ok(Some(name)),
+        (Err(err), Some(name)) => {
+            warn!(
+                "couldn't resolve the Zulip stream ID {} of team `{}`, using `{}`: {}",
+                id,
+                team.name(),
+                name,
+                err
+            );
+            Ok(Some(name.into()))
+        }
+        (Err(err), None) => bail!(
+            "couldn't resolve the Zulip stream ID {} of team `{}`: {}",
+            id,
+            team.name(),
+            err
+        ),
+    }
+}
+
+/// The `limit` largest entries of the document by serialized size, largest first.
+///
+/// Objects with a single key (like the `teams` wrapper of `teams.json`) are skipped, and
+/// entries containing a list (like the orgs of `repos.json`) are expanded into their elements,
+/// named `<key>/<name>`. The sizes are those of the entries serialized on their own, so they
+/// don't include the indentation they have in the whole document.
+fn largest_entries(value: &serde_json::Value, limit: usize) -> Vec<(String, usize)> {
+    use serde_json::Value;
+
+    fn element_name(prefix: Option<&str>, index: usize, element: &Value) -> String {
+        let name = match element.get("name").and_then(|name| name.as_str()) {
+            Some(name) => name.to_string(),
+            None => index.to_string(),
+        };
+        match prefix {
+            Some(prefix) => format!("{}/{}", prefix, name),
+            None => name,
+        }
+    }
+
+    let mut value = value;
+    while let Value::Object(map) = value {
+        match map.values().next() {
+            Some(inner) if map.len() == 1 => value = inner,
+            _ => break,
+        }
+    }
+
+    let size = |value: &Value| serde_json::to_string_pretty(value).map_or(0, |s| s.len());
+    let mut entries = Vec::new();
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    Value::Array(elements) => {
+                        for (index, element) in elements.iter().enumerate() {
+                            entries.push((element_name(Some(key), index, element), size(element)));
+                        }
+                    }
+                    _ => entries.push((key.clone(), size(value))),
+                }
+            }
+        }
+        Value::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                entries.push((element_name(None, index, element), size(element)));
+            }
+        }
+        _ => {}
+    }
+
+    entries.sort_by(|(a_name, a_size), (b_name, b_size)| {
+        b_size.cmp(a_size).then_with(|| a_name.cmp(b_name))
+    });
+    entries.truncate(limit);
+    entries
+}
+
+/// Custom roles return `None`, as they're published apart from the built-in ones.
+fn repo_permission(permission: &RepoPermission) -> Option<v1::RepoPermission> {
+    match permission {
+        RepoPermission::Admin => Some(v1::RepoPermission::Admin),
+        RepoPermission::Write => Some(v1::RepoPermission::Write),
+        RepoPermission::Maintain => Some(v1::RepoPermission::Maintain),
+        RepoPermission::Triage => Some(v1::RepoPermission::Triage),
+        RepoPermission::Custom(_) => None,
+    }
+}
+
+fn custom_role_access(access: &HashMap<String, RepoPermission>) -> Vec<v1::CustomRoleAccess> {
+    let mut custom_roles: Vec<_> = access
+        .iter()
+        .filter_map(|(name, permission)| match permission {
+            RepoPermission::Custom(role) => Some(v1::CustomRoleAccess {
+                name: name.clone(),
+                role: role.clone(),
+            }),
+            _ => None,
+        })
+        .collect();
+    custom_roles.sort_by(|a, b| a.name.cmp(&b.name));
+    custom_roles
+}
+
+fn bot(bot: &Bot) -> v1::Bot {
+    match bot {
+        Bot::Bors => v1::Bot::Bors,
+        Bot::Highfive => v1::Bot::Highfive,
+        Bot::RustTimer => v1::Bot::RustTimer,
+        Bot::Rustbot => v1::Bot::Rustbot,
+        Bot::Rfcbot => v1::Bot::Rfcbot,
+    }
+}
+
+/// Invert the holders of each permission into the permissions of each person.
+fn effective_permissions(
+    data: &Data,
+    permissions: &IndexMap<String, v1::Permission>,
+) -> v1::EffectivePermissions {
+    let mut people = data
+        .people()
+        .map(|person| (person.github().to_string(), Vec::new()))
+        .collect::<IndexMap<_, _>>();
+    for (name, permission) in permissions {
+        for user in &permission.github_users {
+            if let Some(names) = people.get_mut(user) {
+                names.push(name.clone());
+            }
+        }
+    }
+    people.sort_keys();
+    people.values_mut().for_each(|names| names.sort());
+    v1::EffectivePermissions { people }
+}
+
+/// Resolve the `zulip-stream` of the team to the ID of the stream, if the streams could be
+/// fetched and one of them has this name.
+fn resolve_zulip_stream_id(
+    team: &Team,
+    ws: &WebsiteData,
+    streams: &Result<HashMap<u64, String>, String>,
+) -> Option<u64> {
+    let name = ws.zulip_stream()?;
+    // The reason the streams couldn't be fetched was already reported.
+    let streams = streams.as_ref().ok()?;
+    let id = streams
+        .iter()
+        .find(|(_, stream)| *stream == name)
+        .map(|(id, _)| *id);
+    if id.is_none() {
+        warn!(
+            "couldn't resolve the ID of the Zulip stream `{}` of team `{}`: no stream has this name",
+            name,
+            team.name(),
+        );
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::{config, person, team};
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve_zulip_stream() {
+        let website = |extra: &str| format!("[website]\nname = ''\ndescription = ''\n{}", extra);
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &[
+                &team(
+                    "by-id",
+                    &[],
+                    &[],
+                    &website("zulip-stream = 't-old-name'\nzulip-stream-id = 1\n"),
+                ),
+                &team("only-id", &[], &[], &website("zulip-stream-id = 2\n")),
+            ],
+        );
+        let resolve = |team: &str, streams: &Result<HashMap<u64, String>, String>| {
+            let team = data.team(team).unwrap();
+            resolve_zulip_stream(team, team.website_data().unwrap(), streams)
+        };
+        let streams = Ok(vec![(1, "t-new-name".to_string())].into_iter().collect());
+        let unavailable = Err("missing credentials".to_string());
+
+        // The ID is preferred, falling back to the name when it can't be resolved.
+        assert_eq!(
+            resolve("by-id", &streams).unwrap().as_deref(),
+            Some("t-new-name")
+        );
+        assert_eq!(
+            resolve("by-id", &unavailable).unwrap().as_deref(),
+            Some("t-old-name")
+        );
+        assert!(resolve("only-id", &streams).is_err());
+        assert!(resolve("only-id", &unavailable).is_err());
+    }
+
+    #[test]
+    fn test_resolve_zulip_stream_id() {
+        let website = |stream: &str| {
+            format!(
+                "[website]\nname = ''\ndescription = ''\nzulip-stream = '{}'\n",
+                stream
+            )
+        };
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &[
+                &team("known", &[], &[], &website("t-known")),
+                &team("unknown", &[], &[], &website("t-unknown")),
+            ],
+        );
+        let resolve = |team: &str, streams: &Result<HashMap<u64, String>, String>| {
+            let team = data.team(team).unwrap();
+            resolve_zulip_stream_id(team, team.website_data().unwrap(), streams)
+        };
+        let streams = Ok(vec![(1, "t-known".to_string())].into_iter().collect());
+        let unavailable = Err("missing credentials".to_string());
+
+        assert_eq!(resolve("known", &streams), Some(1));
+        assert_eq!(resolve("unknown", &streams), None);
+        assert_eq!(resolve("known", &unavailable), None);
+    }
+
+    #[test]
+    fn test_effective_permissions() -> Result<(), Error> {
+        let data = Data::from_toml(
+            &config(
+                "permissions-bors-repos = ['rust']\n\
+                 permissions-bools = ['crater', 'perf']\n",
+            ),
+            &[
+                &person("foo", 1, "[permissions]\nperf = true\n"),
+                &person("bar", 2, ""),
+                &person("baz", 3, ""),
+            ],
+            &[&team(
+                "team",
+                &["bar"],
+                &["foo", "bar"],
+                "[permissions]\n\
+                 crater = true\n\
+                 [leads-permissions]\n\
+                 bors.rust.review = true\n",
+            )],
+        );
+        let dest = std::env::temp_dir().join(format!(
+            "rust-team-effective-permissions-{}",
+            std::process::id()
+        ));
+        let generator = Generator::new(&dest, &data)?;
+        let permissions = generator.generate_permissions()?;
+        let effective = effective_permissions(&data, &permissions);
+        std::fs::remove_dir_all(&dest)?;
+
+        assert_eq!(
+            effective.people,
+            vec![
+                (
+                    "bar".to_string(),
+                    vec!["bors.rust.review", "bors.rust.try", "crater"]
+                ),
+                ("baz".to_string(), vec![]),
+                ("foo".to_string(), vec!["crater", "perf"]),
+            ]
+            .into_iter()
+            .map(|(person, names)| (person, names.into_iter().map(String::from).collect()))
+            .collect::<IndexMap<String, Vec<String>>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_private_teams() -> Result<(), Error> {
+        let data = Data::from_toml(
+            &config(
+                "allowed-mailing-lists-domains = ['example.com']\n\
+                 allowed-github-orgs = ['org']\n\
+                 permissions-bools = ['crater']\n",
+            ),
+            &[
+                &person("foo", 1, "email = 'foo@example.org'\n"),
+                &person("bar", 2, "email = 'bar@example.org'\n"),
+            ],
+            &[
+                &team(
+                    "public",
+                    &[],
+                    &["foo"],
+                    "[[lists]]\naddress = 'public@example.com'\n",
+                ),
+                &team(
+                    "private",
+                    &["bar"],
+                    &["bar"],
+                    "subteam-of = 'public'\n\
+                     visibility = 'private'\n\
+                     [permissions]\n\
+                     crater = true\n\
+                     [[github]]\n\
+                     orgs = ['org']\n\
+                     [[lists]]\n\
+                     address = 'private@example.com'\n",
+                ),
+            ],
+        );
+        let dest =
+            std::env::temp_dir().join(format!("rust-team-private-teams-{}", std::process::id()));
+        Generator::new(&dest, &data)?.generate()?;
+        let read = |path: &str| -> Result<serde_json::Value, Error> {
+            Ok(serde_json::from_slice(&std::fs::read(dest.join(path))?)?)
+        };
+        let teams = read("v1/teams.json")?;
+        let lists = read("v1/lists.json")?;
+        let bar = read("v1/people/bar.json")?;
+        let permission = read("v1/permissions/crater.json")?;
+        let private_team = read("v1/teams/private.json")?;
+        std::fs::remove_dir_all(&dest)?;
+
+        // The private team is still published for the tools syncing it to GitHub...
+        assert_eq!(teams["private"]["private"], true);
+        assert_eq!(teams["public"]["private"], false);
+        assert_eq!(
+            private_team["github"]["teams"],
+            json!([{ "org": "org", "name": "private", "members": [2] }])
+        );
+        assert_eq!(teams["public"]["members"][0]["github"], "foo");
+        // ...but it's left out of the website-facing files...
+        assert_eq!(
+            lists["lists"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["public@example.com"]
+        );
+        assert_eq!(bar["teams"], json!([]));
+        // ...but its members still get the permissions it grants.
+        assert_eq!(permission["github_users"], json!(["bar"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_largest_entries() {
+        let teams = json!({
+            "teams": {
+                "small": { "name": "small" },
+                "large": { "name": "large", "members": ["a", "b", "c"] },
+                "medium": { "name": "medium", "members": ["a"] },
+            }
+        });
+        let names = |entries: Vec<(String, usize)>| {
+            entries
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(largest_entries(&teams, 2)), vec!["large", "medium"]);
+
+        let repos = json!({
+            "repos": {
+                "org-a": [{ "name": "tiny" }, { "name": "huge", "bots": ["bors", "rfcbot"] }],
+                "org-b": [{ "name": "mid", "bots": ["bors"] }],
+            }
+        });
+        assert_eq!(
+            names(largest_entries(&repos, 10)),
+            vec!["org-a/huge", "org-b/mid", "org-a/tiny"]
+        );
+
+        let (name, size) = &largest_entries(&json!({ "a": "xyz", "b": 1 }), 1)[0];
+        assert_eq!(name, "a");
+        assert_eq!(*size, r#""xyz""#.len());
+    }
 }
@@ -1,125 +1,715 @@
+use crate::codeowners::can_own;
 use crate::data::Data;
 use crate::github::GitHubApi;
-use crate::schema::{Email, Permissions, Team, TeamKind, ZulipGroupMember};
+use crate::permissions::allowed_people;
+use crate::schema::{
+    AutoMerge, Bot, BranchProtection, CiChecks, Config, ElectionStatus, Email, MembershipContact,
+    MissingIncludedTeam, Permissions, Person, RepoPermission, Team, TeamKind, ZulipGroup,
+    ZulipGroupMember,
+};
 use crate::zulip::ZulipApi;
-use failure::{bail, Error};
-use log::{error, warn};
+use failure::{bail, err_msg, Error};
+use log::{error, info, warn};
+use rayon::prelude::*;
 use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::io::IsTerminal;
+use std::str::FromStr;
+use std::sync::OnceLock;
 
 macro_rules! checks {
-    ($($f:ident,)*) => {
+    ($($f:ident => $explanation:ident,)*) => {
         &[$(
             Check {
                 f: $f,
-                name: stringify!($f)
+                name: stringify!($f),
+                explanation: &$explanation,
             }
         ),*]
     }
 }
 
 #[allow(clippy::type_complexity)]
-static CHECKS: &[Check<fn(&Data, &mut Vec<String>)>] = checks![
-    validate_name_prefixes,
-    validate_subteam_of,
-    validate_team_leads,
-    validate_team_members,
-    validate_alumni,
-    validate_inactive_members,
-    validate_list_email_addresses,
-    validate_list_extra_people,
-    validate_list_extra_teams,
-    validate_list_addresses,
-    validate_people_addresses,
-    validate_duplicate_permissions,
-    validate_permissions,
-    validate_rfcbot_labels,
-    validate_rfcbot_exclude_members,
-    validate_team_names,
-    validate_github_teams,
-    validate_zulip_stream_name,
-    validate_project_groups_have_parent_teams,
-    validate_discord_team_members_have_discord_ids,
-    validate_zulip_group_ids,
-    validate_zulip_group_extra_people,
-    validate_repos,
+static CHECKS: &[Check<fn(&Data, &mut Findings)>] = checks![
+    validate_name_prefixes => VALIDATE_NAME_PREFIXES,
+    validate_subteam_of => VALIDATE_SUBTEAM_OF,
+    validate_top_level_teams => VALIDATE_TOP_LEVEL_TEAMS,
+    validate_canonical_names => VALIDATE_CANONICAL_NAMES,
+    validate_team_leads => VALIDATE_TEAM_LEADS,
+    validate_team_members => VALIDATE_TEAM_MEMBERS,
+    validate_draft_teams => VALIDATE_DRAFT_TEAMS,
+    validate_nonempty_membership => VALIDATE_NONEMPTY_MEMBERSHIP,
+    validate_marker_teams_resources => VALIDATE_MARKER_TEAMS_RESOURCES,
+    validate_private_teams => VALIDATE_PRIVATE_TEAMS,
+    validate_alumni => VALIDATE_ALUMNI,
+    validate_alumni_exist => VALIDATE_ALUMNI_EXIST,
+    validate_alumni_permissions => VALIDATE_ALUMNI_PERMISSIONS,
+    validate_inactive_members => VALIDATE_INACTIVE_MEMBERS,
+    validate_list_email_addresses => VALIDATE_LIST_EMAIL_ADDRESSES,
+    validate_list_extra_people => VALIDATE_LIST_EXTRA_PEOPLE,
+    validate_list_only_members => VALIDATE_LIST_ONLY_MEMBERS,
+    validate_list_extra_teams => VALIDATE_LIST_EXTRA_TEAMS,
+    validate_list_extra_teams_cycles => VALIDATE_LIST_EXTRA_TEAMS_CYCLES,
+    validate_list_expansion => VALIDATE_LIST_EXPANSION,
+    validate_extra_team_email_coverage => VALIDATE_EXTRA_TEAM_EMAIL_COVERAGE,
+    validate_list_addresses => VALIDATE_LIST_ADDRESSES,
+    validate_people_addresses => VALIDATE_PEOPLE_ADDRESSES,
+    validate_email_on_list_domain => VALIDATE_EMAIL_ON_LIST_DOMAIN,
+    validate_unique_github_ids => VALIDATE_UNIQUE_GITHUB_IDS,
+    validate_unique_zulip_ids => VALIDATE_UNIQUE_ZULIP_IDS,
+    validate_unique_discord_ids => VALIDATE_UNIQUE_DISCORD_IDS,
+    validate_person_file_names => VALIDATE_PERSON_FILE_NAMES,
+    validate_person_roles => VALIDATE_PERSON_ROLES,
+    validate_person_pronouns => VALIDATE_PERSON_PRONOUNS,
+    validate_duplicate_permissions => VALIDATE_DUPLICATE_PERMISSIONS,
+    validate_duplicate_leads_permissions => VALIDATE_DUPLICATE_LEADS_PERMISSIONS,
+    validate_permissions => VALIDATE_PERMISSIONS,
+    validate_orphaned_permissions => VALIDATE_ORPHANED_PERMISSIONS,
+    validate_rfcbot_labels => VALIDATE_RFCBOT_LABELS,
+    validate_rfcbot_exclude_members => VALIDATE_RFCBOT_EXCLUDE_MEMBERS,
+    validate_rfcbot_ping_format => VALIDATE_RFCBOT_PING_FORMAT,
+    validate_rfcbot_ping => VALIDATE_RFCBOT_PING,
+    validate_team_names => VALIDATE_TEAM_NAMES,
+    validate_github_teams => VALIDATE_GITHUB_TEAMS,
+    validate_zulip_stream_name => VALIDATE_ZULIP_STREAM_NAME,
+    validate_zulip_streams_unique => VALIDATE_ZULIP_STREAMS_UNIQUE,
+    validate_matrix_room_name => VALIDATE_MATRIX_ROOM_NAME,
+    validate_team_membership => VALIDATE_TEAM_MEMBERSHIP,
+    validate_unique_sibling_weights => VALIDATE_UNIQUE_SIBLING_WEIGHTS,
+    validate_project_groups_have_parent_teams => VALIDATE_PROJECT_GROUPS_HAVE_PARENT_TEAMS,
+    validate_elections => VALIDATE_ELECTIONS,
+    validate_team_created_at => VALIDATE_TEAM_CREATED_AT,
+    validate_discord_team_members_have_discord_ids => VALIDATE_DISCORD_TEAM_MEMBERS_HAVE_DISCORD_IDS,
+    validate_discord_colors => VALIDATE_DISCORD_COLORS,
+    validate_discord_role_names_unique => VALIDATE_DISCORD_ROLE_NAMES_UNIQUE,
+    validate_discord_color_contrast => VALIDATE_DISCORD_COLOR_CONTRAST,
+    validate_zulip_group_ids => VALIDATE_ZULIP_GROUP_IDS,
+    validate_zulip_group_extra_people => VALIDATE_ZULIP_GROUP_EXTRA_PEOPLE,
+    validate_zulip_group_duplicate_ids => VALIDATE_ZULIP_GROUP_DUPLICATE_IDS,
+    validate_zulip_group_names_unique => VALIDATE_ZULIP_GROUP_NAMES_UNIQUE,
+    validate_zulip_reserved_names => VALIDATE_ZULIP_RESERVED_NAMES,
+    validate_repos => VALIDATE_REPOS,
+    validate_branch_protections => VALIDATE_BRANCH_PROTECTIONS,
+    validate_branch_protection_ci_check_names => VALIDATE_BRANCH_PROTECTION_CI_CHECK_NAMES,
+    validate_bot_webhooks => VALIDATE_BOT_WEBHOOKS,
+    validate_admin_team_size => VALIDATE_ADMIN_TEAM_SIZE,
+    validate_repo_has_admin => VALIDATE_REPO_HAS_ADMIN,
+    validate_repo_bots => VALIDATE_REPO_BOTS,
+    validate_auto_merge => VALIDATE_AUTO_MERGE,
+    validate_codeowners => VALIDATE_CODEOWNERS,
+    validate_deprecated_fields => VALIDATE_DEPRECATED_FIELDS,
 ];
 
 #[allow(clippy::type_complexity)]
-static GITHUB_CHECKS: &[Check<fn(&Data, &GitHubApi, &mut Vec<String>)>] =
-    checks![validate_github_usernames,];
+static GITHUB_CHECKS: &[Check<fn(&Data, &GitHubApi, bool, &mut Findings)>] = checks![
+    validate_github_usernames => VALIDATE_GITHUB_USERNAMES,
+    validate_account_status => VALIDATE_ACCOUNT_STATUS,
+    validate_branch_protection_ci_checks => VALIDATE_BRANCH_PROTECTION_CI_CHECKS,
+    validate_bot_webhooks_drift => VALIDATE_BOT_WEBHOOKS_DRIFT,
+    validate_repos_exist => VALIDATE_REPOS_EXIST,
+    validate_outside_collaborators => VALIDATE_OUTSIDE_COLLABORATORS,
+];
 
 #[allow(clippy::type_complexity)]
-static ZULIP_CHECKS: &[Check<fn(&Data, &ZulipApi, &mut Vec<String>)>] =
-    checks![validate_zulip_users,];
+static ZULIP_CHECKS: &[Check<fn(&Data, &ZulipApi, &mut Findings)>] = checks![
+    validate_zulip_users => VALIDATE_ZULIP_USERS,
+    validate_zulip_streams_exist => VALIDATE_ZULIP_STREAMS_EXIST,
+];
 
 struct Check<F> {
     f: F,
     name: &'static str,
+    explanation: &'static Explanation,
 }
 
-pub(crate) fn validate(data: &Data, strict: bool, skip: &[&str]) -> Result<(), Error> {
-    let mut errors = Vec::new();
+/// How to fix the errors of a check, printed by the `explain` command.
+///
+/// Each check has its explanation defined right next to it, so they're updated together.
+pub(crate) struct Explanation {
+    /// What the check enforces, and why.
+    summary: &'static str,
+    /// The concrete steps to fix the errors.
+    steps: &'static [&'static str],
+    links: &'static [&'static str],
+}
 
-    for check in CHECKS {
-        if skip.contains(&check.name) {
-            warn!("skipped check: {}", check.name);
-            continue;
+impl std::fmt::Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.summary)?;
+        writeln!(f)?;
+        writeln!(f, "How to fix it:")?;
+        for step in self.steps {
+            writeln!(f, "  - {}", step)?;
+        }
+        if !self.links.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "See also:")?;
+            for link in self.links {
+                writeln!(f, "  - {}", link)?;
+            }
         }
+        Ok(())
+    }
+}
+
+const SCHEMA_PEOPLE: &str = "docs/toml-schema.md#people";
+const SCHEMA_TEAMS: &str = "docs/toml-schema.md#teams";
+const SCHEMA_PERMISSIONS: &str = "docs/toml-schema.md#permissions";
+const SCHEMA_REPOS: &str = "docs/toml-schema.md#repos";
+
+/// The explanations of all the registered checks.
+fn explanations() -> impl Iterator<Item = (&'static str, &'static Explanation)> {
+    CHECKS
+        .iter()
+        .map(|c| (c.name, c.explanation))
+        .chain(GITHUB_CHECKS.iter().map(|c| (c.name, c.explanation)))
+        .chain(ZULIP_CHECKS.iter().map(|c| (c.name, c.explanation)))
+}
+
+/// Print how to fix the errors of the check named `query`, or of the checks whose errors on the
+/// current data contain `query`.
+pub(crate) fn explain(data: &Data, query: &str) -> Result<(), Error> {
+    let mut matching = explanations()
+        .filter(|(name, _)| *name == query)
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+    if matching.is_empty() {
+        // Only the checks that don't need an API token can be run here.
+        let local = CHECKS.iter().map(|c| c.name).collect::<Vec<_>>();
+        let report = run_checks(
+            data,
+            &CheckOptions {
+                only: &local,
+                ..CheckOptions::default()
+            },
+        )?;
+        matching = report
+            .errors
+            .iter()
+            .filter(|error| error.message.contains(query))
+            .map(|error| error.check)
+            .collect();
+        matching.dedup();
+    }
+    if matching.is_empty() {
+        bail!(
+            "no check is named `{}` or currently reports an error containing it \
+             (run `cargo run check --list-checks` to list the checks)",
+            query
+        );
+    }
 
-        (check.f)(data, &mut errors);
+    for (i, (name, explanation)) in explanations()
+        .filter(|(name, _)| matching.contains(name))
+        .enumerate()
+    {
+        if i > 0 {
+            println!();
+        }
+        println!("== {} ==", name);
+        println!();
+        print!("{}", explanation);
     }
+    Ok(())
+}
 
-    let github = GitHubApi::new();
-    if let Err(err) = github.require_auth() {
-        if strict {
-            return Err(err);
-        } else {
-            warn!("couldn't perform checks relying on the GitHub API, some errors will not be detected");
-            warn!("cause: {}", err);
+/// The names of all the registered checks.
+pub(crate) fn check_names() -> impl Iterator<Item = &'static str> {
+    CHECKS
+        .iter()
+        .map(|c| c.name)
+        .chain(GITHUB_CHECKS.iter().map(|c| c.name))
+        .chain(ZULIP_CHECKS.iter().map(|c| c.name))
+}
+
+/// The format the validation results are reported in.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ReportFormat {
+    /// Log each error.
+    Text,
+    /// Print a JUnit XML report to stdout, with a testcase for each check.
+    Junit,
+    /// Print the report as JSON to stdout.
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "text" => Ok(ReportFormat::Text),
+            "junit" => Ok(ReportFormat::Junit),
+            "json" => Ok(ReportFormat::Json),
+            other => bail!("unknown report format: {}", other),
         }
-    } else {
-        for check in GITHUB_CHECKS {
-            if skip.contains(&check.name) {
-                warn!("skipped check: {}", check.name);
-                continue;
+    }
+}
+
+/// An API the checks rely on, whose checks can be required to run with `--require`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Api {
+    GitHub,
+    Zulip,
+}
+
+impl FromStr for Api {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "github" => Ok(Api::GitHub),
+            "zulip" => Ok(Api::Zulip),
+            other => bail!("unknown API: {} (available APIs: github, zulip)", other),
+        }
+    }
+}
+
+/// Something wrong found by a check.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Finding {
+    /// Fails the validation.
+    Error(String),
+    /// Only fails the validation with `--warnings-as-errors`.
+    Warning(String),
+    /// An error resolving the members of a team, reported only once for all the teams including
+    /// the missing team.
+    MissingIncludedTeam(MissingIncludedTeam),
+    /// The entries to add to a data file to fix some of the errors, printed with `--write-stubs`.
+    Stub(String),
+}
+
+/// What a check found, in the order it found it.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Findings(Vec<Finding>);
+
+impl Findings {
+    /// Record an error.
+    fn push(&mut self, message: String) {
+        self.0.push(Finding::Error(message));
+    }
+
+    /// Record a warning, for problems too noisy to be errors but still worth fixing.
+    fn warn(&mut self, message: String) {
+        self.0.push(Finding::Warning(message));
+    }
+
+    /// Record the entries to add to a data file to fix some of the errors.
+    fn stub(&mut self, stub: String) {
+        self.0.push(Finding::Stub(stub));
+    }
+
+    /// Record the error returned by a check, keeping track of the missing included teams.
+    fn push_error(&mut self, err: Error) {
+        match err.downcast::<MissingIncludedTeam>() {
+            Ok(missing) => self.0.push(Finding::MissingIncludedTeam(missing)),
+            Err(err) => self.push(err.to_string()),
+        }
+    }
+
+    /// The messages of the errors, ignoring the warnings.
+    #[cfg(test)]
+    fn errors(self) -> Vec<String> {
+        self.0
+            .into_iter()
+            .filter_map(|finding| match finding {
+                Finding::Error(message) => Some(message),
+                Finding::MissingIncludedTeam(missing) => Some(missing.to_string()),
+                Finding::Warning(_) | Finding::Stub(_) => None,
+            })
+            .collect()
+    }
+}
+
+impl Extend<String> for Findings {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, errors: I) {
+        self.0.extend(errors.into_iter().map(Finding::Error));
+    }
+}
+
+/// A validation error or warning, tagged with the check that found it.
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ValidationError {
+    pub(crate) check: &'static str,
+    pub(crate) message: String,
+}
+
+/// The outcome of a validation run.
+#[derive(Debug, Default, serde::Serialize)]
+pub(crate) struct Report {
+    /// The names of the checks that were run, in the order they were run.
+    pub(crate) checks: Vec<&'static str>,
+    pub(crate) errors: Vec<ValidationError>,
+    pub(crate) warnings: Vec<ValidationError>,
+    /// The entries to add to the data files, only kept with `--write-stubs`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) stubs: Vec<String>,
+    /// The index in `errors` of the error about each missing included team, keyed by the
+    /// including team and the missing team, with the length of its chain of included teams.
+    #[serde(skip)]
+    missing_included_teams: HashMap<(String, String), (usize, usize)>,
+}
+
+impl Report {
+    fn record(&mut self, check: &'static str, findings: Findings) {
+        let mut findings = findings.0;
+        findings.sort();
+        findings.dedup();
+        self.checks.push(check);
+        for finding in findings {
+            match finding {
+                Finding::Error(message) => self.errors.push(ValidationError { check, message }),
+                Finding::Warning(message) => self.warnings.push(ValidationError { check, message }),
+                Finding::MissingIncludedTeam(missing) => {
+                    self.record_missing_included_team(check, missing)
+                }
+                Finding::Stub(stub) => self.stubs.push(stub),
             }
+        }
+    }
 
-            (check.f)(data, &github, &mut errors);
+    /// Record a single error per missing included team, rather than one for every team and check
+    /// failing to resolve members because of it. The error shows the longest chain of included
+    /// teams leading to the missing team.
+    fn record_missing_included_team(&mut self, check: &'static str, missing: MissingIncludedTeam) {
+        let (includer, team) = missing.root_cause();
+        let key = (includer.to_string(), team.to_string());
+        match self.missing_included_teams.get_mut(&key) {
+            Some((index, len)) => {
+                if missing.path.len() > *len {
+                    *len = missing.path.len();
+                    self.errors[*index].message = missing.to_string();
+                }
+            }
+            None => {
+                self.missing_included_teams
+                    .insert(key, (self.errors.len(), missing.path.len()));
+                self.errors.push(ValidationError {
+                    check,
+                    message: missing.to_string(),
+                });
+            }
         }
     }
 
-    let zulip = ZulipApi::new();
-    if let Err(err) = zulip.require_auth() {
-        warn!("couldn't perform checks relying on the Zulip API, some errors will not be detected");
-        warn!("cause: {}", err);
-    } else {
-        for check in ZULIP_CHECKS {
-            if skip.contains(&check.name) {
-                warn!("skipped check: {}", check.name);
+    /// Turn the warnings into errors, as requested by `--warnings-as-errors`.
+    fn promote_warnings(&mut self) {
+        let checks = &self.checks;
+        self.errors.append(&mut self.warnings);
+        // Keep the errors grouped by check, in the order the checks were run.
+        self.errors
+            .sort_by_key(|e| checks.iter().position(|check| *check == e.check));
+    }
+
+    /// Render the report as a JUnit XML test suite, with each check as a testcase and each error
+    /// as a failure of its check's testcase.
+    pub(crate) fn to_junit(&self) -> String {
+        fn escape(s: &str) -> String {
+            s.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+                .replace('\'', "&apos;")
+        }
+
+        let failed = self
+            .checks
+            .iter()
+            .filter(|check| self.errors.iter().any(|e| e.check == **check))
+            .count();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        // Writing to a String can't fail.
+        let _ = writeln!(
+            xml,
+            "<testsuite name=\"validate\" tests=\"{}\" failures=\"{}\">",
+            self.checks.len(),
+            failed
+        );
+        for check in &self.checks {
+            let errors = self
+                .errors
+                .iter()
+                .filter(|e| e.check == *check)
+                .collect::<Vec<_>>();
+            if errors.is_empty() {
+                let _ = writeln!(
+                    xml,
+                    "  <testcase classname=\"validate\" name=\"{}\"/>",
+                    check
+                );
                 continue;
             }
+            let _ = writeln!(
+                xml,
+                "  <testcase classname=\"validate\" name=\"{}\">",
+                check
+            );
+            for error in errors {
+                let _ = writeln!(xml, "    <failure message=\"{}\"/>", escape(&error.message));
+            }
+            xml.push_str("  </testcase>\n");
+        }
+        xml.push_str("</testsuite>\n");
+        xml
+    }
+}
+
+/// Which checks [`run_checks`] runs, and how.
+#[derive(Debug, Default)]
+pub(crate) struct CheckOptions<'a> {
+    /// Report the CI checks GitHub never saw as errors, and require the credentials of all the
+    /// APIs unless `require` is set.
+    pub(crate) strict: bool,
+    /// Stop as soon as a check reports errors, without running the remaining checks: the feedback
+    /// is faster, but it only includes the errors of that check.
+    pub(crate) fail_fast: bool,
+    /// The APIs whose checks must be executed, failing if there are no credentials for them.
+    pub(crate) require: &'a [Api],
+    /// The checks not to run.
+    pub(crate) skip: &'a [&'a str],
+    /// The only checks to run, if not empty.
+    pub(crate) only: &'a [&'a str],
+    /// Report the entries to add to the data files to fix some of the errors, like the
+    /// `access.individuals` of the unknown outside collaborators.
+    pub(crate) write_stubs: bool,
+}
+
+/// Run the validation checks, and report their results in the given format.
+///
+/// See [`run_checks`] for how the `options` select the checks to run. Warnings only fail the
+/// validation if `warnings_as_errors` is set, in which case they're reported as errors.
+pub(crate) fn validate(
+    data: &Data,
+    options: &CheckOptions<'_>,
+    warnings_as_errors: bool,
+    format: ReportFormat,
+) -> Result<(), Error> {
+    let mut report = run_checks(data, options)?;
+    if warnings_as_errors {
+        report.promote_warnings();
+    }
+    if !options.write_stubs {
+        report.stubs.clear();
+    }
+
+    match format {
+        ReportFormat::Text => {
+            for warning in &report.warnings {
+                warn!("validation warning: {}", warning.message);
+            }
+            // The hint is only useful to humans, and would clutter CI logs.
+            let hint = std::io::stderr().is_terminal();
+            for err in &report.errors {
+                if hint {
+                    error!(
+                        "validation error: {} (run `cargo run explain {}` for help)",
+                        err.message, err.check
+                    );
+                } else {
+                    error!("validation error: {}", err.message);
+                }
+            }
+            for stub in &report.stubs {
+                println!("{}\n", stub);
+            }
+        }
+        ReportFormat::Junit => {
+            print!("{}", report.to_junit());
+            // Keep the XML on stdout valid.
+            for stub in &report.stubs {
+                eprintln!("{}\n", stub);
+            }
+        }
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
 
-            (check.f)(data, &zulip, &mut errors);
+    if !report.errors.is_empty() {
+        if options.fail_fast {
+            bail!(
+                "{} validation errors found by {} (stopped because of --fail-fast)",
+                report.errors.len(),
+                report.errors[0].check
+            );
         }
+        bail!("{} validation errors found", report.errors.len());
     }
 
-    if !errors.is_empty() {
-        errors.sort();
-        errors.dedup_by(|a, b| a == b);
+    Ok(())
+}
+
+/// Run the validation checks, and collect their errors in a report.
+///
+/// If `only` is not empty, only the checks whose name is in it are executed. Checks whose name is
+/// in `skip` are then not executed, even if they're also in `only`: when both are provided, `only`
+/// selects the checks and `skip` further narrows them down.
+///
+/// The checks relying on an API are skipped with a warning when there are no credentials for it,
+/// unless the API is required, in which case the missing credentials are an error. The APIs in
+/// `require` are required if it's not empty, and all of them are required otherwise if `strict`
+/// is set.
+///
+/// With `fail_fast`, the checks are run one at a time, and the report is returned as soon as one
+/// of them reports errors.
+pub(crate) fn run_checks(data: &Data, options: &CheckOptions<'_>) -> Result<Report, Error> {
+    let CheckOptions {
+        strict,
+        fail_fast,
+        require,
+        skip,
+        only,
+        write_stubs: _,
+    } = *options;
+    for name in only {
+        if !check_names().any(|n| n == *name) {
+            bail!(
+                "unknown check: {} (available checks: {})",
+                name,
+                check_names().collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+    let enabled = |name: &str| {
+        if !only.is_empty() && !only.contains(&name) {
+            false
+        } else if skip.contains(&name) {
+            warn!("skipped check: {}", name);
+            false
+        } else {
+            true
+        }
+    };
+
+    let required = |api: Api| {
+        if require.is_empty() {
+            strict
+        } else {
+            require.contains(&api)
+        }
+    };
+
+    let mut report = Report::default();
+
+    let checks = CHECKS
+        .iter()
+        .filter(|c| enabled(c.name))
+        .collect::<Vec<_>>();
+    if fail_fast {
+        for check in checks {
+            let mut errors = Findings::default();
+            (check.f)(data, &mut errors);
+            report.record(check.name, errors);
+            if !report.errors.is_empty() {
+                return Ok(report);
+            }
+        }
+    } else {
+        for (check, errors) in checks.iter().zip(run_data_checks(data, &checks)) {
+            report.record(check.name, errors);
+        }
+    }
 
-        for err in &errors {
-            error!("validation error: {}", err);
+    let github_checks = GITHUB_CHECKS
+        .iter()
+        .filter(|c| enabled(c.name))
+        .collect::<Vec<_>>();
+    if !github_checks.is_empty() {
+        let github = GitHubApi::new();
+        if let Err(err) = github.require_auth() {
+            if required(Api::GitHub) {
+                return Err(err);
+            } else {
+                warn!("couldn't perform checks relying on the GitHub API, some errors will not be detected");
+                warn!("cause: {}", err);
+            }
+        } else {
+            for check in github_checks {
+                let mut errors = Findings::default();
+                (check.f)(data, &github, strict, &mut errors);
+                report.record(check.name, errors);
+                if fail_fast && !report.errors.is_empty() {
+                    return Ok(report);
+                }
+            }
+            let (hits, misses) = github.cache_stats();
+            info!("GitHub API cache: {} hits, {} misses", hits, misses);
         }
+    }
 
-        bail!("{} validation errors found", errors.len());
+    let zulip_checks = ZULIP_CHECKS
+        .iter()
+        .filter(|c| enabled(c.name))
+        .collect::<Vec<_>>();
+    if !zulip_checks.is_empty() {
+        run_zulip_checks(
+            data,
+            &ZulipApi::new(),
+            &zulip_checks,
+            required(Api::Zulip),
+            fail_fast,
+            &mut report,
+        )?;
     }
 
+    Ok(report)
+}
+
+/// Run the checks relying on the Zulip API, failing if there are no credentials for it and it's
+/// `required`.
+#[allow(clippy::type_complexity)]
+fn run_zulip_checks(
+    data: &Data,
+    zulip: &ZulipApi,
+    checks: &[&Check<fn(&Data, &ZulipApi, &mut Findings)>],
+    required: bool,
+    fail_fast: bool,
+    report: &mut Report,
+) -> Result<(), Error> {
+    if let Err(err) = zulip.require_auth() {
+        if required {
+            return Err(err);
+        }
+        warn!("couldn't perform checks relying on the Zulip API, some errors will not be detected");
+        warn!("cause: {}", err);
+        return Ok(());
+    }
+    for check in checks {
+        let mut errors = Findings::default();
+        (check.f)(data, zulip, &mut errors);
+        report.record(check.name, errors);
+        if fail_fast && !report.errors.is_empty() {
+            break;
+        }
+    }
     Ok(())
 }
 
+/// Run the checks that only rely on the data in parallel, returning the errors of each check in
+/// the same order as the checks, so that the report doesn't depend on the scheduling.
+///
+/// The checks relying on an API are run sequentially instead, as they share the API client.
+#[allow(clippy::type_complexity)]
+fn run_data_checks(data: &Data, checks: &[&Check<fn(&Data, &mut Findings)>]) -> Vec<Findings> {
+    checks
+        .par_iter()
+        .map(|check| {
+            let mut errors = Findings::default();
+            (check.f)(data, &mut errors);
+            errors
+        })
+        .collect()
+}
+
+const VALIDATE_NAME_PREFIXES: Explanation = Explanation {
+    summary: "Working groups must be named `wg-*` and project groups `project-*`, so their kind is obvious from their name.",
+    steps: &[
+        "Rename the team (both the file and its `name`) to use the right prefix, or change its `kind`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
 /// Ensure working group names start with `wg-`
-fn validate_name_prefixes(data: &Data, errors: &mut Vec<String>) {
+fn validate_name_prefixes(data: &Data, errors: &mut Findings) {
     fn ensure_prefix(
         team: &Team,
         kind: TeamKind,
@@ -147,7 +737,7 @@ fn validate_name_prefixes(data: &Data, errors: &mut Vec<String>) {
         }
         Ok(())
     }
-    wrapper(data.teams(), errors, |team, _| {
+    wrapper(data.teams().chain(data.draft_teams()), errors, |team, _| {
         ensure_prefix(team, TeamKind::WorkingGroup, "wg-", &["wg-leads"])?;
         ensure_prefix(
             team,
@@ -159,137 +749,432 @@ fn validate_name_prefixes(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
-/// Ensure `subteam-of` points to an existing team
-fn validate_subteam_of(data: &Data, errors: &mut Vec<String>) {
-    wrapper(data.teams(), errors, |mut team, _| {
-        let mut visited = Vec::new();
-        while let Some(parent) = team.subteam_of() {
-            visited.push(team.name());
-
-            if visited.contains(&parent) {
-                bail!(
-                    "team `{parent}` is a subteam of itself: {} => {parent}",
-                    visited.join(" => "),
-                );
-            }
-
-            let Some(parent) = data.team(parent) else {
-                bail!(
-                    "the parent of team `{}` doesn't exist: `{}`",
-                    team.name(),
-                    parent,
-                );
-            };
-
-            if !matches!(team.kind(), TeamKind::Team) && parent.subteam_of().is_some() {
-                bail!(
-                    "{} `{}` can't be a subteam of a subteam (`{}`)",
-                    team.kind(),
-                    team.name(),
-                    parent.name(),
-                );
-            }
+const VALIDATE_TOP_LEVEL_TEAMS: Explanation = Explanation {
+    summary: "An explicit `top-level` flag must agree with `subteam-of`: top-level teams have no parent, and other teams must have one.",
+    steps: &[
+        "Add the missing `subteam-of` to a team with `top-level = false`.",
+        "Remove either `top-level = true` or `subteam-of`, depending on where the team belongs.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
 
-            team = parent;
+/// Ensure the explicit `top-level` flag of teams is consistent with their `subteam-of`.
+fn validate_top_level_teams(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, _| {
+        match (team.explicit_top_level(), team.subteam_of()) {
+            (Some(false), None) => bail!(
+                "team `{}` is not top-level, but has no `subteam-of`",
+                team.name()
+            ),
+            (Some(true), Some(parent)) => bail!(
+                "team `{}` is top-level, but is a subteam of `{}`",
+                team.name(),
+                parent
+            ),
+            _ => {}
         }
         Ok(())
-    });
+    })
 }
 
-/// Ensure team leaders are part of the teams they lead
-fn validate_team_leads(data: &Data, errors: &mut Vec<String>) {
-    wrapper(data.teams(), errors, |team, errors| {
-        let members = team.members(data)?;
-        wrapper(team.leads().iter(), errors, |lead, _| {
-            if !members.contains(lead) {
-                bail!(
-                    "`{}` leads team `{}`, but is not a member of it",
-                    lead,
-                    team.name()
-                );
-            }
-            Ok(())
-        });
+const VALIDATE_CANONICAL_NAMES: Explanation = Explanation {
+    summary: "Teams using canonical naming should name their GitHub teams, Zulip groups and Discord roles after their slug.",
+    steps: &[
+        "Remove the explicit name, so the canonical one is used.",
+        "If the team needs a different name, add it to `canonical-naming-exceptions` in `config.toml`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Warn about teams using canonical naming whose explicit GitHub team, Zulip group or Discord
+/// role names differ from the canonical ones.
+fn validate_canonical_names(data: &Data, errors: &mut Findings) {
+    let exceptions = data.config().canonical_naming_exceptions();
+    let teams = data
+        .teams()
+        .filter(|team| team.uses_canonical_naming() && !exceptions.contains(team.name()));
+    wrapper(teams, errors, |team, errors| {
+        for (what, name, canonical) in canonical_name_mismatches(team) {
+            errors.warn(format!(
+                "the {} `{}` of team `{}` differs from its canonical name `{}`",
+                what,
+                name,
+                team.name(),
+                canonical
+            ));
+        }
         Ok(())
     });
 }
 
-/// Ensure team members are people
-fn validate_team_members(data: &Data, errors: &mut Vec<String>) {
-    wrapper(data.teams(), errors, |team, errors| {
-        wrapper(team.members(data)?.iter(), errors, |member, _| {
-            if data.person(member).is_none() {
-                bail!(
-                    "person `{}` is member of team `{}` but doesn't exist",
-                    member,
-                    team.name()
-                );
+/// The explicit names of the team differing from the canonical ones, as `(what, name, canonical)`.
+fn canonical_name_mismatches(team: &Team) -> Vec<(&'static str, &str, String)> {
+    let mut mismatches = Vec::new();
+    for name in team.github_team_names() {
+        if name != team.slug() {
+            mismatches.push(("GitHub team", name, team.slug().to_string()));
+        }
+    }
+    let canonical_zulip_group = team.canonical_zulip_group();
+    for group in team.raw_zulip_groups() {
+        if let Some(name) = group.raw_name.as_deref() {
+            if name != canonical_zulip_group {
+                mismatches.push(("Zulip group", name, canonical_zulip_group.clone()));
             }
-            Ok(())
-        });
-        Ok(())
-    });
+        }
+    }
+    for role in team.discord_roles().into_iter().flatten() {
+        if let Some(name) = role.explicit_name() {
+            if name != team.slug() {
+                mismatches.push(("Discord role", name, team.slug().to_string()));
+            }
+        }
+    }
+    mismatches
 }
 
-/// Ensure alumni are not active
-fn validate_alumni(data: &Data, errors: &mut Vec<String>) {
-    let active_members = match data.active_members() {
-        Ok(ms) => ms,
-        Err(e) => {
-            errors.push(e.to_string());
-            return;
+const VALIDATE_SUBTEAM_OF: Explanation = Explanation {
+    summary: "`subteam-of` must point to an existing team that isn't archived or a draft, and following the parents must not loop back to the team.",
+    steps: &[
+        "Fix the typo in `subteam-of`, or add the missing parent team.",
+        "If the parent team was archived, move the team under another parent, or archive it too.",
+        "If the parent team is a draft, promote it first, or make the team a draft too.",
+        "If the error shows a chain of teams, remove the `subteam-of` closing the loop.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure `subteam-of` points to an existing, active team
+fn validate_subteam_of(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, _| {
+        let ancestors = data.team_ancestors(team.name())?;
+        if !matches!(team.kind(), TeamKind::Team) && ancestors.len() > 1 {
+            bail!(
+                "{} `{}` can't be a subteam of a subteam (`{}`)",
+                team.kind(),
+                team.name(),
+                ancestors[0],
+            );
         }
-    };
-    wrapper(data.team("alumni").iter(), errors, |alumni_team, errors| {
-        let mut explicit_members: HashSet<_> = alumni_team.explicit_members().iter().collect();
-        // Ensure alumni team members are not active
-        wrapper(alumni_team.members(data)?.iter(), errors, |member, _| {
-            if active_members.contains(member) {
-                bail!("alumni team includes active member '{}'", member)
+        Ok(())
+    });
+    wrapper(data.draft_teams(), errors, |team, _| {
+        if let Some(parent) = team.subteam_of() {
+            if data.team(parent).is_none() {
+                bail!("{}", data.missing_parent_error(team, parent));
             }
-            Ok(())
-        });
-        // Ensure all explicitly named members of the alumni team are not also implicitly members
-        wrapper(
-            data.teams()
-                .filter(|t| t.name() != "alumni")
-                .flat_map(|t| t.alumni().iter().map(move |m| (t.name(), m))),
-            errors,
-            |(team, member), _| {
-                if explicit_members.remove(member) {
-                    bail!("alumni team explicitly includes member '{}' who was specified as an alumni already in '{}'", member, team)
-                }
-                Ok(())
-            },
-        );
+        }
         Ok(())
     });
 }
 
-/// Ensure every person is part of at least one team (active or archived)
-fn validate_inactive_members(data: &Data, errors: &mut Vec<String>) {
-    let mut referenced_members = HashSet::new();
+const VALIDATE_TEAM_LEADS: Explanation = Explanation {
+    summary: "Every lead of a team must be a person in `people/` and a member of the team they lead.",
+    steps: &[
+        "Add the lead to the `members` of the team.",
+        "If the lead has no file in `people/`, add one with `cargo run add-person <github-username>`.",
+    ],
+    links: &[SCHEMA_TEAMS, SCHEMA_PEOPLE],
+};
+
+/// Ensure team leaders are people and part of the teams they lead
+fn validate_team_leads(data: &Data, errors: &mut Findings) {
     wrapper(
-        data.teams().chain(data.archived_teams()),
+        data.teams().chain(data.draft_teams()),
         errors,
-        |team, _| {
+        |team, errors| {
             let members = team.members(data)?;
-            for member in members {
-                referenced_members.insert(member);
-            }
-            for person in team.alumni() {
-                referenced_members.insert(person);
-            }
-            for list in team.raw_lists() {
-                for person in &list.extra_people {
-                    referenced_members.insert(person);
+            wrapper(team.leads().iter(), errors, |lead, _| {
+                if data.person(lead).is_none() {
+                    bail!(
+                        "`{}` leads team `{}`, but doesn't exist as a person",
+                        lead,
+                        team.name()
+                    );
                 }
-            }
+                if !members.contains(lead) {
+                    bail!(
+                        "`{}` leads team `{}`, but is not a member of it",
+                        lead,
+                        team.name()
+                    );
+                }
+                Ok(())
+            });
             Ok(())
         },
     );
+}
 
-    let all_members = data.people().map(|p| p.github()).collect::<HashSet<_>>();
+const VALIDATE_TEAM_MEMBERS: Explanation = Explanation {
+    summary:
+        "Every member of a team must have a file in `people/` named after their GitHub username.",
+    steps: &[
+        "Check the spelling of the username in the team file.",
+        "Add the missing person with `cargo run add-person <github-username>`.",
+    ],
+    links: &[SCHEMA_TEAMS, SCHEMA_PEOPLE],
+};
+
+/// Ensure team members are people
+fn validate_team_members(data: &Data, errors: &mut Findings) {
+    wrapper(
+        data.teams().chain(data.draft_teams()),
+        errors,
+        |team, errors| {
+            wrapper(team.members(data)?.iter(), errors, |member, _| {
+                if data.person(member).is_none() {
+                    bail!(
+                        "person `{}` is member of team `{}` but doesn't exist",
+                        member,
+                        team.name()
+                    );
+                }
+                Ok(())
+            });
+            Ok(())
+        },
+    );
+}
+
+const VALIDATE_NONEMPTY_MEMBERSHIP: Explanation = Explanation {
+    summary: "Teams, working groups and project groups must have at least one member.",
+    steps: &[
+        "Add the members of the team, or archive the team by moving its file to `teams/archive/`.",
+        "If the team is intentionally empty, add it to `allowed-empty-teams` in `config.toml`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure teams, working groups and project groups have at least one member
+fn validate_nonempty_membership(data: &Data, errors: &mut Findings) {
+    let allowed = data.config().allowed_empty_teams();
+    wrapper(data.teams(), errors, |team, _| {
+        if team.kind() == TeamKind::MarkerTeam
+            || team.is_alumni_team()
+            || allowed.contains(team.name())
+        {
+            return Ok(());
+        }
+        if team.members(data)?.is_empty() {
+            bail!(
+                "{} `{}` doesn't have any members (if that's intentional, add it to \
+                 `allowed-empty-teams` in config.toml)",
+                team.kind(),
+                team.name()
+            );
+        }
+        Ok(())
+    });
+}
+
+const VALIDATE_MARKER_TEAMS_RESOURCES: Explanation = Explanation {
+    summary:
+        "Marker teams only group people, so they must not declare GitHub teams or mailing lists.",
+    steps: &[
+        "Remove the `[[github]]` and `[[lists]]` tables of the marker team.",
+        "If the team needs them, change its `kind` to a team or a working group.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure marker teams don't declare GitHub teams or mailing lists
+fn validate_marker_teams_resources(data: &Data, errors: &mut Findings) {
+    let allowed = data.config().marker_teams_with_resources();
+    wrapper(data.teams(), errors, |team, errors| {
+        if team.kind() != TeamKind::MarkerTeam || allowed.contains(team.name()) {
+            return Ok(());
+        }
+        wrapper(team.github_teams(data)?.iter(), errors, |github, _| {
+            bail!(
+                "marker team `{}` declares the GitHub team `{}/{}`",
+                team.name(),
+                github.org,
+                github.name
+            );
+        });
+        wrapper(team.raw_lists().iter(), errors, |list, _| {
+            bail!(
+                "marker team `{}` declares the mailing list `{}`",
+                team.name(),
+                list.address
+            );
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_PRIVATE_TEAMS: Explanation = Explanation {
+    summary:
+        "Private teams are left out of the website, so they must not have a `[website]` section.",
+    steps: &[
+        "Remove the `[website]` section of the team.",
+        "If the team should be on the website, remove `visibility = \"private\"`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure private teams don't have website data
+fn validate_private_teams(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, _| {
+        if team.is_private() && team.website_data().is_some() {
+            bail!(
+                "team `{}` is private, but has a `[website]` section: private teams are left out \
+                 of the website, so it would never be shown",
+                team.name()
+            );
+        }
+        Ok(())
+    });
+}
+
+const VALIDATE_ALUMNI: Explanation = Explanation {
+    summary: "People listed as alumni of a team must not also be active members of that team.",
+    steps: &["Remove the person from either the `members` or the `alumni` of the team."],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure alumni are not active
+fn validate_alumni(data: &Data, errors: &mut Findings) {
+    let active_members = match data.active_members() {
+        Ok(ms) => ms,
+        Err(e) => {
+            errors.push_error(e);
+            return;
+        }
+    };
+    wrapper(data.team("alumni").iter(), errors, |alumni_team, errors| {
+        let mut explicit_members: HashSet<_> = alumni_team.explicit_members().iter().collect();
+        // Ensure alumni team members are not active
+        wrapper(alumni_team.members(data)?.iter(), errors, |member, _| {
+            if active_members.contains(member) {
+                bail!("alumni team includes active member '{}'", member)
+            }
+            Ok(())
+        });
+        // Ensure all explicitly named members of the alumni team are not also implicitly members
+        wrapper(
+            data.teams()
+                .filter(|t| t.name() != "alumni")
+                .flat_map(|t| t.alumni().iter().map(move |m| (t.name(), m))),
+            errors,
+            |(team, member), _| {
+                if explicit_members.remove(member) {
+                    bail!("alumni team explicitly includes member '{}' who was specified as an alumni already in '{}'", member, team)
+                }
+                Ok(())
+            },
+        );
+        Ok(())
+    });
+}
+
+const VALIDATE_ALUMNI_EXIST: Explanation = Explanation {
+    summary:
+        "Every alumni of a team, including archived teams, must have a file in `people/`, and \
+              must not also be a member of the same team.",
+    steps: &[
+        "Check the spelling of the username in the `alumni` of the team.",
+        "Remove the person from either the `members` or the `alumni` of the team.",
+    ],
+    links: &[SCHEMA_TEAMS, SCHEMA_PEOPLE],
+};
+
+/// Ensure the alumni of every team exist, and aren't also members of it
+fn validate_alumni_exist(data: &Data, errors: &mut Findings) {
+    wrapper(
+        data.teams().chain(data.archived_teams()),
+        errors,
+        |team, errors| {
+            wrapper(team.alumni().iter(), errors, |person, _| {
+                if data.person(person).is_none() {
+                    bail!(
+                        "person `{}` is alumni of team `{}` but doesn't exist",
+                        person,
+                        team.name()
+                    );
+                }
+                if team.explicit_members().contains(person) {
+                    bail!(
+                        "person `{}` is both a member and an alumni of team `{}`",
+                        person,
+                        team.name()
+                    );
+                }
+                Ok(())
+            });
+            Ok(())
+        },
+    );
+}
+
+const VALIDATE_ALUMNI_PERMISSIONS: Explanation = Explanation {
+    summary: "Alumni must not keep the permissions granted in their person file, like review rights or bors access.",
+    steps: &[
+        "Remove the `[permissions]` of the person, or move them back to an active team.",
+        "If the person must keep the permissions, add them to `alumni-with-permissions` in `config.toml`.",
+    ],
+    links: &[SCHEMA_PEOPLE, SCHEMA_PERMISSIONS],
+};
+
+/// Ensure alumni don't have permissions of their own
+fn validate_alumni_permissions(data: &Data, errors: &mut Findings) {
+    let exempted = data.config().alumni_with_permissions();
+    wrapper(data.team("alumni").iter(), errors, |alumni_team, errors| {
+        let mut members = alumni_team.members(data)?.into_iter().collect::<Vec<_>>();
+        members.sort_unstable();
+        wrapper(members.into_iter(), errors, |member, _| {
+            if let Some(person) = data.person(member) {
+                if person.permissions().has_any() && !exempted.contains(member) {
+                    bail!(
+                        "alumni member '{}' still has permissions in people/{}.toml",
+                        member,
+                        person.file_name()
+                    );
+                }
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_INACTIVE_MEMBERS: Explanation = Explanation {
+    summary: "Every person in `people/` must be a member (or alumni) of at least one active or archived team.",
+    steps: &[
+        "Add the person to the team they belong to, or to the `alumni` of a team they were part of.",
+        "If the person isn't involved in the project anymore, delete their file.",
+    ],
+    links: &[SCHEMA_TEAMS, SCHEMA_PEOPLE],
+};
+
+/// Ensure every person is part of at least one team (active or archived)
+fn validate_inactive_members(data: &Data, errors: &mut Findings) {
+    let mut referenced_members = HashSet::new();
+    wrapper(
+        data.query().teams().chain(data.draft_teams()),
+        errors,
+        |team, _| {
+            let members = team.members(data)?;
+            for member in members {
+                referenced_members.insert(member);
+            }
+            for person in team.alumni() {
+                referenced_members.insert(person);
+            }
+            for list in team.raw_lists() {
+                for person in &list.extra_people {
+                    referenced_members.insert(person);
+                }
+            }
+            for person in team.list_only_members() {
+                referenced_members.insert(person);
+            }
+            Ok(())
+        },
+    );
+
+    let all_members = data.people().map(|p| p.github()).collect::<HashSet<_>>();
     // All the individual contributors to any Rust controlled repos
     let all_ics = data
         .repos()
@@ -311,15 +1196,42 @@ fn validate_inactive_members(data: &Data, errors: &mut Vec<String>) {
     );
 }
 
-/// Ensure every member of a team with a mailing list has an email address
-fn validate_list_email_addresses(data: &Data, errors: &mut Vec<String>) {
+const VALIDATE_LIST_EMAIL_ADDRESSES: Explanation = Explanation {
+    summary: "Every member of a team with a mailing list must have an email address, otherwise they won't receive the mails.",
+    steps: &[
+        "Add `email = \"...\"` to the person's file, even if they have a `list-email`.",
+        "If the person doesn't want to be on the lists, set `email = false` instead.",
+    ],
+    links: &[SCHEMA_PEOPLE, SCHEMA_TEAMS],
+};
+
+/// Ensure every member of a team with a mailing list, and every lead included in a list through
+/// `extra-team-leads`, has an email address
+fn validate_list_email_addresses(data: &Data, errors: &mut Findings) {
     wrapper(data.teams(), errors, |team, errors| {
         if team.lists(data)?.is_empty() {
             return Ok(());
         }
-        wrapper(team.members(data)?.iter(), errors, |member, _| {
+        let mut people = team.members(data)?;
+        for list in team.raw_lists() {
+            // Missing teams are reported by `validate_list_extra_teams`.
+            for extra in list.extra_team_leads.iter().filter_map(|t| data.team(t)) {
+                people.extend(extra.leads());
+            }
+        }
+        let mut people = people.into_iter().collect::<Vec<_>>();
+        people.sort_unstable();
+        wrapper(people.iter(), errors, |member, _| {
             if let Some(member) = data.person(member) {
                 if let Email::Missing = member.email() {
+                    // The public address is required, even if the lists use the `list-email`.
+                    if member.has_list_email() {
+                        bail!(
+                            "person `{}` is a member of a mailing list and has a `list-email`, \
+                             but has no public `email` address",
+                            member.github()
+                        );
+                    }
                     bail!(
                         "person `{}` is a member of a mailing list but has no email address",
                         member.github()
@@ -332,8 +1244,16 @@ fn validate_list_email_addresses(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
+const VALIDATE_LIST_EXTRA_PEOPLE: Explanation = Explanation {
+    summary: "The `extra-people` of a mailing list must be people in `people/`.",
+    steps: &[
+        "Fix the spelling of the username, or add the person with `cargo run add-person <github-username>`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
 /// Ensure members of extra-people in a list are real people
-fn validate_list_extra_people(data: &Data, errors: &mut Vec<String>) {
+fn validate_list_extra_people(data: &Data, errors: &mut Findings) {
     wrapper(data.teams(), errors, |team, errors| {
         wrapper(team.raw_lists().iter(), errors, |list, _| {
             for person in &list.extra_people {
@@ -351,8 +1271,70 @@ fn validate_list_extra_people(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
-/// Ensure members of extra-people in a list are real people
-fn validate_list_extra_teams(data: &Data, errors: &mut Vec<String>) {
+const VALIDATE_LIST_ONLY_MEMBERS: Explanation = Explanation {
+    summary: "The `list-only-members` of a team must be people in `people/` with an email address, and the team must have mailing lists. List-only members who became members of the team are reported as warnings, as their entry is then redundant.",
+    steps: &[
+        "Fix the spelling of the username, or add the person with `cargo run add-person <github-username>`.",
+        "Add an email address to the person's file.",
+        "Remove the people who are now members of the team from `list-only-members`.",
+    ],
+    links: &[SCHEMA_TEAMS, SCHEMA_PEOPLE],
+};
+
+/// Ensure the list-only members of a team are real people with an email address, and warn about
+/// the ones who are also members of the team
+fn validate_list_only_members(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, errors| {
+        if team.list_only_members().is_empty() {
+            return Ok(());
+        }
+        if team.raw_lists().is_empty() {
+            bail!(
+                "team `{}` has list-only-members but no mailing lists",
+                team.name()
+            );
+        }
+        let members = team.members(data)?;
+        wrapper(team.list_only_members().iter(), errors, |person, errors| {
+            let person = match data.person(person) {
+                Some(person) => person,
+                None => bail!(
+                    "person `{}` does not exist (in the list-only-members of team `{}`)",
+                    person,
+                    team.name()
+                ),
+            };
+            if !matches!(person.list_email(), Email::Present(_)) {
+                bail!(
+                    "person `{}` is a list-only member of team `{}` but has no email address",
+                    person.github(),
+                    team.name()
+                );
+            }
+            if members.contains(person.github()) {
+                errors.warn(format!(
+                    "person `{}` is a member of team `{}`, so their list-only-members entry is \
+                     redundant",
+                    person.github(),
+                    team.name()
+                ));
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_LIST_EXTRA_TEAMS: Explanation = Explanation {
+    summary: "The `extra-teams` and `extra-team-leads` of a mailing list must be existing teams.",
+    steps: &[
+        "Fix the spelling of the team name, or remove it from `extra-teams` or `extra-team-leads`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure the teams in extra-teams and extra-team-leads of a list exist
+fn validate_list_extra_teams(data: &Data, errors: &mut Findings) {
     wrapper(data.teams(), errors, |team, errors| {
         wrapper(team.raw_lists().iter(), errors, |list, _| {
             for list_team in &list.extra_teams {
@@ -364,15 +1346,179 @@ fn validate_list_extra_teams(data: &Data, errors: &mut Vec<String>) {
                     );
                 }
             }
+            for list_team in &list.extra_team_leads {
+                if data.team(list_team).is_none() {
+                    bail!(
+                        "team `{}` does not exist (in the `extra-team-leads` of list `{}`)",
+                        list_team,
+                        list.address
+                    );
+                }
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_LIST_EXTRA_TEAMS_CYCLES: Explanation = Explanation {
+    summary: "The lists of teams including each other through `extra-teams` would expand to each other forever.",
+    steps: &[
+        "Follow the chain in the error and remove one of the `extra-teams` entries closing the loop.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure the teams included in lists through extra-teams don't include each other
+fn validate_list_extra_teams_cycles(data: &Data, errors: &mut Findings) {
+    fn visit<'a>(
+        team: &'a str,
+        graph: &HashMap<&'a str, Vec<&'a str>>,
+        path: &mut Vec<&'a str>,
+        done: &mut HashSet<&'a str>,
+        cycles: &mut HashSet<Vec<&'a str>>,
+    ) {
+        if let Some(pos) = path.iter().position(|t| *t == team) {
+            // Rotate the cycle so that the same cycle is always reported in the same way.
+            let mut cycle = path[pos..].to_vec();
+            let min = cycle.iter().enumerate().min_by_key(|(_, t)| **t).unwrap().0;
+            cycle.rotate_left(min);
+            cycles.insert(cycle);
+            return;
+        }
+        if !done.insert(team) {
+            return;
+        }
+        path.push(team);
+        for next in graph.get(team).into_iter().flatten() {
+            visit(next, graph, path, done, cycles);
+        }
+        path.pop();
+    }
+
+    let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
+    wrapper(data.teams(), errors, |team, errors| {
+        wrapper(team.raw_lists().iter(), errors, |list, _| {
+            for extra in &list.extra_teams {
+                if extra == team.name() {
+                    bail!(
+                        "list `{}` includes its own team `{}` in extra-teams, which is redundant",
+                        list.address,
+                        team.name()
+                    );
+                }
+                graph.entry(team.name()).or_default().push(extra);
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+
+    let mut teams = graph.keys().copied().collect::<Vec<_>>();
+    teams.sort_unstable();
+    let mut done = HashSet::new();
+    let mut cycles = HashSet::new();
+    for team in teams {
+        visit(team, &graph, &mut Vec::new(), &mut done, &mut cycles);
+    }
+    for cycle in cycles {
+        errors.push(format!(
+            "the lists of teams include each other through extra-teams: {} => {}",
+            cycle.join(" => "),
+            cycle[0]
+        ));
+    }
+}
+
+const VALIDATE_LIST_EXPANSION: Explanation = Explanation {
+    summary: "The members of every mailing list must be expandable, which fails when teams include each other through `included-teams`.",
+    steps: &[
+        "Follow the chain in the error and remove one of the `included-teams` entries closing the loop.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure the members of every list can be expanded, without teams including each other
+fn validate_list_expansion(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, errors| {
+        wrapper(team.raw_lists().iter(), errors, |list, _| {
+            let own_team = Some(team).filter(|_| list.include_team_members);
+            // Missing teams are reported by `validate_list_extra_teams`.
+            let extra_teams = list.extra_teams.iter().filter_map(|t| data.team(t));
+            for source in own_team.into_iter().chain(extra_teams) {
+                source.members(data).map_err(|e| {
+                    err_msg(format!(
+                        "the members of list `{}` can't be expanded: {}",
+                        list.address, e
+                    ))
+                })?;
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_EXTRA_TEAM_EMAIL_COVERAGE: Explanation = Explanation {
+    summary: "Most members of a team included in a mailing list through `extra-teams` must have an email address, otherwise the list silently misses people.",
+    steps: &[
+        "Ask the members of the team to add their email address to their file in `people/`.",
+        "Alternatively, list the people with an address in `extra-people` instead of including the whole team.",
+    ],
+    links: &[SCHEMA_TEAMS, SCHEMA_PEOPLE],
+};
+
+/// Ensure most members of the teams included in lists through extra-teams have an email address
+fn validate_extra_team_email_coverage(data: &Data, errors: &mut Findings) {
+    let threshold = data.config().list_extra_teams_max_missing_emails();
+    wrapper(data.teams(), errors, |team, errors| {
+        wrapper(team.raw_lists().iter(), errors, |list, errors| {
+            wrapper(list.extra_teams.iter(), errors, |extra, _| {
+                // Missing teams are reported by `validate_list_extra_teams`.
+                let Some(extra) = data.team(extra) else {
+                    return Ok(());
+                };
+                let members = extra.members(data)?;
+                let mut missing = members
+                    .iter()
+                    .filter(|member| {
+                        data.person(member)
+                            .map(|p| !matches!(p.list_email(), Email::Present(_)))
+                            .unwrap_or(false)
+                    })
+                    .copied()
+                    .collect::<Vec<_>>();
+                if !members.is_empty() && missing.len() as f64 / members.len() as f64 > threshold {
+                    missing.sort_unstable();
+                    bail!(
+                        "list `{}` includes team `{}`, but {} of its {} members don't have an \
+                         email address: {}",
+                        list.address,
+                        extra.name(),
+                        missing.len(),
+                        members.len(),
+                        missing.join(", ")
+                    );
+                }
+                Ok(())
+            });
             Ok(())
         });
         Ok(())
     });
 }
 
+const VALIDATE_LIST_ADDRESSES: Explanation = Explanation {
+    summary: "Mailing list addresses must be valid and on one of the `allowed-mailing-lists-domains` of `config.toml`.",
+    steps: &[
+        "Fix the address of the list, or use one of the allowed domains.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
 /// Ensure the list addresses are correct
-fn validate_list_addresses(data: &Data, errors: &mut Vec<String>) {
-    let email_re = Regex::new(r"^[a-zA-Z0-9_\.-]+@([a-zA-Z0-9_\.-]+)$").unwrap();
+fn validate_list_addresses(data: &Data, errors: &mut Findings) {
+    let email_re = email_re();
     let config = data.config().allowed_mailing_lists_domains();
     wrapper(data.teams(), errors, |team, errors| {
         wrapper(team.raw_lists().iter(), errors, |list, _| {
@@ -389,29 +1535,290 @@ fn validate_list_addresses(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
+/// The regex matching valid email addresses, capturing their domain.
+fn email_re() -> &'static Regex {
+    static EMAIL_RE: OnceLock<Regex> = OnceLock::new();
+    EMAIL_RE.get_or_init(|| {
+        Regex::new(r"^[a-zA-Z0-9_\.+-]+@([a-zA-Z0-9_-]+(?:\.[a-zA-Z0-9_-]+)+)$").unwrap()
+    })
+}
+
+const VALIDATE_PEOPLE_ADDRESSES: Explanation = Explanation {
+    summary: "The email addresses of people must be valid.",
+    steps: &["Fix the `email` or the `list-email` in the person's file."],
+    links: &[SCHEMA_PEOPLE],
+};
+
 /// Ensure people email addresses are correct
-fn validate_people_addresses(data: &Data, errors: &mut Vec<String>) {
+fn validate_people_addresses(data: &Data, errors: &mut Findings) {
     wrapper(data.people(), errors, |person, _| {
-        if let Email::Present(email) = person.email() {
-            if !email.contains('@') {
-                bail!("invalid email address of `{}`: {}", person.github(), email);
+        for (field, email) in [
+            ("email", person.email()),
+            ("list-email", person.list_email()),
+        ] {
+            if let Email::Present(email) = email {
+                if !email_re().is_match(email) {
+                    bail!(
+                        "invalid {} address of `{}`: {}",
+                        field,
+                        person.github(),
+                        email
+                    );
+                }
             }
         }
         Ok(())
     });
 }
 
-/// Ensure members of teams with permissions don't explicitly have those permissions
-fn validate_duplicate_permissions(data: &Data, errors: &mut Vec<String>) {
-    wrapper(data.teams(), errors, |team, errors| {
-        wrapper(team.members(data)?.iter(), errors, |member, _| {
-            if let Some(person) = data.person(member) {
-                for permission in &Permissions::available(data.config()) {
-                    if team.permissions().has(permission)
-                        && person.permissions().has_directly(permission)
-                    {
-                        bail!(
-                            "user `{}` has the permission `{}` both explicitly and through \
+const VALIDATE_EMAIL_ON_LIST_DOMAIN: Explanation = Explanation {
+    summary: "Email addresses on a mailing lists domain are routed through the lists, and can loop or bounce.",
+    steps: &[
+        "Use a personal email address in the person's file.",
+        "If the address is managed by the infra team, set `infra-managed-email = true`.",
+    ],
+    links: &[SCHEMA_PEOPLE],
+};
+
+/// Warn about people whose email address is on one of the mailing lists domains.
+///
+/// Mail to those addresses is routed by Mailgun, so unless the address is managed by the infra team
+/// it may loop or bounce. This check is advisory: it only emits warnings.
+fn validate_email_on_list_domain(data: &Data, errors: &mut Findings) {
+    let domains = data.config().allowed_mailing_lists_domains();
+    let lists = match data.lists() {
+        Ok(lists) => lists,
+        // Errors resolving the lists are reported by the other checks.
+        Err(_) => return,
+    };
+    let list_addresses = lists.keys().map(|a| a.as_str()).collect::<HashSet<_>>();
+    for person in data.people() {
+        let mut emails = vec![person.email()];
+        if person.has_list_email() {
+            emails.push(person.list_email());
+        }
+        for email in emails {
+            let Email::Present(email) = email else {
+                continue;
+            };
+            if email_on_list_domain(
+                email,
+                person.infra_managed_email(),
+                domains,
+                &list_addresses,
+            ) {
+                errors.warn(format!(
+                    "the email address of `{}` ({}) is on a mailing lists domain: mail to it is \
+                     routed by Mailgun and may loop or bounce (set `infra-managed-email = true` \
+                     if it's managed by the infra team)",
+                    person.github(),
+                    email
+                ));
+            }
+        }
+    }
+}
+
+/// Whether an email address is on one of the mailing lists `domains` without being managed by
+/// the infra team or being a list address itself.
+fn email_on_list_domain(
+    email: &str,
+    infra_managed: bool,
+    domains: &HashSet<String>,
+    list_addresses: &HashSet<&str>,
+) -> bool {
+    let Some((_, domain)) = email.rsplit_once('@') else {
+        return false;
+    };
+    !infra_managed && domains.contains(&domain.to_lowercase()) && !list_addresses.contains(email)
+}
+
+const VALIDATE_UNIQUE_GITHUB_IDS: Explanation = Explanation {
+    summary: "No two people can have the same `github-id`, as it identifies the GitHub account.",
+    steps: &[
+        "Find the duplicated person and delete one of the two files, merging their team memberships.",
+        "If the person was renamed, rename their file instead of adding a new one.",
+    ],
+    links: &[SCHEMA_PEOPLE],
+};
+
+/// Ensure no two people share the same GitHub ID
+fn validate_unique_github_ids(data: &Data, errors: &mut Findings) {
+    validate_unique_ids(data, errors, "GitHub", |person| Some(person.github_id()));
+}
+
+const VALIDATE_UNIQUE_ZULIP_IDS: Explanation = Explanation {
+    summary: "No two people can have the same `zulip-id`, as it identifies the Zulip account and maps it to a single GitHub account in `v1/zulip-map.json`.",
+    steps: &[
+        "If the files are the same person, delete one of them, merging their team memberships.",
+        "Otherwise, fix the `zulip-id` of the person it doesn't belong to.",
+    ],
+    links: &[SCHEMA_PEOPLE],
+};
+
+/// Ensure no two people share the same Zulip ID, which is the only check of the Zulip to GitHub
+/// mapping: with unique IDs, every Zulip account maps to exactly one person.
+fn validate_unique_zulip_ids(data: &Data, errors: &mut Findings) {
+    validate_unique_ids(data, errors, "Zulip", |person| person.zulip_id());
+}
+
+const VALIDATE_UNIQUE_DISCORD_IDS: Explanation = Explanation {
+    summary: "No two people can have the same `discord-id`, as it identifies the Discord account.",
+    steps: &[
+        "If the files are the same person, delete one of them, merging their team memberships.",
+        "Otherwise, fix the `discord-id` of the person it doesn't belong to.",
+    ],
+    links: &[SCHEMA_PEOPLE],
+};
+
+/// Ensure no two people share the same Discord ID
+fn validate_unique_discord_ids(data: &Data, errors: &mut Findings) {
+    validate_unique_ids(data, errors, "Discord", |person| person.discord_id());
+}
+
+/// Report every ID shared by multiple people, listing the files of all of them.
+fn validate_unique_ids(
+    data: &Data,
+    errors: &mut Findings,
+    platform: &str,
+    id: impl Fn(&Person) -> Option<usize>,
+) {
+    let mut files: HashMap<usize, Vec<String>> = HashMap::new();
+    for person in data.people() {
+        if let Some(id) = id(person) {
+            files
+                .entry(id)
+                .or_default()
+                .push(format!("people/{}.toml", person.file_name()));
+        }
+    }
+    let mut duplicates = files
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .collect::<Vec<_>>();
+    duplicates.sort_unstable();
+    wrapper(duplicates.into_iter(), errors, |(id, mut files), _| {
+        files.sort();
+        bail!(
+            "{} ID {} is used by multiple people: {}",
+            platform,
+            id,
+            files.join(", ")
+        );
+    });
+}
+
+const VALIDATE_PERSON_FILE_NAMES: Explanation = Explanation {
+    summary: "The file of a person must be named after their GitHub username, and two people can't have usernames differing only by case.",
+    steps: &[
+        "Rename the file as suggested in the error.",
+        "If two files differ only by case, they're the same person: merge them.",
+    ],
+    links: &[SCHEMA_PEOPLE],
+};
+
+/// Ensure the people files are named after the GitHub username of the person, and that no two
+/// people have GitHub usernames differing only by case
+fn validate_person_file_names(data: &Data, errors: &mut Findings) {
+    let mut people = data.people().collect::<Vec<_>>();
+    people.sort_by_key(|person| person.file_name());
+    let mut seen: HashMap<String, &Person> = HashMap::new();
+    wrapper(people.into_iter(), errors, |person, _| {
+        if person.file_name().to_lowercase() != person.github().to_lowercase() {
+            bail!(
+                "people/{}.toml declares `github = \"{}\"`, but the file name must match the \
+                 GitHub username (rename it to people/{}.toml)",
+                person.file_name(),
+                person.github(),
+                person.github()
+            );
+        }
+        if let Some(other) = seen.insert(person.github().to_lowercase(), person) {
+            bail!(
+                "people/{}.toml (`github = \"{}\"`) and people/{}.toml (`github = \"{}\"`) \
+                 declare GitHub usernames differing only by case",
+                other.file_name(),
+                other.github(),
+                person.file_name(),
+                person.github()
+            );
+        }
+        Ok(())
+    });
+}
+
+const VALIDATE_PERSON_ROLES: Explanation = Explanation {
+    summary: "The `roles` of people must be listed in the `allowed-roles` of `config.toml`.",
+    steps: &["Fix the spelling of the role, or add it to `allowed-roles`."],
+    links: &[SCHEMA_PEOPLE],
+};
+
+/// Ensure people only have the roles allowed in the configuration
+fn validate_person_roles(data: &Data, errors: &mut Findings) {
+    wrapper(data.people(), errors, |person, errors| {
+        wrapper(person.roles().iter(), errors, |role, _| {
+            if !data.config().allowed_roles().contains(role) {
+                bail!(
+                    "person `{}` has the unknown role `{}` (maybe add it to the \
+                     `allowed-roles` in config.toml?)",
+                    person.github(),
+                    role
+                );
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_PERSON_PRONOUNS: Explanation = Explanation {
+    summary: "Pronouns are displayed on the website, so they must not be empty nor longer than 40 characters.",
+    steps: &[
+        "Shorten the `pronouns` of the person, or remove the field.",
+    ],
+    links: &[SCHEMA_PEOPLE],
+};
+
+/// Ensure people's pronouns are short enough to be displayed on the website
+fn validate_person_pronouns(data: &Data, errors: &mut Findings) {
+    const MAX_PRONOUNS_LENGTH: usize = 40;
+
+    wrapper(data.people(), errors, |person, _| {
+        if let Some(pronouns) = person.pronouns() {
+            if pronouns.trim().is_empty() {
+                bail!("person `{}` has empty pronouns", person.github());
+            }
+            if pronouns.chars().count() > MAX_PRONOUNS_LENGTH {
+                bail!(
+                    "the pronouns of person `{}` are longer than {} characters",
+                    person.github(),
+                    MAX_PRONOUNS_LENGTH
+                );
+            }
+        }
+        Ok(())
+    });
+}
+
+const VALIDATE_DUPLICATE_PERMISSIONS: Explanation = Explanation {
+    summary: "People must not be granted a permission directly when a team they're a member of already grants it.",
+    steps: &[
+        "Remove the permission from the person's file, it's already granted by the team.",
+    ],
+    links: &[SCHEMA_PERMISSIONS],
+};
+
+/// Ensure members of teams with permissions don't explicitly have those permissions
+fn validate_duplicate_permissions(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, errors| {
+        wrapper(team.members(data)?.iter(), errors, |member, _| {
+            if let Some(person) = data.person(member) {
+                for permission in &Permissions::available(data.config()) {
+                    if team.permissions().has(permission)
+                        && person.permissions().has_directly(permission)
+                    {
+                        bail!(
+                            "user `{}` has the permission `{}` both explicitly and through \
                              the `{}` team",
                             member,
                             permission,
@@ -426,8 +1833,66 @@ fn validate_duplicate_permissions(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
+const VALIDATE_DUPLICATE_LEADS_PERMISSIONS: Explanation = Explanation {
+    summary: "`leads-permissions` must not grant permissions the team or the leads already have.",
+    steps: &[
+        "Remove the permission from the `leads-permissions` of the team, or from the lead's file.",
+    ],
+    links: &[SCHEMA_PERMISSIONS, SCHEMA_TEAMS],
+};
+
+/// Ensure leads-permissions don't grant permissions the team or the leads already have
+fn validate_duplicate_leads_permissions(data: &Data, errors: &mut Findings) {
+    let available = Permissions::available(data.config());
+    wrapper(data.teams(), errors, |team, errors| {
+        let leads_permissions = available
+            .iter()
+            .filter(|permission| team.leads_permissions().has_directly(permission))
+            .collect::<Vec<_>>();
+        wrapper(leads_permissions.iter(), errors, |permission, _| {
+            if team.permissions().has(permission) {
+                bail!(
+                    "team `{}` has the permission `{}` both in `permissions` and in \
+                     `leads-permissions`",
+                    team.name(),
+                    permission
+                );
+            }
+            Ok(())
+        });
+        let mut leads = team.leads().into_iter().collect::<Vec<_>>();
+        leads.sort_unstable();
+        wrapper(leads.into_iter(), errors, |lead, _| {
+            if let Some(person) = data.person(lead) {
+                for permission in &leads_permissions {
+                    if person.permissions().has_directly(permission) {
+                        bail!(
+                            "user `{}` has the permission `{}` both explicitly and through the \
+                             `leads-permissions` of the `{}` team",
+                            lead,
+                            permission,
+                            team.name()
+                        );
+                    }
+                }
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_PERMISSIONS: Explanation = Explanation {
+    summary: "Permissions must be defined in `config.toml`, and `bors.<repo>.try` must not be granted together with `bors.<repo>.review`.",
+    steps: &[
+        "Fix the spelling of the permission, or add it to `permissions-bools` or `permissions-bors-repos` in `config.toml`.",
+        "Remove the `try` permission when `review` is also granted, as it implies it.",
+    ],
+    links: &[SCHEMA_PERMISSIONS],
+};
+
 /// Ensure the permissions are valid
-fn validate_permissions(data: &Data, errors: &mut Vec<String>) {
+fn validate_permissions(data: &Data, errors: &mut Findings) {
     wrapper(data.teams(), errors, |team, _| {
         team.permissions()
             .validate(format!("team `{}`", team.name()), data.config())?;
@@ -443,8 +1908,39 @@ fn validate_permissions(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
+const VALIDATE_ORPHANED_PERMISSIONS: Explanation = Explanation {
+    summary: "Permissions defined in `config.toml` should be granted to someone, otherwise they're likely leftovers.",
+    steps: &[
+        "Remove the permissions nobody has from `permissions-bools` or `permissions-bors-repos` in `config.toml`.",
+    ],
+    links: &[SCHEMA_PERMISSIONS],
+};
+
+/// Warn about the permissions defined in the config that nobody has
+fn validate_orphaned_permissions(data: &Data, errors: &mut Findings) {
+    wrapper(
+        Permissions::available(data.config()).iter(),
+        errors,
+        |permission, errors| {
+            if allowed_people(data, permission)?.is_empty() {
+                errors.warn(format!(
+                    "permission `{}` is defined in config.toml, but nobody has it",
+                    permission
+                ));
+            }
+            Ok(())
+        },
+    );
+}
+
+const VALIDATE_RFCBOT_LABELS: Explanation = Explanation {
+    summary: "Every team must use a different rfcbot label.",
+    steps: &["Change the `label` in the `[rfcbot]` section of one of the teams."],
+    links: &[SCHEMA_TEAMS],
+};
+
 /// Ensure there are no duplicate rfcbot labels
-fn validate_rfcbot_labels(data: &Data, errors: &mut Vec<String>) {
+fn validate_rfcbot_labels(data: &Data, errors: &mut Findings) {
     let mut labels = HashSet::new();
     wrapper(data.teams(), errors, move |team, errors| {
         if let Some(rfcbot) = team.rfcbot_data() {
@@ -456,37 +1952,137 @@ fn validate_rfcbot_labels(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
-/// Ensure rfcbot's exclude-members only contains not duplicated team members
-fn validate_rfcbot_exclude_members(data: &Data, errors: &mut Vec<String>) {
+const VALIDATE_RFCBOT_EXCLUDE_MEMBERS: Explanation = Explanation {
+    summary: "rfcbot's `exclude-members` must only contain members of the team, without duplicates. Excluding a lead of the team is reported as a warning, as their votes then don't count toward the FCP quorum.",
+    steps: &[
+        "Remove the people who aren't members of the team, and the duplicated entries.",
+        "Make sure excluding the leads is intended.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure rfcbot's exclude-members only contains not duplicated team members, warning about the
+/// excluded leads
+fn validate_rfcbot_exclude_members(data: &Data, errors: &mut Findings) {
     wrapper(data.teams(), errors, move |team, errors| {
         if let Some(rfcbot) = team.rfcbot_data() {
             let mut exclude = HashSet::new();
             let members = team.members(data)?;
-            wrapper(rfcbot.exclude_members.iter(), errors, move |member, _| {
-                if !exclude.insert(member) {
-                    bail!(
-                        "duplicate member in `{}` rfcbot.exclude-members: {}",
-                        team.name(),
-                        member
-                    );
-                }
-                if !members.contains(member.as_str()) {
-                    bail!(
-                        "person `{}` is not a member of team `{}` (in rfcbot.exclude-members)",
-                        member,
-                        team.name()
-                    );
-                }
-                Ok(())
-            });
+            let leads = team.leads();
+            wrapper(
+                rfcbot.exclude_members.iter(),
+                errors,
+                move |member, errors| {
+                    if !exclude.insert(member) {
+                        bail!(
+                            "duplicate member in `{}` rfcbot.exclude-members: {}",
+                            team.name(),
+                            member
+                        );
+                    }
+                    if !members.contains(member.as_str()) {
+                        bail!(
+                            "person `{}` is not a member of team `{}` (in rfcbot.exclude-members)",
+                            member,
+                            team.name()
+                        );
+                    }
+                    if leads.contains(member.as_str()) {
+                        errors.warn(format!(
+                            "lead `{}` of team `{}` is in rfcbot.exclude-members, so their votes \
+                         don't count toward the FCP quorum",
+                            member,
+                            team.name()
+                        ));
+                    }
+                    Ok(())
+                },
+            );
         }
         Ok(())
     });
 }
 
-/// Ensure team names are alphanumeric + `-`
-fn validate_team_names(data: &Data, errors: &mut Vec<String>) {
+const VALIDATE_RFCBOT_PING_FORMAT: Explanation = Explanation {
+    summary: "rfcbot's `ping` must not be empty, and GitHub team pings must be lowercase.",
+    steps: &["Set `ping` to the lowercase `@org/team` to mention."],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure rfcbot's ping is not empty, and that GitHub team pings are lowercase
+fn validate_rfcbot_ping_format(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, _| {
+        if let Some(rfcbot) = team.rfcbot_data() {
+            if rfcbot.ping.trim().is_empty() {
+                bail!("the rfcbot ping of team `{}` is empty", team.name());
+            }
+            // GitHub team slugs are always lowercase, and pinging a team with a different case
+            // will not notify anyone.
+            if rfcbot.ping.contains('/') && rfcbot.ping != rfcbot.ping.to_lowercase() {
+                bail!(
+                    "the rfcbot ping of team `{}` is not lowercase: `{}`",
+                    team.name(),
+                    rfcbot.ping
+                );
+            }
+        }
+        Ok(())
+    });
+}
+
+const VALIDATE_RFCBOT_PING: Explanation = Explanation {
+    summary: "rfcbot's `ping` must be one of the GitHub teams of the team, otherwise the pings don't notify anyone.",
+    steps: &[
+        "Set `ping` to the `org/name` of one of the team's GitHub teams.",
+        "If the GitHub team was renamed, update the `ping` to the new name.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure rfcbot's ping is one of the GitHub teams of the team
+fn validate_rfcbot_ping(data: &Data, errors: &mut Findings) {
     wrapper(data.teams(), errors, |team, _| {
+        if let Some(rfcbot) = team.rfcbot_data() {
+            // Empty pings are reported by `validate_rfcbot_ping_format`.
+            if rfcbot.ping.trim().is_empty() {
+                return Ok(());
+            }
+            let ping = rfcbot.ping.trim_start_matches('@');
+            let (org, name) = match ping.split_once('/') {
+                Some(split) => split,
+                None => bail!(
+                    "the rfcbot ping of team `{}` is not a GitHub team (`org/name`): `{}`",
+                    team.name(),
+                    rfcbot.ping
+                ),
+            };
+            let github_teams = team.github_teams(data)?;
+            if !github_teams.iter().any(|t| t.org == org && t.name == name) {
+                bail!(
+                    "the rfcbot ping of team `{}` is `{}`, which is not one of its GitHub teams ({})",
+                    team.name(),
+                    rfcbot.ping,
+                    github_teams
+                        .iter()
+                        .map(|t| format!("{}/{}", t.org, t.name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+        }
+        Ok(())
+    });
+}
+
+const VALIDATE_TEAM_NAMES: Explanation = Explanation {
+    summary: "Team names can only contain alphanumeric characters and `-`.",
+    steps: &["Rename the team (both the file and its `name`)."],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure team names are alphanumeric + `-`
+fn validate_team_names(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams().chain(data.draft_teams()), errors, |team, _| {
         if !team.name().chars().all(|c| c.is_alphanumeric() || c == '-') {
             bail!(
                 "team name `{}` can only be alphanumeric with dashes",
@@ -497,8 +2093,16 @@ fn validate_team_names(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
-/// Ensure GitHub teams are unique and in the allowed orgs
-fn validate_github_teams(data: &Data, errors: &mut Vec<String>) {
+const VALIDATE_GITHUB_TEAMS: Explanation = Explanation {
+    summary: "GitHub teams must be in the `allowed-github-orgs` of `config.toml`, and two teams can't manage the same GitHub team (ignoring case).",
+    steps: &[
+        "Fix the org of the `[[github]]` section, or use a different `team-name`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure GitHub teams are unique (case-insensitively) and in the allowed orgs
+fn validate_github_teams(data: &Data, errors: &mut Findings) {
     let mut found = HashMap::new();
     let allowed = data.config().allowed_github_orgs();
     wrapper(data.teams(), errors, |team, errors| {
@@ -513,7 +2117,21 @@ fn validate_github_teams(data: &Data, errors: &mut Vec<String>) {
                         team.name()
                     );
                 }
-                if let Some(other) = found.insert((gh_team.org, gh_team.name), team.name()) {
+                // GitHub compares team slugs case-insensitively.
+                let key = (gh_team.org.to_lowercase(), gh_team.name.to_lowercase());
+                if let Some((other_name, other)) = found.insert(key, (gh_team.name, team.name())) {
+                    if other_name != gh_team.name {
+                        bail!(
+                            "GitHub team `{}/{}` (in team `{}`) differs only in case from \
+                             `{}/{}` (in team `{}`)",
+                            gh_team.org,
+                            gh_team.name,
+                            team.name(),
+                            gh_team.org,
+                            other_name,
+                            other
+                        );
+                    }
                     bail!(
                         "GitHub team `{}/{}` is defined for both the `{}` and `{}` teams",
                         gh_team.org,
@@ -529,155 +2147,358 @@ fn validate_github_teams(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
+const VALIDATE_GITHUB_USERNAMES: Explanation = Explanation {
+    summary: "People must use their current GitHub username: renamed accounts are detected through their `github-id`.",
+    steps: &[
+        "Run `cargo run fix-usernames` to rename the files and update all the references.",
+    ],
+    links: &[SCHEMA_PEOPLE],
+};
+
 /// Ensure there are no misspelled GitHub account names
-fn validate_github_usernames(data: &Data, github: &GitHubApi, errors: &mut Vec<String>) {
-    let people = data
-        .people()
-        .map(|p| (p.github_id(), p))
-        .collect::<HashMap<_, _>>();
-    match github.usernames(&people.keys().cloned().collect::<Vec<_>>()) {
-        Ok(res) => wrapper(res.iter(), errors, |(id, name), _| {
-            let original = people[id].github();
-            if original != name {
-                bail!("user `{}` changed username to `{}`", original, name);
-            }
-            Ok(())
+fn validate_github_usernames(
+    data: &Data,
+    github: &GitHubApi,
+    _strict: bool,
+    errors: &mut Findings,
+) {
+    match crate::fix_usernames::renamed_people(data, github) {
+        Ok(renamed) => wrapper(renamed.iter(), errors, |(person, name), _| {
+            bail!(
+                "user `{}` changed username to `{}` (run `cargo run fix-usernames` to apply it)",
+                person.github(),
+                name
+            );
         }),
-        Err(err) => errors.push(format!("couldn't verify GitHub usernames: {}", err)),
+        Err(err) => errors.warn(format!("couldn't verify GitHub usernames: {}", err)),
     }
 }
 
-/// Ensure the user doens't put an URL as the Zulip stream name.
-fn validate_zulip_stream_name(data: &Data, errors: &mut Vec<String>) {
-    wrapper(data.teams(), errors, |team, _| {
-        if let Some(stream) = team.website_data().and_then(|ws| ws.zulip_stream()) {
-            if stream.starts_with("https://") {
-                bail!(
-                    "the zulip stream name of the team `{}` is a link: only the name is required",
-                    team.name()
-                );
-            }
+const VALIDATE_ACCOUNT_STATUS: Explanation = Explanation {
+    summary: "The GitHub accounts of team members must still exist and belong to users, otherwise mentioning them and syncing the GitHub teams fails.",
+    steps: &[
+        "If the account was suspended or deleted, ask the person whether they have a new account, and update their `github` and `github-id`.",
+        "If the person left, move them to the alumni of their teams.",
+        "If the account was converted to an organization, ask the person for their new user account.",
+    ],
+    links: &[SCHEMA_PEOPLE],
+};
+
+/// Ensure the GitHub accounts of team members weren't suspended, deleted or converted to
+/// organizations, and warn about the other people with such accounts
+fn validate_account_status(data: &Data, github: &GitHubApi, _strict: bool, errors: &mut Findings) {
+    let ids = data.people().map(|p| p.github_id()).collect::<Vec<_>>();
+    // This shares the lookups of `validate_github_usernames`.
+    let accounts = match github.accounts(&ids) {
+        Ok(accounts) => accounts,
+        Err(err) => {
+            errors.push(format!("couldn't verify the GitHub accounts: {}", err));
+            return;
         }
-        Ok(())
-    })
-}
+    };
+    let active_members = || -> Result<HashSet<&str>, Error> {
+        let mut active_members = HashSet::new();
+        for team in data.teams() {
+            active_members.extend(team.members(data)?);
+        }
+        Ok(active_members)
+    };
+    let active_members = match active_members() {
+        Ok(active_members) => active_members,
+        Err(err) => {
+            errors.push_error(err);
+            return;
+        }
+    };
 
-/// Ensure each project group has a parent team, according to RFC 2856.
-fn validate_project_groups_have_parent_teams(data: &Data, errors: &mut Vec<String>) {
-    wrapper(data.teams(), errors, |team, _| {
-        if team.kind() == TeamKind::ProjectGroup && team.subteam_of().is_none() {
+    let mut people = data.people().collect::<Vec<_>>();
+    people.sort_by_key(|p| p.github());
+    wrapper(people.into_iter(), errors, |person, errors| {
+        let active = active_members.contains(person.github());
+        let problem = match accounts.get(&person.github_id()) {
+            Some(Some(account)) if account.kind != "User" => format!(
+                "is now a GitHub account of type `{}` instead of a user",
+                account.kind
+            ),
+            Some(Some(_)) => return Ok(()),
+            Some(None) => "can't be resolved anymore (it was suspended or deleted)".to_string(),
+            None => return Ok(()),
+        };
+        if active {
             bail!(
-                "the project group `{}` doesn't have a parent team, but it's required to have one",
-                team.name()
+                "the GitHub account of `{}` (ID {}), an active team member, {}",
+                person.github(),
+                person.github_id(),
+                problem
             );
         }
-        Ok(())
-    })
-}
-
-fn validate_discord_team_members_have_discord_ids(data: &Data, errors: &mut Vec<String>) {
-    wrapper(data.teams(), errors, |team, _| {
-        if team.discord_roles().is_some() && team.name() != "all" {
-            let team_members = team.members(data)?;
-            if team_members.len() != team.discord_ids(data)?.len() {
-                let members: String = team_members
-                    .into_iter()
-                    .filter(|name| data.person(name).map(|p| p.discord_id()) == Some(None))
-                    .map(|name| format!("{}, ", name))
-                    .collect();
-
-                bail!(
-                    "the following members of the \"{}\" team do not have discord_ids: {}",
-                    team.name(),
-                    &members[..members.len() - 2]
-                );
-            }
-        }
-
+        errors.warn(format!(
+            "the GitHub account of `{}` (ID {}), not an active team member, {}",
+            person.github(),
+            person.github_id(),
+            problem
+        ));
         Ok(())
     });
 }
 
-/// Ensure every member of a team that has a Zulip group has a Zulip id
-fn validate_zulip_users(data: &Data, zulip: &ZulipApi, errors: &mut Vec<String>) {
-    let by_id = match zulip.get_users() {
-        Ok(u) => u.iter().map(|u| u.user_id).collect::<HashSet<_>>(),
+const VALIDATE_REPOS_EXIST: Explanation = Explanation {
+    summary: "The repos in `repos/` must exist on GitHub under their current name, and archived repos can't have bots or teams, otherwise the sync fails.",
+    steps: &[
+        "If the repo was renamed, rename its file and update its `name` to the new name.",
+        "If the repo was deleted, remove its file.",
+        "If the repo was archived, remove its `bots` and `access.teams`.",
+    ],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure the repos exist on GitHub, weren't renamed, and aren't archived with bots or teams.
+fn validate_repos_exist(data: &Data, github: &GitHubApi, _strict: bool, errors: &mut Findings) {
+    let mut repos = data.repos().collect::<Vec<_>>();
+    repos.sort_by(|a, b| (&a.org, &a.name).cmp(&(&b.org, &b.name)));
+    let names = repos
+        .iter()
+        .map(|repo| (repo.org.as_str(), repo.name.as_str()))
+        .collect::<Vec<_>>();
+    // A single error is reported if GitHub can't be reached, rather than one per repo.
+    let statuses = match github.repo_statuses(&names) {
+        Ok(statuses) => statuses,
         Err(err) => {
-            errors.push(format!("couldn't verify Zulip users: {}", err));
+            errors.push(format!("couldn't verify that the repos exist: {}", err));
             return;
         }
     };
-    let zulip_groups = match data.zulip_groups() {
-        Ok(zgs) => zgs,
-        Err(err) => {
-            errors.push(format!("couldn't get all the Zulip groups: {}", err));
-            return;
+    wrapper(repos.iter().zip(statuses), errors, |(repo, status), _| {
+        let full_name = format!("{}/{}", repo.org, repo.name);
+        let Some(status) = status else {
+            bail!(
+                "repo {} doesn't exist on GitHub (or the token can't access it)",
+                full_name
+            );
+        };
+        if !status.name_with_owner.eq_ignore_ascii_case(&full_name) {
+            bail!(
+                "repo {} was renamed to {} on GitHub",
+                full_name,
+                status.name_with_owner
+            );
         }
-    };
-    wrapper(zulip_groups.iter(), errors, |(group_name, group), _| {
-        let missing_members = group
-            .members()
-            .iter()
-            .filter_map(|m| match m {
-                ZulipGroupMember::MemberWithId { github, zulip_id }
-                    if !by_id.contains(zulip_id) =>
-                {
-                    Some(github.clone())
-                }
-                ZulipGroupMember::JustId(zulip_id) if !by_id.contains(zulip_id) => {
-                    Some(format!("ID: {zulip_id}"))
-                }
-                ZulipGroupMember::MemberWithoutId { github } => Some(github.clone()),
-                _ => None,
-            })
-            .collect::<HashSet<_>>();
-        if !missing_members.is_empty() {
+        if status.archived && (!repo.bots.is_empty() || !repo.access.teams.is_empty()) {
             bail!(
-                "the \"{}\" Zulip group includes members who don't appear on Zulip: {}",
-                group_name,
-                missing_members.into_iter().collect::<Vec<_>>().join(", ")
+                "repo {} is archived on GitHub, but still has bots or teams configured",
+                full_name
             );
         }
         Ok(())
-    })
+    });
 }
 
-/// Ensure every member of a team that has a Zulip group either has a Zulip id
-fn validate_zulip_group_ids(data: &Data, errors: &mut Vec<String>) {
-    wrapper(data.teams(), errors, |team, errors| {
-        let groups = team.zulip_groups(data)?;
-        // Returns if group is empty or all the groups don't include the team members
-        if groups.is_empty() || groups.iter().all(|g| !g.includes_team_members()) {
+const VALIDATE_OUTSIDE_COLLABORATORS: Explanation = Explanation {
+    summary: "The outside collaborators of the repos on GitHub must be listed in the `access.individuals` of the repo, otherwise nobody knows why they have access.",
+    steps: &[
+        "Add the collaborator to `access.individuals` if they should keep their access, or remove them from the repo on GitHub.",
+        "Run `cargo run check --only validate_outside_collaborators --write-stubs` to print the entries to add.",
+    ],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure the outside collaborators of the repos are listed in their `access.individuals`, and
+/// point out the individuals who are members of the org, as they could get access through a team.
+///
+/// Repos that don't exist yet are skipped.
+fn validate_outside_collaborators(
+    data: &Data,
+    github: &GitHubApi,
+    _strict: bool,
+    errors: &mut Findings,
+) {
+    let mut repos = data.repos().collect::<Vec<_>>();
+    repos.sort_by(|a, b| (&a.org, &a.name).cmp(&(&b.org, &b.name)));
+    let mut org_members: HashMap<&str, HashSet<String>> = HashMap::new();
+    wrapper(repos.into_iter(), errors, |repo, errors| {
+        let Some(collaborators) = github.outside_collaborators(&repo.org, &repo.name)? else {
             return Ok(());
-        }
-        wrapper(team.members(data)?.iter(), errors, |member, _| {
-            if let Some(member) = data.person(member) {
-                if member.zulip_id().is_none() {
-                    bail!(
-                        "person `{}` in '{}' is a member of a Zulip user group but has no Zulip id",
-                        member.github(),
-                        team.name()
-                    );
+        };
+        let individuals = repo
+            .access
+            .individuals
+            .keys()
+            .map(|name| name.to_lowercase())
+            .collect::<HashSet<_>>();
+        let mut stubs = Vec::new();
+        for collaborator in &collaborators {
+            if !individuals.contains(&collaborator.name.to_lowercase()) {
+                errors.push(format!(
+                    "`{}` is an outside collaborator of repo {}/{} on GitHub, but isn't in its \
+                     `access.individuals`",
+                    collaborator.name, repo.org, repo.name
+                ));
+                match collaborator.permissions.highest() {
+                    // Read access can't be granted through `access.individuals`.
+                    "read" => stubs.push(format!(
+                        "# {} only has read access, remove it on GitHub or grant another permission",
+                        collaborator.name
+                    )),
+                    permission => stubs.push(format!("{} = \"{}\"", collaborator.name, permission)),
                 }
             }
-            Ok(())
-        });
+        }
+        if !stubs.is_empty() {
+            errors.stub(format!(
+                "# repos/{}/{}.toml\n[access.individuals]\n{}",
+                repo.org,
+                repo.name,
+                stubs.join("\n")
+            ));
+        }
+
+        if !org_members.contains_key(repo.org.as_str()) {
+            let members = github
+                .org_members(&repo.org)?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|member| member.name.to_lowercase())
+                .collect();
+            org_members.insert(&repo.org, members);
+        }
+        let mut names = repo.access.individuals.keys().collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            if org_members[repo.org.as_str()].contains(&name.to_lowercase()) {
+                info!(
+                    "`{}` has individual access to repo {}/{}, but is a member of the {} org \
+                     rather than an outside collaborator",
+                    name, repo.org, repo.name, repo.org
+                );
+            }
+        }
         Ok(())
     });
 }
 
-/// Ensure members of extra-people in a Zulip user group are real people
-fn validate_zulip_group_extra_people(data: &Data, errors: &mut Vec<String>) {
-    wrapper(data.teams(), errors, |team, errors| {
-        wrapper(team.raw_zulip_groups().iter(), errors, |group, _| {
-            for person in &group.extra_people {
-                if data.person(person).is_none() {
-                    bail!(
-                        "person `{}` does not exist (in Zulip group `{}`)",
-                        person,
-                        group.name
-                    );
+const VALIDATE_BRANCH_PROTECTION_CI_CHECKS: Explanation = Explanation {
+    summary: "The CI checks required by branch protections must have been reported on the default branch, otherwise nothing can be merged.",
+    steps: &[
+        "Check the name of the CI job in the repo's workflows, and update `ci-checks` to match it.",
+    ],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure the CI checks required by the managed branch protections of the default branch of each
+/// repo were reported on that branch at least once.
+///
+/// A check might legitimately not have run on the latest commit, so unknown check names are only
+/// errors in strict mode, and warnings otherwise. Repos without any reported check are skipped.
+fn validate_branch_protection_ci_checks(
+    data: &Data,
+    github: &GitHubApi,
+    strict: bool,
+    errors: &mut Findings,
+) {
+    let repos = data.repos().filter(|repo| {
+        repo.branch_protections
+            .iter()
+            .any(|bp| bp.managed && !bp.ci_checks.is_empty())
+    });
+    wrapper(repos, errors, |repo, errors| {
+        let Some(remote) = github.repo(&repo.org, &repo.name)? else {
+            return Ok(());
+        };
+        let observed = github.check_names(&repo.org, &repo.name, &remote.default_branch)?;
+        if observed.is_empty() {
+            info!(
+                "no CI checks reported yet on {}/{}, skipping its branch protections",
+                repo.org, repo.name
+            );
+            return Ok(());
+        }
+        let protections = repo
+            .branch_protections
+            .iter()
+            .filter(|bp| bp.managed)
+            .filter(|bp| bp.pattern == remote.default_branch || bp.pattern == "*");
+        for protection in protections {
+            for check in &protection.ci_checks {
+                if observed.contains(check) {
+                    continue;
                 }
+                let message = format!(
+                    "the branch protection `{}` of {}/{} requires the CI check `{}`, which was \
+                     never reported on `{}`",
+                    protection.pattern, repo.org, repo.name, check, remote.default_branch
+                );
+                if strict {
+                    errors.push(message);
+                } else {
+                    errors.warn(message);
+                }
+            }
+        }
+        Ok(())
+    });
+}
+
+const VALIDATE_BOT_WEBHOOKS_DRIFT: Explanation = Explanation {
+    summary: "The webhooks of the bots enabled on a repo must be configured on GitHub as defined in `bot-webhooks` in `config.toml`.",
+    steps: &[
+        "Ask the infra team to sync the webhooks of the repo, or fix the `bot-webhooks` definition if it's outdated.",
+    ],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure the webhooks of the bots enabled on each repo are configured on GitHub as expected.
+///
+/// Only webhooks with a `url` in the config can be checked, and webhooks not managed by the
+/// config are ignored. Repos whose webhooks can't be read with the current token are skipped.
+fn validate_bot_webhooks_drift(
+    data: &Data,
+    github: &GitHubApi,
+    _strict: bool,
+    errors: &mut Findings,
+) {
+    let repos = data.repos().filter(|repo| {
+        repo.bots.iter().any(|bot| {
+            data.config()
+                .bot_webhook(bot)
+                .is_some_and(|spec| spec.url.is_some())
+        })
+    });
+    wrapper(repos, errors, |repo, errors| {
+        let Some(hooks) = github.repo_hooks(&repo.org, &repo.name)? else {
+            return Ok(());
+        };
+        wrapper(repo.bots.iter(), errors, |bot, _| {
+            let Some(spec) = data.config().bot_webhook(bot) else {
+                return Ok(());
+            };
+            let Some(url) = &spec.url else {
+                return Ok(());
+            };
+            let Some(hook) = hooks.iter().find(|h| h.config.url.as_ref() == Some(url)) else {
+                bail!(
+                    "the webhook of the bot `{}` ({}) is missing on {}/{}",
+                    bot.name(),
+                    url,
+                    repo.org,
+                    repo.name
+                );
+            };
+            let expected = spec.events.iter().collect::<HashSet<_>>();
+            let actual = hook.events.iter().collect::<HashSet<_>>();
+            if expected != actual {
+                let mut expected = expected.into_iter().collect::<Vec<_>>();
+                let mut actual = actual.into_iter().collect::<Vec<_>>();
+                expected.sort();
+                actual.sort();
+                bail!(
+                    "the webhook of the bot `{}` on {}/{} is configured for the events {:?}, \
+                     but {:?} are expected",
+                    bot.name(),
+                    repo.org,
+                    repo.name,
+                    actual,
+                    expected
+                );
             }
             Ok(())
         });
@@ -685,37 +2506,408 @@ fn validate_zulip_group_extra_people(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
-/// Ensure repos reference valid teams
-fn validate_repos(data: &Data, errors: &mut Vec<String>) {
-    let allowed_orgs = data.config().allowed_github_orgs();
-    let github_teams = data.github_teams();
-    wrapper(data.repos(), errors, |repo, _| {
-        if !allowed_orgs.contains(&repo.org) {
+const VALIDATE_ZULIP_STREAM_NAME: Explanation = Explanation {
+    summary: "The `zulip-stream` of a team must be the name of the stream, not a link to it.",
+    steps: &["Replace the link with the name of the stream."],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure the user doens't put an URL as the Zulip stream name.
+fn validate_zulip_stream_name(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, _| {
+        if let Some(stream) = team.website_data().and_then(|ws| ws.zulip_stream()) {
+            if stream.starts_with("https://") {
+                bail!(
+                    "the zulip stream name of the team `{}` is a link: only the name is required",
+                    team.name()
+                );
+            }
+        }
+        Ok(())
+    })
+}
+
+const VALIDATE_ZULIP_STREAMS_UNIQUE: Explanation = Explanation {
+    summary: "Teams sharing a Zulip stream are reported as warnings, as it's usually a copy-paste mistake. It's fine if the teams really discuss in the same stream.",
+    steps: &["Point each team to its own stream, unless sharing it is intended."],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Warn about teams sharing the same Zulip stream, either by name or by ID
+fn validate_zulip_streams_unique(data: &Data, errors: &mut Findings) {
+    let mut by_name: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut by_id: BTreeMap<u64, Vec<&str>> = BTreeMap::new();
+    for team in data.teams() {
+        if let Some(ws) = team.website_data() {
+            if let Some(id) = ws.zulip_stream_id() {
+                by_id.entry(id).or_default().push(team.name());
+            } else if let Some(name) = ws.zulip_stream() {
+                by_name.entry(name).or_default().push(team.name());
+            }
+        }
+    }
+    let shared = by_name
+        .into_iter()
+        .map(|(name, teams)| (format!("`{}`", name), teams))
+        .chain(
+            by_id
+                .into_iter()
+                .map(|(id, teams)| (format!("with ID {}", id), teams)),
+        );
+    for (stream, mut teams) in shared {
+        if teams.len() > 1 {
+            teams.sort_unstable();
+            errors.warn(format!(
+                "teams {} share the Zulip stream {}",
+                teams
+                    .iter()
+                    .map(|team| format!("`{}`", team))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                stream
+            ));
+        }
+    }
+}
+
+const VALIDATE_ZULIP_STREAMS_EXIST: Explanation = Explanation {
+    summary: "The `zulip-stream` of a team must be an existing stream, otherwise the link on the website is broken. Streams the API can't see, like private ones, are only reported as warnings. A `zulip-stream-id` must be the ID of an existing stream, and a `zulip-stream` set along with it is reported if the stream was renamed since.",
+    steps: &[
+        "Fix the typo in the name of the stream, using the suggested names if any.",
+        "Fix the `zulip-stream-id`, or update the `zulip-stream` to the current name of the stream.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure the Zulip streams of the teams exist, and that the stream IDs match the names
+fn validate_zulip_streams_exist(data: &Data, zulip: &ZulipApi, errors: &mut Findings) {
+    let streams = match zulip.get_streams() {
+        Ok(streams) => streams,
+        Err(err) => {
+            errors.push(format!("couldn't verify the Zulip streams: {}", err));
+            return;
+        }
+    };
+    let names = streams
+        .iter()
+        .map(|stream| stream.name.as_str())
+        .collect::<HashSet<_>>();
+    let ids = streams
+        .iter()
+        .map(|stream| (stream.stream_id, stream.name.as_str()))
+        .collect::<HashMap<_, _>>();
+    let mut teams = data.teams().collect::<Vec<_>>();
+    teams.sort_by_key(|team| team.name());
+    for team in teams {
+        let ws = match team.website_data() {
+            Some(ws) => ws,
+            None => continue,
+        };
+        match (ws.zulip_stream_id(), ws.zulip_stream()) {
+            (Some(id), name) => match ids.get(&id) {
+                None => errors.push(format!(
+                    "the Zulip stream ID {} of team `{}` doesn't match any stream",
+                    id,
+                    team.name()
+                )),
+                Some(current) if name.is_some_and(|name| name != *current) => errors.warn(format!(
+                    "the Zulip stream with ID {} of team `{}` is now named `{}`, \
+                         not `{}`",
+                    id,
+                    team.name(),
+                    current,
+                    name.unwrap()
+                )),
+                Some(_) => {}
+            },
+            (None, Some(stream)) => missing_zulip_stream(team, stream, &names, errors),
+            (None, None) => {}
+        }
+    }
+}
+
+/// Report the Zulip stream of the team if it isn't one of the `streams`.
+///
+/// The API doesn't list the private streams, so a missing stream is only an error if there's a
+/// stream with a similar name, as it's then likely a typo.
+fn missing_zulip_stream(team: &Team, stream: &str, streams: &HashSet<&str>, errors: &mut Findings) {
+    if streams.contains(stream) {
+        return;
+    }
+    let similar = crate::similar_names(stream, streams.iter().copied());
+    if similar.is_empty() {
+        errors.warn(format!(
+            "the Zulip stream `{}` of team `{}` isn't a public stream: check that it exists, \
+             unless it's private",
+            stream,
+            team.name()
+        ));
+    } else {
+        errors.push(format!(
+            "the Zulip stream `{}` of team `{}` doesn't exist (did you mean {}?)",
+            stream,
+            team.name(),
+            similar
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+}
+
+const VALIDATE_MATRIX_ROOM_NAME: Explanation = Explanation {
+    summary: "The `matrix-room` of a team must be the handle of the room, not a link to it.",
+    steps: &[
+        "Replace the value with the `#room:server` handle of the room, like `#wg-foo:matrix.org`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure the Matrix room is a `#room:server` handle rather than an URL.
+fn validate_matrix_room_name(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, _| {
+        if let Some(room) = team.website_data().and_then(|ws| ws.matrix_room()) {
+            if room.starts_with("https://") {
+                bail!(
+                    "the matrix room of the team `{}` is a link: only the `#room:server` handle is required",
+                    team.name()
+                );
+            }
+            let valid = room
+                .strip_prefix('#')
+                .and_then(|handle| handle.split_once(':'))
+                .is_some_and(|(name, server)| {
+                    !name.is_empty() && !server.is_empty() && !room.contains(char::is_whitespace)
+                });
+            if !valid {
+                bail!(
+                    "the matrix room `{}` of the team `{}` is not a `#room:server` handle",
+                    room,
+                    team.name()
+                );
+            }
+        }
+        Ok(())
+    })
+}
+
+const VALIDATE_TEAM_MEMBERSHIP: Explanation = Explanation {
+    summary: "Teams open to new members must be reachable through the `contact` they advertise.",
+    steps: &[
+        "Add a `zulip-stream` or `zulip-stream-id` (for `contact = \"zulip\"`) or a mailing list (for `contact = \"email\"`), or change the `contact`.",
+        "Explain in `how-to-join` how people can join the team.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure teams open to new members can be contacted through the channel they advertise, and
+/// warn when they don't explain how to join them.
+fn validate_team_membership(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, errors| {
+        let website = match team.website_data() {
+            Some(website) => website,
+            None => return Ok(()),
+        };
+        let membership = match website.membership() {
+            Some(membership) => membership,
+            None => return Ok(()),
+        };
+        match membership.contact {
+            Some(MembershipContact::Zulip) if !website.has_zulip_stream() => bail!(
+                "team `{}` wants to be contacted on Zulip to join it, but it has no \
+                 `zulip-stream` or `zulip-stream-id`",
+                team.name()
+            ),
+            Some(MembershipContact::Email) if team.raw_lists().is_empty() => bail!(
+                "team `{}` wants to be contacted by email to join it, but it has no lists",
+                team.name()
+            ),
+            _ => {}
+        }
+        if membership.open && membership.how_to_join.trim().is_empty() {
+            errors.warn(format!(
+                "team `{}` is open to new members but doesn't explain how to join it \
+                 (`how-to-join` is empty)",
+                team.name()
+            ));
+        }
+        Ok(())
+    });
+}
+
+const VALIDATE_UNIQUE_SIBLING_WEIGHTS: Explanation = Explanation {
+    summary: "Teams with the same parent must not use the same website `weight`, otherwise their order on the website is random.",
+    steps: &[
+        "Change the `weight` of one of the teams.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure teams with the same parent don't have the same website weight, as that would result in
+/// a nondeterministic ordering. Teams using the default weight are not considered.
+fn validate_unique_sibling_weights(data: &Data, errors: &mut Findings) {
+    let mut found = HashMap::new();
+    wrapper(data.teams(), errors, |team, _| {
+        if let Some(weight) = team.website_data().and_then(|ws| ws.explicit_weight()) {
+            if let Some(other) = found.insert((team.subteam_of(), weight), team.name()) {
+                bail!(
+                    "teams `{}` and `{}` have the same parent and the same website weight ({})",
+                    other.min(team.name()),
+                    other.max(team.name()),
+                    weight
+                );
+            }
+        }
+        Ok(())
+    });
+}
+
+const VALIDATE_PROJECT_GROUPS_HAVE_PARENT_TEAMS: Explanation = Explanation {
+    summary: "Project groups must have a parent team, which must be a team (not a working group, a project group or a marker team), according to RFC 2856.",
+    steps: &[
+        "Set `subteam-of` to the team the project group belongs to.",
+        "If the project group predates the rule, add it to `project-groups-outside-teams` in `config.toml`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure each project group has a parent team, according to RFC 2856.
+fn validate_project_groups_have_parent_teams(data: &Data, errors: &mut Findings) {
+    let allowed = data.config().project_groups_outside_teams();
+    let project_groups = data
+        .query()
+        .teams()
+        .archived(false)
+        .kind(TeamKind::ProjectGroup);
+    wrapper(project_groups, errors, |team, _| {
+        // Broken hierarchies are reported by `validate_subteam_of`.
+        let Ok(ancestors) = data.team_ancestors(team.name()) else {
+            return Ok(());
+        };
+        let Some(parent) = ancestors.first() else {
             bail!(
-                "The repo '{}' is in an invalid org '{}'",
-                repo.name,
-                repo.org
+                "the project group `{}` doesn't have a parent team, but it's required to have one",
+                team.name()
+            );
+        };
+        if let Some(parent) = data.team(parent) {
+            if parent.kind() != TeamKind::Team && !allowed.contains(team.name()) {
+                bail!(
+                    "the project group `{}` is a subteam of the {} `{}`, but project groups must \
+                     be subteams of teams",
+                    team.name(),
+                    parent.kind(),
+                    parent.name()
+                );
+            }
+        }
+        Ok(())
+    })
+}
+
+const VALIDATE_DRAFT_TEAMS: Explanation = Explanation {
+    summary: "Draft teams must have a `created-at` date, and are reported once they've been a draft for longer than `draft-teams-max-age-days` in `config.toml`.",
+    steps: &[
+        "Add the date the draft was proposed as `created-at`.",
+        "Once the team is approved, remove `draft = true` so that it's synced.",
+        "If the team isn't going to be created, remove its file.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure draft teams don't linger, promoting or removing them once they're too old
+fn validate_draft_teams(data: &Data, errors: &mut Findings) {
+    let today = chrono::Local::now().date_naive();
+    let max_age = data.config().draft_teams_max_age_days();
+    wrapper(data.draft_teams(), errors, |team, errors| {
+        let Some(created_at) = team.created_at()? else {
+            bail!(
+                "draft team `{}` has no `created-at` date, so its age can't be tracked",
+                team.name()
             );
+        };
+        let age = (today - created_at).num_days();
+        if age > max_age {
+            errors.warn(format!(
+                "team `{}` has been a draft for {} days (more than {}): promote it by removing \
+                 `draft = true`, or remove it",
+                team.name(),
+                age,
+                max_age
+            ));
         }
-        for (team_name, _) in &repo.access.teams {
-            if !github_teams.contains(&(repo.org.clone(), team_name.clone())) {
+        Ok(())
+    });
+}
+
+const VALIDATE_ELECTIONS: Explanation = Explanation {
+    summary: "Election terms must be between 1 and 60 months, and the next election must be at most a term away.",
+    steps: &[
+        "Fix the `term-months` or the `next-election` date of the `[election]` section.",
+        "Once an election happened, set `next-election` to the date of the next one.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure the election metadata of teams is sensible, and warn about overdue elections
+fn validate_elections(data: &Data, errors: &mut Findings) {
+    let today = chrono::Local::now().date_naive();
+    wrapper(data.teams(), errors, |team, errors| {
+        if let Some(election) = team.election() {
+            if !(1..=60).contains(&election.term_months) {
                 bail!(
-                        "access for {}/{} is invalid: '{}' is not configured as a GitHub team for the '{}' org",
-                        repo.org,
-                        repo.name,
-                        team_name,
-                        repo.org
-                    )
+                    "the election term of team `{}` must be between 1 and 60 months, found {}",
+                    team.name(),
+                    election.term_months
+                );
+            }
+            let next_election = election.next_election().map_err(|e| {
+                err_msg(format!("invalid election of team `{}`: {}", team.name(), e))
+            })?;
+            match election.status(today)? {
+                ElectionStatus::Overdue => errors.warn(format!(
+                    "the election of team `{}` is overdue (it was due on {})",
+                    team.name(),
+                    next_election
+                )),
+                ElectionStatus::BeyondTerm => bail!(
+                    "the next election of team `{}` ({}) is more than a term ({} months) away",
+                    team.name(),
+                    next_election,
+                    election.term_months
+                ),
+                ElectionStatus::Scheduled => {}
             }
         }
+        Ok(())
+    });
+}
 
-        for (name, _) in &repo.access.individuals {
-            if data.person(name).is_none() {
+const VALIDATE_TEAM_CREATED_AT: Explanation = Explanation {
+    summary:
+        "The `created-at` date of a team must be a date without a time, and not in the future.",
+    steps: &["Set `created-at` to the date the team was founded, like `created-at = 2018-03-01`."],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure the founding date of teams is a date, and not in the future
+fn validate_team_created_at(data: &Data, errors: &mut Findings) {
+    let today = chrono::Local::now().date_naive();
+    wrapper(data.teams(), errors, |team, _| {
+        let created_at = team.created_at().map_err(|e| {
+            err_msg(format!(
+                "invalid founding date of team `{}`: {}",
+                team.name(),
+                e
+            ))
+        })?;
+        if let Some(created_at) = created_at {
+            if created_at > today {
                 bail!(
-                    "access for {}/{} is invalid: '{}' is not the name of a person in the team repo",
-                    repo.org,
-                    repo.name,
-                    name
+                    "team `{}` was founded in the future ({})",
+                    team.name(),
+                    created_at
                 );
             }
         }
@@ -723,14 +2915,2507 @@ fn validate_repos(data: &Data, errors: &mut Vec<String>) {
     });
 }
 
-fn wrapper<T, I, F>(iter: I, errors: &mut Vec<String>, mut func: F)
-where
-    I: Iterator<Item = T>,
-    F: FnMut(T, &mut Vec<String>) -> Result<(), Error>,
-{
-    for item in iter {
-        if let Err(err) = func(item, errors) {
-            errors.push(err.to_string());
+const VALIDATE_DISCORD_TEAM_MEMBERS_HAVE_DISCORD_IDS: Explanation = Explanation {
+    summary: "Members of teams with Discord roles must have a `discord-id`, otherwise they can't be given the role.",
+    steps: &[
+        "Ask the people to add their `discord-id` to their file in `people/`.",
+        "Run `cargo run export issues --check validate_discord_team_members_have_discord_ids` to draft an issue per team.",
+    ],
+    links: &[SCHEMA_PEOPLE, SCHEMA_TEAMS],
+};
+
+fn validate_discord_team_members_have_discord_ids(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, _| {
+        if team.discord_roles().is_some() && team.name() != "all" {
+            let team_members = team.members(data)?;
+            if team_members.len() != team.discord_ids(data)?.len() {
+                let mut members = data
+                    .query()
+                    .people()
+                    .in_team(team.name())?
+                    .filter(|person| person.discord_id().is_none())
+                    .map(|person| person.github())
+                    .collect::<Vec<_>>();
+                members.sort_unstable();
+
+                bail!(
+                    "the following members of the \"{}\" team do not have discord_ids: {}",
+                    team.name(),
+                    members.join(", ")
+                );
+            }
+        }
+
+        Ok(())
+    });
+}
+
+const VALIDATE_DISCORD_COLORS: Explanation = Explanation {
+    summary: "Discord role colors must be `#RRGGBB` hex codes or the name of a color of Discord's color picker.",
+    steps: &["Fix the `color` of the Discord role, for example `color = \"#e91e63\"` or `color = \"magenta\"`."],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure the colors of Discord roles are valid
+fn validate_discord_colors(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, errors| {
+        wrapper(
+            team.discord_roles().into_iter().flatten(),
+            errors,
+            |role, _| {
+                role.hex_color().map_err(|e| {
+                    err_msg(format!(
+                        "Discord role `{}` of team `{}` has an {}",
+                        role.name(),
+                        team.name(),
+                        e
+                    ))
+                })?;
+                Ok(())
+            },
+        );
+        Ok(())
+    });
+}
+
+const VALIDATE_DISCORD_ROLE_NAMES_UNIQUE: Explanation = Explanation {
+    summary: "Two teams can't declare the same Discord role, otherwise the sync would fight over it. The `all` team is exempted, as its roles are shared with the other teams on purpose.",
+    steps: &["Rename the Discord role of one of the teams, or remove it."],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure no two teams declare the same Discord role
+fn validate_discord_role_names_unique(data: &Data, errors: &mut Findings) {
+    let mut teams = data
+        .teams()
+        .filter(|team| team.name() != "all")
+        .collect::<Vec<_>>();
+    teams.sort_by_key(|team| team.name());
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    wrapper(teams.into_iter(), errors, |team, errors| {
+        wrapper(
+            team.discord_roles().into_iter().flatten(),
+            errors,
+            |role, _| {
+                if let Some(other) = seen.insert(role.name(), team.name()) {
+                    if other != team.name() {
+                        bail!(
+                            "the Discord role `{}` is declared by both team `{}` and team `{}`",
+                            role.name(),
+                            other,
+                            team.name()
+                        );
+                    }
+                    bail!(
+                        "the Discord role `{}` is declared twice by team `{}`",
+                        role.name(),
+                        team.name()
+                    );
+                }
+                Ok(())
+            },
+        );
+        Ok(())
+    });
+}
+
+const VALIDATE_DISCORD_COLOR_CONTRAST: Explanation = Explanation {
+    summary: "Discord role colors must be readable on Discord's dark background.",
+    steps: &["Pick a lighter `color` for the Discord role."],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Warn about Discord role colors that are hard to read on Discord's dark background.
+///
+/// This check is advisory: it only emits warnings.
+fn validate_discord_color_contrast(data: &Data, errors: &mut Findings) {
+    // The background color of Discord's dark theme (#313338).
+    const BACKGROUND: (u8, u8, u8) = (0x31, 0x33, 0x38);
+    // WCAG 2.1 minimum contrast ratio for user interface components.
+    const MIN_CONTRAST: f64 = 3.0;
+
+    for team in data.teams() {
+        for role in team.discord_roles().into_iter().flatten() {
+            // Malformed colors are reported by `validate_discord_colors`.
+            let Ok(Some(color)) = role.hex_color() else {
+                continue;
+            };
+            let Some(rgb) = parse_hex_color(&color) else {
+                continue;
+            };
+            let contrast = contrast_ratio(rgb, BACKGROUND);
+            if contrast < MIN_CONTRAST {
+                errors.warn(format!(
+                    "the color `{}` of the Discord role `{}` of team `{}` has low contrast \
+                     against Discord's dark background (luminance {:.3}, contrast ratio {:.2}:1)",
+                    color,
+                    role.name(),
+                    team.name(),
+                    relative_luminance(rgb),
+                    contrast,
+                ));
+            }
+        }
+    }
+}
+
+fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Relative luminance of a color, as defined by WCAG 2.1.
+fn relative_luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    let linear = |channel: u8| {
+        let c = f64::from(channel) / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
         }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+const VALIDATE_ZULIP_USERS: Explanation = Explanation {
+    summary: "The Zulip IDs of people must belong to existing Zulip accounts.",
+    steps: &["Check the `zulip-id` in the person's file against their Zulip profile."],
+    links: &[SCHEMA_PEOPLE],
+};
+
+/// Ensure every member of a team that has a Zulip group has a Zulip id
+fn validate_zulip_users(data: &Data, zulip: &ZulipApi, errors: &mut Findings) {
+    let by_id = match zulip.get_users() {
+        Ok(u) => u.iter().map(|u| u.user_id).collect::<HashSet<_>>(),
+        Err(err) => {
+            errors.push(format!("couldn't verify Zulip users: {}", err));
+            return;
+        }
+    };
+    let zulip_groups = match data.zulip_groups() {
+        Ok(zgs) => zgs,
+        Err(err) => {
+            errors.push(format!("couldn't get all the Zulip groups: {}", err));
+            return;
+        }
+    };
+    wrapper(zulip_groups.iter(), errors, |(group_name, group), _| {
+        let missing_members = group
+            .members()
+            .iter()
+            .filter(|m| !m.zulip_id().is_some_and(|id| by_id.contains(&id)))
+            .map(|m| match m {
+                ZulipGroupMember::MemberWithId { github, .. }
+                | ZulipGroupMember::MemberWithoutId { github } => github.clone(),
+                ZulipGroupMember::JustId(zulip_id) => format!("ID: {zulip_id}"),
+            })
+            .collect::<HashSet<_>>();
+        if !missing_members.is_empty() {
+            bail!(
+                "the \"{}\" Zulip group includes members who don't appear on Zulip: {}",
+                group_name,
+                missing_members.into_iter().collect::<Vec<_>>().join(", ")
+            );
+        }
+        Ok(())
+    })
+}
+
+const VALIDATE_ZULIP_GROUP_IDS: Explanation = Explanation {
+    summary: "Members of teams with a Zulip group must have a `zulip-id`, otherwise they can't be added to the group.",
+    steps: &[
+        "Add the `zulip-id` of the person (in their Zulip profile, under \"three dots\" > \"Copy user ID\").",
+    ],
+    links: &[SCHEMA_PEOPLE],
+};
+
+/// Ensure every member of a team that has a Zulip group either has a Zulip id
+fn validate_zulip_group_ids(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, errors| {
+        let groups = team.zulip_groups(data)?;
+        // Returns if group is empty or all the groups don't include the team members
+        if groups.is_empty() || groups.iter().all(|g| !g.includes_team_members()) {
+            return Ok(());
+        }
+        wrapper(team.members(data)?.iter(), errors, |member, _| {
+            if let Some(member) = data.person(member) {
+                if member.zulip_id().is_none() {
+                    bail!(
+                        "person `{}` in '{}' is a member of a Zulip user group but has no Zulip id",
+                        member.github(),
+                        team.name()
+                    );
+                }
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_ZULIP_GROUP_EXTRA_PEOPLE: Explanation = Explanation {
+    summary: "The `extra-people` of a Zulip group must be people in `people/`.",
+    steps: &[
+        "Fix the spelling of the username, or add the person with `cargo run add-person <github-username>`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure members of extra-people in a Zulip user group are real people
+fn validate_zulip_group_extra_people(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, errors| {
+        wrapper(team.raw_zulip_groups().iter(), errors, |group, _| {
+            for person in &group.extra_people {
+                if data.person(person).is_none() {
+                    bail!(
+                        "person `{}` does not exist (in Zulip group `{}`)",
+                        person,
+                        group.name
+                    );
+                }
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_ZULIP_GROUP_DUPLICATE_IDS: Explanation = Explanation {
+    summary: "People added to a Zulip group through their username don't need to be in `extra-zulip-ids` too.",
+    steps: &[
+        "Remove the redundant IDs from `extra-zulip-ids`.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Warn about people added to a Zulip user group both through their GitHub username and their
+/// Zulip ID, as the `extra-zulip-ids` entry is redundant
+fn validate_zulip_group_duplicate_ids(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, errors| {
+        for group in team.zulip_groups(data)? {
+            if let Some(warning) = duplicate_zulip_ids_warning(team, &group) {
+                errors.warn(warning);
+            }
+        }
+        Ok(())
+    });
+}
+
+fn duplicate_zulip_ids_warning(team: &Team, group: &ZulipGroup) -> Option<String> {
+    if group.collapsed_zulip_ids().is_empty() {
+        return None;
+    }
+    let mut ids = group.collapsed_zulip_ids().to_vec();
+    ids.sort_unstable();
+    Some(format!(
+        "the `extra-zulip-ids` {} of the Zulip group `{}` (team `{}`) are already in the group, \
+         remove them",
+        ids.iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        group.name(),
+        team.name()
+    ))
+}
+
+const VALIDATE_ZULIP_GROUP_NAMES_UNIQUE: Explanation = Explanation {
+    summary: "Zulip group names must be unique, ignoring case and surrounding whitespace like Zulip does.",
+    steps: &[
+        "Rename one of the Zulip groups.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// Ensure Zulip group names are unique, ignoring case and surrounding whitespace like Zulip does
+fn validate_zulip_group_names_unique(data: &Data, errors: &mut Findings) {
+    let mut teams = data.teams().collect::<Vec<_>>();
+    teams.sort_by_key(|team| team.name());
+    let mut names: HashMap<String, (&str, &str)> = HashMap::new();
+    wrapper(teams.into_iter(), errors, |team, errors| {
+        wrapper(team.raw_zulip_groups().iter(), errors, |group, _| {
+            let normalized = group.name.trim().to_lowercase();
+            if let Some((other_team, other_name)) =
+                names.insert(normalized, (team.name(), &group.name))
+            {
+                if other_name == group.name {
+                    bail!(
+                        "Zulip group `{}` is declared by both `{}` and `{}`",
+                        group.name,
+                        other_team,
+                        team.name()
+                    );
+                } else {
+                    bail!(
+                        "Zulip groups `{}` (in `{}`) and `{}` (in `{}`) differ only in case or \
+                         surrounding whitespace",
+                        other_name,
+                        other_team,
+                        group.name,
+                        team.name()
+                    );
+                }
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_ZULIP_RESERVED_NAMES: Explanation = Explanation {
+    summary: "Zulip group names can't use the names Zulip reserves, or be rejected by the Zulip API when the groups are synchronized.",
+    steps: &[
+        "Rename the Zulip group: it can't start with `role:`, `@` or `channel:`, contain `,`, or \
+         have surrounding whitespace, and it must be at most 100 characters long.",
+    ],
+    links: &[SCHEMA_TEAMS],
+};
+
+/// The system groups of Zulip, from https://zulip.com/help/user-groups.
+const ZULIP_SYSTEM_GROUPS: &[&str] = &[
+    "role:internet",
+    "role:everyone",
+    "role:members",
+    "role:fullmembers",
+    "role:moderators",
+    "role:administrators",
+    "role:owners",
+    "role:nobody",
+];
+
+/// The maximum length of a Zulip group name.
+const ZULIP_GROUP_NAME_MAX_LEN: usize = 100;
+
+/// Ensure Zulip group names are accepted by the Zulip API
+fn validate_zulip_reserved_names(data: &Data, errors: &mut Findings) {
+    wrapper(data.teams(), errors, |team, errors| {
+        wrapper(team.raw_zulip_groups().iter(), errors, |group, _| {
+            let name = &group.name;
+            let normalized = name.trim().to_lowercase();
+            if ZULIP_SYSTEM_GROUPS.contains(&normalized.as_str()) {
+                bail!(
+                    "Zulip group `{}` of team `{}` has the name of a Zulip system group",
+                    name,
+                    team.name()
+                );
+            }
+            if let Some(prefix) = ["role:", "@", "channel:"]
+                .iter()
+                .find(|prefix| normalized.starts_with(*prefix))
+            {
+                bail!(
+                    "Zulip group `{}` of team `{}` starts with `{}`, which is reserved by Zulip",
+                    name,
+                    team.name(),
+                    prefix
+                );
+            }
+            if name.contains(',') {
+                bail!(
+                    "Zulip group `{}` of team `{}` contains a `,`",
+                    name,
+                    team.name()
+                );
+            }
+            if name.trim() != name {
+                bail!(
+                    "Zulip group `{}` of team `{}` has leading or trailing whitespace",
+                    name,
+                    team.name()
+                );
+            }
+            if name.chars().count() > ZULIP_GROUP_NAME_MAX_LEN {
+                bail!(
+                    "Zulip group `{}` of team `{}` is longer than {} characters",
+                    name,
+                    team.name(),
+                    ZULIP_GROUP_NAME_MAX_LEN
+                );
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_REPOS: Explanation = Explanation {
+    summary: "Repos must be in the allowed orgs, and only give access to existing GitHub teams and people with known permissions. Only the GitHub teams marked with `repo-admin` can be granted admin access.",
+    steps: &[
+        "Fix the team, person or permission mentioned in the error in the repo's file.",
+        "If the team must administer the repo, set `repo-admin = true` in its `[[github]]` table, which needs the approval of the org owners.",
+    ],
+    links: &[SCHEMA_REPOS, SCHEMA_TEAMS],
+};
+
+/// Ensure repos reference valid teams
+fn validate_repos(data: &Data, errors: &mut Findings) {
+    let allowed_orgs = data.config().allowed_github_orgs();
+    let github_teams = data.github_teams();
+    let admin_teams = data
+        .teams()
+        .flat_map(|team| team.github_teams(data).unwrap_or_default())
+        .filter(|github_team| github_team.repo_admin)
+        .map(|github_team| (github_team.org.to_owned(), github_team.name.to_owned()))
+        .collect::<HashSet<_>>();
+    wrapper(data.repos(), errors, |repo, _| {
+        if !allowed_orgs.contains(&repo.org) {
+            bail!(
+                "The repo '{}' is in an invalid org '{}'",
+                repo.name,
+                repo.org
+            );
+        }
+        for team_name in repo.access.teams.keys() {
+            if !github_teams.contains(&(repo.org.clone(), team_name.clone())) {
+                bail!(
+                        "access for {}/{} is invalid: '{}' is not configured as a GitHub team for the '{}' org",
+                        repo.org,
+                        repo.name,
+                        team_name,
+                        repo.org
+                    )
+            }
+        }
+
+        for (team_name, permission) in &repo.access.teams {
+            if *permission == RepoPermission::Admin
+                && !admin_teams.contains(&(repo.org.clone(), team_name.clone()))
+            {
+                bail!(
+                    "access for {}/{} is invalid: '{}' is granted admin, but its GitHub team isn't \
+                     marked with `repo-admin = true`",
+                    repo.org,
+                    repo.name,
+                    team_name
+                );
+            }
+        }
+
+        for name in repo.access.individuals.keys() {
+            if data.person(name).is_none() {
+                bail!(
+                    "access for {}/{} is invalid: '{}' is not the name of a person in the team repo",
+                    repo.org,
+                    repo.name,
+                    name
+                );
+            }
+        }
+
+        for (name, permission) in repo
+            .access
+            .teams
+            .iter()
+            .chain(repo.access.individuals.iter())
+        {
+            validate_repo_permission(permission, data.config()).map_err(|e| {
+                err_msg(format!(
+                    "access for {}/{} is invalid: '{}' {}",
+                    repo.org, repo.name, name, e
+                ))
+            })?;
+        }
+        Ok(())
+    });
+}
+
+/// Ensure custom repository roles are known
+fn validate_repo_permission(permission: &RepoPermission, config: &Config) -> Result<(), Error> {
+    if let RepoPermission::Custom(role) = permission {
+        if !config.custom_repo_roles().contains(role) {
+            bail!(
+                "has the unknown repository role '{}' (custom roles must be listed in \
+                 `custom-repo-roles` in config.toml)",
+                role
+            );
+        }
+    }
+    Ok(())
+}
+
+const VALIDATE_BOT_WEBHOOKS: Explanation = Explanation {
+    summary: "Every bot enabled on a repo must have its webhook defined in `bot-webhooks` in `config.toml`.",
+    steps: &[
+        "Add the webhook of the bot to `config.toml`, or remove the bot from the repo.",
+    ],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure every bot enabled on a repo has its webhook defined in the config
+fn validate_bot_webhooks(data: &Data, errors: &mut Findings) {
+    wrapper(data.repos(), errors, |repo, errors| {
+        wrapper(repo.bots.iter(), errors, |bot, _| {
+            if data.config().bot_webhook(bot).is_none() {
+                bail!(
+                    "the bot `{}` of {}/{} doesn't have a webhook defined in the \
+                     `bot-webhooks` table of config.toml",
+                    bot.name(),
+                    repo.org,
+                    repo.name
+                );
+            }
+            Ok(())
+        });
+        Ok(())
+    });
+}
+
+const VALIDATE_BRANCH_PROTECTIONS: Explanation = Explanation {
+    summary: "Branch protection patterns must be non-empty and unique in each repo, and can only inherit CI checks in orgs with defaults.",
+    steps: &[
+        "Fix the `pattern` of the branch protection.",
+        "Define `org-branch-protection-defaults` for the org in `config.toml`, or list the `ci-checks` explicitly.",
+        "If none of the repo's branch protections are managed (`managed = false`), remove them.",
+    ],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure branch protection patterns are not empty and unique in each repo, and warn about repos
+/// where no branch protection is managed.
+fn validate_branch_protections(data: &Data, errors: &mut Findings) {
+    wrapper(data.repos(), errors, |repo, errors| {
+        if !repo.branch_protections.is_empty()
+            && repo.branch_protections.iter().all(|bp| !bp.managed)
+        {
+            errors.warn(format!(
+                "none of the branch protections of {}/{} are managed, consider removing them",
+                repo.org, repo.name
+            ));
+        }
+        let mut patterns = HashSet::new();
+        wrapper(
+            repo.branch_protections.iter(),
+            errors,
+            |protection, errors| {
+                if protection.pattern.trim().is_empty() {
+                    bail!(
+                        "branch protection for {}/{} has an empty pattern",
+                        repo.org,
+                        repo.name
+                    );
+                }
+                if !patterns.insert(protection.pattern.as_str()) {
+                    bail!(
+                        "branch protection for {}/{} has a duplicate pattern: `{}`",
+                        repo.org,
+                        repo.name,
+                        protection.pattern
+                    );
+                }
+                validate_branch_protection_defaults(
+                    &repo.org,
+                    &repo.name,
+                    protection,
+                    data.config(),
+                    errors,
+                )
+            },
+        );
+        Ok(())
+    });
+}
+
+const VALIDATE_BRANCH_PROTECTION_CI_CHECK_NAMES: Explanation = Explanation {
+    summary: "The CI checks required by a branch protection must have a name, and be listed only once.",
+    steps: &[
+        "Remove the empty or repeated check from the `ci-checks` of the branch protection.",
+        "If the check comes from the `org-branch-protection-defaults` of `config.toml`, fix it there.",
+    ],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure the CI checks of branch protections are not empty and not duplicated
+fn validate_branch_protection_ci_check_names(data: &Data, errors: &mut Findings) {
+    wrapper(data.repos(), errors, |repo, errors| {
+        for protection in &repo.branch_protections {
+            let mut seen = HashSet::new();
+            let mut reported = HashSet::new();
+            for check in &protection.ci_checks {
+                if check.trim().is_empty() {
+                    errors.push(format!(
+                        "branch protection `{}` of {}/{} requires a CI check with an empty name",
+                        protection.pattern, repo.org, repo.name
+                    ));
+                } else if !seen.insert(check) && reported.insert(check) {
+                    errors.push(format!(
+                        "branch protection `{}` of {}/{} requires the CI check `{}` more than once",
+                        protection.pattern, repo.org, repo.name, check
+                    ));
+                }
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Ensure branch protections only inherit the CI checks of orgs with defaults, and warn about
+/// explicit CI checks identical to the defaults (they should inherit them instead).
+fn validate_branch_protection_defaults(
+    org: &str,
+    name: &str,
+    protection: &BranchProtection,
+    config: &Config,
+    errors: &mut Findings,
+) -> Result<(), Error> {
+    let defaults = config.org_branch_protection_defaults(org);
+    match (&protection.raw_ci_checks, defaults) {
+        (CiChecks::Inherit, None) => bail!(
+            "branch protection `{}` of {}/{} inherits the CI checks, but there are no \
+             `org-branch-protection-defaults` for the {} org",
+            protection.pattern,
+            org,
+            name,
+            org
+        ),
+        (CiChecks::Explicit(checks), Some(defaults)) if protection.managed => {
+            let mut checks = checks.clone();
+            let mut default_checks = defaults.ci_checks.clone();
+            checks.sort();
+            default_checks.sort();
+            if checks == default_checks {
+                errors.warn(format!(
+                    "branch protection `{}` of {}/{} lists the same CI checks as the {} org \
+                     defaults, consider inheriting them instead",
+                    protection.pattern, org, name, org
+                ));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+const VALIDATE_REPO_HAS_ADMIN: Explanation = Explanation {
+    summary: "Every repo should grant admin access to a team or a person, otherwise nobody can change its settings. This is an error for critical repos.",
+    steps: &[
+        "Grant `admin` access to the team maintaining the repo in `access.teams`.",
+        "If the repo is archived and administered only by the org owners, add it to `repos-without-admin` in `config.toml`.",
+    ],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure every repo grants admin access to at least one team or person.
+///
+/// This is only a warning for most repos, but it's an error for the critical repos defined in
+/// the configuration.
+fn validate_repo_has_admin(data: &Data, errors: &mut Findings) {
+    let exempted = data.config().repos_without_admin();
+    let critical = data.config().critical_repos();
+    wrapper(data.repos(), errors, |repo, errors| {
+        let repo_name = format!("{}/{}", repo.org, repo.name);
+        let has_admin = repo
+            .access
+            .teams
+            .values()
+            .chain(repo.access.individuals.values())
+            .any(|permission| matches!(permission, RepoPermission::Admin));
+        if !has_admin && !exempted.contains(&repo_name) {
+            let message = format!(
+                "repo {} doesn't grant admin access to any team or person",
+                repo_name
+            );
+            if critical.contains(&repo_name) {
+                bail!("{}", message);
+            }
+            errors.warn(message);
+        }
+        Ok(())
+    })
+}
+
+const VALIDATE_REPO_BOTS: Explanation = Explanation {
+    summary: "Each bot must be listed only once in the `bots` of a repo, otherwise the sync tool configures it multiple times.",
+    steps: &["Remove the repeated bot from the `bots` of the repo."],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure the bots of a repo are not duplicated
+fn validate_repo_bots(data: &Data, errors: &mut Findings) {
+    wrapper(data.repos(), errors, |repo, errors| {
+        let mut seen = HashSet::new();
+        let mut reported = HashSet::new();
+        for bot in &repo.bots {
+            if !seen.insert(bot) && reported.insert(bot) {
+                errors.push(format!(
+                    "repo {}/{} lists the bot `{}` more than once",
+                    repo.org,
+                    repo.name,
+                    bot.name()
+                ));
+            }
+        }
+        Ok(())
+    })
+}
+
+const VALIDATE_AUTO_MERGE: Explanation = Explanation {
+    summary: "Repos merged with a merge queue must not also use bors, and the checks required by the queue must run on a protected branch.",
+    steps: &[
+        "If the repo moved to a merge queue, remove `bors` from its `bots`.",
+        "Add the required check to the `ci-checks` of a branch protection, or remove it from the `required-checks` of `auto-merge`.",
+    ],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure the way a repo merges PRs is consistent with its bots and branch protections
+fn validate_auto_merge(data: &Data, errors: &mut Findings) {
+    wrapper(data.repos(), errors, |repo, errors| {
+        let Some(AutoMerge::MergeQueue { required_checks }) = &repo.auto_merge else {
+            return Ok(());
+        };
+        if repo.bots.contains(&Bot::Bors) {
+            errors.push(format!(
+                "repo {}/{} is merged with a merge queue, but also uses bors",
+                repo.org, repo.name
+            ));
+        }
+        let ci_checks = repo
+            .branch_protections
+            .iter()
+            .flat_map(|protection| &protection.ci_checks)
+            .collect::<HashSet<_>>();
+        for check in required_checks {
+            if !ci_checks.contains(check) {
+                errors.push(format!(
+                    "repo {}/{} requires the check `{}` in its merge queue, but no branch \
+                     protection runs it",
+                    repo.org, repo.name, check
+                ));
+            }
+        }
+        Ok(())
+    })
+}
+
+const VALIDATE_CODEOWNERS: Explanation = Explanation {
+    summary: "The owners in the `codeowners` of a repo must be GitHub teams or people with write access to the repo, as GitHub ignores the other ones.",
+    steps: &[
+        "Fix the name of the team or person in the `codeowners` of the repo.",
+        "Grant at least write access to the team or person in the `access` of the repo.",
+    ],
+    links: &[SCHEMA_REPOS],
+};
+
+/// Ensure the owners of the paths of a repo exist and can own code
+fn validate_codeowners(data: &Data, errors: &mut Findings) {
+    let github_teams = data.github_teams();
+    wrapper(data.repos(), errors, |repo, errors| {
+        for (pattern, owners) in &repo.codeowners {
+            let mut push = |message: String| {
+                errors.push(format!(
+                    "codeowners of {}/{} are invalid: `{}` {}",
+                    repo.org, repo.name, pattern, message
+                ))
+            };
+            if owners.teams.is_empty() && owners.people.is_empty() {
+                push("has no owners".into());
+            }
+            for team in &owners.teams {
+                if !github_teams.contains(&(repo.org.clone(), team.clone())) {
+                    push(format!(
+                        "is owned by '{}', which is not a GitHub team of the '{}' org",
+                        team, repo.org
+                    ));
+                } else if !repo.access.teams.get(team).is_some_and(can_own) {
+                    push(format!(
+                        "is owned by the team '{}', which doesn't have write access to the repo",
+                        team
+                    ));
+                }
+            }
+            for person in &owners.people {
+                if data.person(person).is_none() {
+                    push(format!(
+                        "is owned by '{}', which is not the name of a person in the team repo",
+                        person
+                    ));
+                } else if !repo.access.individuals.get(person).is_some_and(can_own) {
+                    push(format!(
+                        "is owned by '{}', who doesn't have write access to the repo",
+                        person
+                    ));
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+const VALIDATE_ADMIN_TEAM_SIZE: Explanation = Explanation {
+    summary: "Teams with admin access to a repo must have at least two members, so access doesn't depend on a single person.",
+    steps: &[
+        "Add another member to the team, or give it a lower permission on the repo.",
+    ],
+    links: &[SCHEMA_REPOS, SCHEMA_TEAMS],
+};
+
+/// Ensure teams with admin access to a repo have at least two members.
+///
+/// This is only a warning for most repos, but it's an error for the critical repos defined in
+/// the configuration.
+fn validate_admin_team_size(data: &Data, errors: &mut Findings) {
+    let mut github_teams = HashMap::new();
+    wrapper(data.teams(), errors, |team, _| {
+        for github_team in team.github_teams(data)? {
+            github_teams.insert(
+                (github_team.org, github_team.name),
+                github_team.members.len(),
+            );
+        }
+        Ok(())
+    });
+    let critical = data.config().critical_repos();
+    wrapper(data.repos(), errors, |repo, errors| {
+        let repo_name = format!("{}/{}", repo.org, repo.name);
+        wrapper(
+            repo.access
+                .teams
+                .iter()
+                .filter(|(_, permission)| matches!(permission, RepoPermission::Admin)),
+            errors,
+            |(team, _), errors| {
+                // Missing GitHub teams are reported by `validate_repos`.
+                let Some(&members) = github_teams.get(&(repo.org.as_str(), team.as_str())) else {
+                    return Ok(());
+                };
+                if members < 2 {
+                    let message = format!(
+                        "team '{}' has admin access to {} but only {} member(s): \
+                         add another member or downgrade the permission",
+                        team, repo_name, members
+                    );
+                    if critical.contains(&repo_name) {
+                        bail!("{}", message);
+                    }
+                    errors.warn(message);
+                }
+                Ok(())
+            },
+        );
+        Ok(())
+    });
+}
+
+const VALIDATE_DEPRECATED_FIELDS: Explanation = Explanation {
+    summary: "Deprecated fields of the TOML files are warnings until their removal date, and errors after it.",
+    steps: &[
+        "Follow the instructions in the message to stop using the field.",
+        "The deprecated fields are listed in the `DEPRECATIONS` table of `src/schema.rs`.",
+    ],
+    links: &[SCHEMA_PEOPLE, SCHEMA_TEAMS],
+};
+
+/// Ensure the files don't use deprecated fields after their removal date
+fn validate_deprecated_fields(data: &Data, errors: &mut Findings) {
+    let today = chrono::Local::now().date_naive();
+    wrapper(
+        data.deprecated_fields().iter(),
+        errors,
+        |(file, deprecation), errors| {
+            errors.warn(deprecation.check(file, today)?);
+            Ok(())
+        },
+    );
+}
+
+fn wrapper<T, I, F>(iter: I, errors: &mut Findings, mut func: F)
+where
+    I: Iterator<Item = T>,
+    F: FnMut(T, &mut Findings) -> Result<(), Error>,
+{
+    for item in iter {
+        if let Err(err) = func(item, errors) {
+            errors.push_error(err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::{config, person, team};
+    use crate::github::tests::serve;
+
+    #[test]
+    fn test_email_on_list_domain() {
+        let domains = vec!["rust-lang.org".to_string()].into_iter().collect();
+        let lists = vec!["infra@rust-lang.org"].into_iter().collect();
+
+        // Flagged: on a lists domain, regardless of the case.
+        assert!(email_on_list_domain(
+            "someone@rust-lang.org",
+            false,
+            &domains,
+            &lists
+        ));
+        assert!(email_on_list_domain(
+            "someone@Rust-Lang.org",
+            false,
+            &domains,
+            &lists
+        ));
+        // Exempted: managed by the infra team.
+        assert!(!email_on_list_domain(
+            "someone@rust-lang.org",
+            true,
+            &domains,
+            &lists
+        ));
+        // Exempted: the address is a list address.
+        assert!(!email_on_list_domain(
+            "infra@rust-lang.org",
+            false,
+            &domains,
+            &lists
+        ));
+        // Not on a lists domain.
+        assert!(!email_on_list_domain(
+            "someone@example.com",
+            false,
+            &domains,
+            &lists
+        ));
+    }
+
+    #[test]
+    fn test_json_report() {
+        let mut report = Report::default();
+        report.record("validate_foo", Findings::default());
+        let mut findings = Findings::default();
+        findings.push("an error".into());
+        findings.warn("a warning".into());
+        report.record("validate_bar", findings);
+
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "checks": ["validate_foo", "validate_bar"],
+                "errors": [{"check": "validate_bar", "message": "an error"}],
+                "warnings": [{"check": "validate_bar", "message": "a warning"}],
+            })
+        );
+    }
+
+    #[test]
+    fn test_promote_warnings() {
+        let mut report = Report::default();
+        let mut findings = Findings::default();
+        findings.warn("a warning".into());
+        report.record("validate_foo", findings);
+        let mut findings = Findings::default();
+        findings.push("an error".into());
+        report.record("validate_bar", findings);
+
+        report.promote_warnings();
+        assert!(report.warnings.is_empty());
+        assert_eq!(
+            report
+                .errors
+                .iter()
+                .map(|e| (e.check, e.message.as_str()))
+                .collect::<Vec<_>>(),
+            [("validate_foo", "a warning"), ("validate_bar", "an error")]
+        );
+    }
+
+    #[test]
+    fn test_unknown_custom_repo_role() {
+        let config: Config =
+            toml::from_str(&config("custom-repo-roles = ['triage-plus-labels']\n")).unwrap();
+        assert!(validate_repo_permission(&RepoPermission::Write, &config).is_ok());
+        assert!(validate_repo_permission(
+            &RepoPermission::Custom("triage-plus-labels".into()),
+            &config
+        )
+        .is_ok());
+        assert!(
+            validate_repo_permission(&RepoPermission::Custom("unknown".into()), &config).is_err()
+        );
+    }
+
+    #[test]
+    fn test_inherited_ci_checks_without_defaults() {
+        let config: Config = toml::from_str(&config(
+            "[org-branch-protection-defaults.with-defaults]\n\
+             ci-checks = ['CI']\n",
+        ))
+        .unwrap();
+        let protection: BranchProtection =
+            toml::from_str("pattern = 'master'\nci-checks = 'inherit'\n").unwrap();
+        let errors = &mut Findings::default();
+        assert!(validate_branch_protection_defaults(
+            "with-defaults",
+            "repo",
+            &protection,
+            &config,
+            errors
+        )
+        .is_ok());
+        assert!(validate_branch_protection_defaults(
+            "no-defaults",
+            "repo",
+            &protection,
+            &config,
+            errors
+        )
+        .is_err());
+
+        let omitted: BranchProtection = toml::from_str("pattern = 'master'\n").unwrap();
+        assert!(validate_branch_protection_defaults(
+            "no-defaults",
+            "repo",
+            &omitted,
+            &config,
+            errors
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_zulip_group_duplicate_ids() {
+        let data = Data::from_toml(
+            &config(""),
+            &[&person("foo", 1, "zulip-id = 42\n")],
+            &[&team(
+                "team",
+                &[],
+                &["foo"],
+                "[[zulip-groups]]\n\
+                 name = 'T-team'\n\
+                 extra-zulip-ids = [42, 43]\n",
+            )],
+        );
+        let team = data.team("team").unwrap();
+        let groups = team.zulip_groups(&data).unwrap();
+        let group = &groups[0];
+
+        let mut members = group.members().to_vec();
+        members.sort();
+        assert_eq!(
+            members,
+            vec![
+                ZulipGroupMember::MemberWithId {
+                    github: "foo".into(),
+                    zulip_id: 42
+                },
+                ZulipGroupMember::JustId(43),
+            ]
+        );
+        assert_eq!(
+            duplicate_zulip_ids_warning(team, group).unwrap(),
+            "the `extra-zulip-ids` 42 of the Zulip group `T-team` (team `team`) are already in \
+             the group, remove them"
+        );
+    }
+
+    #[test]
+    fn test_data_checks_order() {
+        let data = Data::from_toml(
+            &config(""),
+            &[
+                &person("foo", 1, "email = 'invalid'\n"),
+                &person("bar", 1, ""),
+            ],
+            &[
+                &team("wg-team", &["missing"], &["foo", "missing"], ""),
+                &team("other", &[], &[], "subteam-of = 'missing'\n"),
+            ],
+        );
+        let checks = CHECKS.iter().collect::<Vec<_>>();
+
+        let sequential = checks
+            .iter()
+            .map(|check| {
+                let mut errors = Findings::default();
+                (check.f)(&data, &mut errors);
+                errors
+            })
+            .collect::<Vec<_>>();
+        assert!(sequential.iter().filter(|e| !e.0.is_empty()).count() > 1);
+
+        for _ in 0..10 {
+            assert_eq!(run_data_checks(&data, &checks), sequential);
+        }
+    }
+
+    #[test]
+    fn test_discord_role_names_unique() {
+        let with_role = |name: &str, role: &str| {
+            team(
+                name,
+                &[],
+                &[],
+                &format!("[[discord-roles]]\nname = '{}'\n", role),
+            )
+        };
+        let teams = [
+            with_role("all", "shared"),
+            with_role("foo", "shared"),
+            with_role("bar", "bar"),
+            with_role("baz", "bar"),
+        ];
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &teams.iter().map(|t| t.as_str()).collect::<Vec<_>>(),
+        );
+
+        let mut errors = Findings::default();
+        validate_discord_role_names_unique(&data, &mut errors);
+        let errors = errors.errors();
+        assert_eq!(
+            errors,
+            ["the Discord role `bar` is declared by both team `bar` and team `baz`"]
+        );
+    }
+
+    #[test]
+    fn test_people_addresses() {
+        let with_email = |github: &str, id: usize, email: &str| {
+            person(github, id, &format!("email = '{}'\n", email))
+        };
+        let people = [
+            with_email("valid", 1, "valid+rust@example.com"),
+            with_email("no-domain", 2, "alice@"),
+            with_email("double-at", 3, "alice@@example.com"),
+            with_email("no-tld", 4, "alice@localhost"),
+            person("list", 5, "list-email = 'list@example..com'\n"),
+        ];
+        let data = Data::from_toml(
+            &config(""),
+            &people.iter().map(|p| p.as_str()).collect::<Vec<_>>(),
+            &[],
+        );
+
+        let mut errors = Findings::default();
+        validate_people_addresses(&data, &mut errors);
+        let mut errors = errors.errors();
+        errors.sort();
+        assert_eq!(
+            errors,
+            [
+                "invalid email address of `double-at`: alice@@example.com",
+                "invalid email address of `no-domain`: alice@",
+                "invalid email address of `no-tld`: alice@localhost",
+                "invalid list-email address of `list`: list@example..com",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_addresses() {
+        // The list addresses are checked with the same regex as the people addresses: `+` is
+        // allowed in them, and the domain needs a dot.
+        let with_list = |name: &str, address: &str| {
+            team(
+                name,
+                &[],
+                &[],
+                &format!("[[lists]]\naddress = '{}'\n", address),
+            )
+        };
+        let teams = [
+            with_list("valid", "valid@lists.example.com"),
+            with_list("plus", "plus+tag@lists.example.com"),
+            with_list("no-tld", "no-tld@localhost"),
+            with_list("double-dot", "double-dot@lists..example.com"),
+            with_list("foreign", "foreign@example.org"),
+        ];
+        let data = Data::from_toml(
+            &config("allowed-mailing-lists-domains = ['lists.example.com', 'localhost']\n"),
+            &[],
+            &teams.iter().map(|t| t.as_str()).collect::<Vec<_>>(),
+        );
+
+        let mut errors = Findings::default();
+        validate_list_addresses(&data, &mut errors);
+        let mut errors = errors.errors();
+        errors.sort();
+        assert_eq!(
+            errors,
+            [
+                "invalid list address: `double-dot@lists..example.com`",
+                "invalid list address: `no-tld@localhost`",
+                "list address on a domain we don't own: `foreign@example.org`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_marker_teams_resources() {
+        let data = Data::from_toml(
+            &config(
+                "allowed-github-orgs = ['org']\n\
+                 marker-teams-with-resources = ['allowed']\n",
+            ),
+            &[],
+            &[
+                &team(
+                    "marker",
+                    &[],
+                    &[],
+                    "kind = 'marker-team'\n\
+                     [[github]]\n\
+                     orgs = ['org']\n\
+                     [[lists]]\n\
+                     address = 'marker@example.com'\n",
+                ),
+                &team(
+                    "allowed",
+                    &[],
+                    &[],
+                    "kind = 'marker-team'\n\
+                     [[lists]]\n\
+                     address = 'allowed@example.com'\n",
+                ),
+                &team(
+                    "team",
+                    &[],
+                    &[],
+                    "[[lists]]\naddress = 'team@example.com'\n",
+                ),
+            ],
+        );
+
+        let mut errors = Findings::default();
+        validate_marker_teams_resources(&data, &mut errors);
+        assert_eq!(
+            errors.errors(),
+            [
+                "marker team `marker` declares the GitHub team `org/marker`",
+                "marker team `marker` declares the mailing list `marker@example.com`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_private_teams() {
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &[
+                &team(
+                    "private",
+                    &[],
+                    &[],
+                    "visibility = 'private'\n\
+                     [website]\n\
+                     name = 'Private'\n\
+                     description = 'Hidden'\n",
+                ),
+                &team(
+                    "public",
+                    &[],
+                    &[],
+                    "visibility = 'public'\n\
+                     [website]\n\
+                     name = 'Public'\n\
+                     description = 'Shown'\n",
+                ),
+            ],
+        );
+
+        let mut errors = Findings::default();
+        validate_private_teams(&data, &mut errors);
+        assert_eq!(
+            errors.errors(),
+            [
+                "team `private` is private, but has a `[website]` section: private teams are left \
+              out of the website, so it would never be shown"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_project_groups_parent_kind() {
+        let with_kind = |name: &str, kind: &str, parent: Option<&str>| {
+            let parent = parent
+                .map(|p| format!("subteam-of = '{}'\n", p))
+                .unwrap_or_default();
+            team(name, &[], &[], &format!("kind = '{}'\n{}", kind, parent))
+        };
+        let teams = [
+            with_kind("compiler", "team", None),
+            with_kind("wg-embedded", "working-group", None),
+            with_kind("project-ok", "project-group", Some("compiler")),
+            with_kind("project-in-wg", "project-group", Some("wg-embedded")),
+            with_kind("project-allowed", "project-group", Some("wg-embedded")),
+            with_kind("project-orphan", "project-group", None),
+        ];
+        let teams = teams.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+        let data = Data::from_toml(
+            &config("project-groups-outside-teams = ['project-allowed']\n"),
+            &[],
+            &teams,
+        );
+
+        let mut errors = Findings::default();
+        validate_project_groups_have_parent_teams(&data, &mut errors);
+        let mut errors = errors.errors();
+        errors.sort();
+        assert_eq!(
+            errors,
+            [
+                "the project group `project-in-wg` is a subteam of the working group \
+                 `wg-embedded`, but project groups must be subteams of teams",
+                "the project group `project-orphan` doesn't have a parent team, but it's \
+                 required to have one",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unique_ids() {
+        let data = Data::from_toml(
+            &config(""),
+            &[
+                &person("foo", 1, "zulip-id = 10\ndiscord-id = 100\n"),
+                &person("foo-renamed", 1, "zulip-id = 10\n"),
+                &person("bar", 2, "zulip-id = 10\ndiscord-id = 200\n"),
+                &person("baz", 3, "discord-id = 200\n"),
+            ],
+            &[],
+        );
+
+        let mut errors = Findings::default();
+        validate_unique_github_ids(&data, &mut errors);
+        validate_unique_zulip_ids(&data, &mut errors);
+        validate_unique_discord_ids(&data, &mut errors);
+        assert_eq!(
+            errors.errors(),
+            [
+                "GitHub ID 1 is used by multiple people: people/foo-renamed.toml, people/foo.toml",
+                "Zulip ID 10 is used by multiple people: people/bar.toml, \
+                 people/foo-renamed.toml, people/foo.toml",
+                "Discord ID 200 is used by multiple people: people/bar.toml, people/baz.toml",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alumni_exist() {
+        let data = Data::from_toml(
+            &config(""),
+            &[&person("foo", 1, ""), &person("bar", 2, "")],
+            &[&team(
+                "team",
+                &[],
+                &["foo"],
+                "people.alumni = ['foo', 'bar', 'missing']\n",
+            )],
+        );
+
+        let mut errors = Findings::default();
+        validate_alumni_exist(&data, &mut errors);
+        assert_eq!(
+            errors.errors(),
+            [
+                "person `foo` is both a member and an alumni of team `team`",
+                "person `missing` is alumni of team `team` but doesn't exist",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repo_admin_teams() {
+        let repo = |teams: &str| {
+            format!(
+                "org = 'org'\n\
+                 name = 'repo'\n\
+                 description = ''\n\
+                 bots = []\n\
+                 [access.teams]\n\
+                 {}",
+                teams
+            )
+        };
+        let data = |repo: &str| {
+            Data::from_toml(
+                &config("allowed-github-orgs = ['org']\n"),
+                &[],
+                &[
+                    &team(
+                        "admins",
+                        &[],
+                        &[],
+                        "[[github]]\norgs = ['org']\nrepo-admin = true\n",
+                    ),
+                    &team("others", &[], &[], "[[github]]\norgs = ['org']\n"),
+                ],
+            )
+            .with_repos(&[repo])
+        };
+
+        let mut errors = Findings::default();
+        validate_repos(
+            &data(&repo("admins = 'admin'\nothers = 'maintain'\n")),
+            &mut errors,
+        );
+        assert!(errors.errors().is_empty());
+
+        let mut errors = Findings::default();
+        validate_repos(&data(&repo("others = 'admin'\n")), &mut errors);
+        assert_eq!(
+            errors.errors(),
+            [
+                "access for org/repo is invalid: 'others' is granted admin, but its GitHub team \
+              isn't marked with `repo-admin = true`"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_auto_merge() {
+        let data = Data::from_toml(&config("allowed-github-orgs = ['org']\n"), &[], &[])
+            .with_repos(&[
+                "org = 'org'\n\
+                 name = 'queue'\n\
+                 description = ''\n\
+                 bots = []\n\
+                 auto-merge = { kind = 'merge-queue', required-checks = ['CI'] }\n\
+                 [access.teams]\n\
+                 [[branch-protections]]\n\
+                 pattern = 'main'\n\
+                 ci-checks = ['CI']\n",
+                "org = 'org'\n\
+                 name = 'both'\n\
+                 description = ''\n\
+                 bots = ['bors']\n\
+                 auto-merge = { kind = 'merge-queue', required-checks = ['CI', 'lint'] }\n\
+                 [access.teams]\n\
+                 [[branch-protections]]\n\
+                 pattern = 'main'\n\
+                 ci-checks = ['CI']\n",
+                "org = 'org'\n\
+                 name = 'bors'\n\
+                 description = ''\n\
+                 bots = ['bors']\n\
+                 auto-merge = 'bors'\n\
+                 [access.teams]\n",
+            ]);
+
+        let mut errors = Findings::default();
+        validate_auto_merge(&data, &mut errors);
+        let mut errors = errors.errors();
+        errors.sort();
+        assert_eq!(
+            errors,
+            [
+                "repo org/both is merged with a merge queue, but also uses bors",
+                "repo org/both requires the check `lint` in its merge queue, but no branch \
+                 protection runs it",
+            ]
+        );
+
+        assert_eq!(
+            toml::from_str::<crate::schema::Repo>(
+                "org = 'org'\nname = 'r'\ndescription = ''\nbots = []\n\
+                 auto-merge = 'merge-queue'\n[access.teams]\n"
+            )
+            .unwrap()
+            .auto_merge,
+            Some(AutoMerge::MergeQueue {
+                required_checks: vec![]
+            })
+        );
+        assert!(toml::from_str::<crate::schema::Repo>(
+            "org = 'org'\nname = 'r'\ndescription = ''\nbots = []\n\
+             auto-merge = 'homu'\n[access.teams]\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_codeowners_validation() {
+        let data = Data::from_toml(
+            &config("allowed-github-orgs = ['org']\n"),
+            &[&person("foo", 1, "")],
+            &[
+                &team("writers", &[], &[], "[[github]]\norgs = ['org']\n"),
+                &team("triagers", &[], &[], "[[github]]\norgs = ['org']\n"),
+            ],
+        )
+        .with_repos(&["org = 'org'\n\
+                       name = 'repo'\n\
+                       description = ''\n\
+                       bots = []\n\
+                       [access.teams]\n\
+                       writers = 'write'\n\
+                       triagers = 'triage'\n\
+                       [access.individuals]\n\
+                       foo = 'triage'\n\
+                       [codeowners]\n\
+                       '/ok/' = { teams = ['writers'] }\n\
+                       '/empty/' = {}\n\
+                       '/bad/' = { teams = ['triagers', 'missing'], people = ['foo', 'ghost'] }\n"]);
+
+        let mut errors = Findings::default();
+        validate_codeowners(&data, &mut errors);
+        assert_eq!(
+            errors.errors(),
+            [
+                "codeowners of org/repo are invalid: `/empty/` has no owners",
+                "codeowners of org/repo are invalid: `/bad/` is owned by the team 'triagers', \
+                 which doesn't have write access to the repo",
+                "codeowners of org/repo are invalid: `/bad/` is owned by 'missing', which is not \
+                 a GitHub team of the 'org' org",
+                "codeowners of org/repo are invalid: `/bad/` is owned by 'foo', who doesn't have \
+                 write access to the repo",
+                "codeowners of org/repo are invalid: `/bad/` is owned by 'ghost', which is not \
+                 the name of a person in the team repo",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_required_zulip_auth() {
+        let data = Data::from_toml(&config(""), &[], &[]);
+        let zulip = ZulipApi::unauthenticated();
+        let checks = ZULIP_CHECKS.iter().collect::<Vec<_>>();
+
+        // Without credentials the checks are skipped, unless the API is required.
+        let mut report = Report::default();
+        run_zulip_checks(&data, &zulip, &checks, false, false, &mut report).unwrap();
+        assert!(report.checks.is_empty());
+        let err = run_zulip_checks(&data, &zulip, &checks, true, false, &mut report).unwrap_err();
+        assert!(err.to_string().contains("ZULIP_TOKEN"));
+
+        assert_eq!("zulip".parse::<Api>().unwrap(), Api::Zulip);
+        assert_eq!("github".parse::<Api>().unwrap(), Api::GitHub);
+        assert!("discord".parse::<Api>().is_err());
+    }
+
+    #[test]
+    fn test_fail_fast() {
+        let data = Data::from_toml(
+            &config(""),
+            &[&person("foo", 1, "")],
+            &[&team("team", &["foo"], &["missing"], "")],
+        );
+        let only = ["validate_team_leads", "validate_team_members"];
+        let failing_checks = |report: &Report| {
+            let mut checks = report.errors.iter().map(|e| e.check).collect::<Vec<_>>();
+            checks.dedup();
+            checks
+        };
+
+        let report = run_checks(
+            &data,
+            &CheckOptions {
+                only: &only,
+                ..CheckOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(report.checks, only);
+        assert_eq!(failing_checks(&report), only);
+
+        // The checks after the first failing one are not run.
+        let report = run_checks(
+            &data,
+            &CheckOptions {
+                fail_fast: true,
+                only: &only,
+                ..CheckOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(report.checks, ["validate_team_leads"]);
+        assert_eq!(failing_checks(&report), ["validate_team_leads"]);
+    }
+
+    #[test]
+    fn test_missing_included_team() {
+        let including = |name: &str, included: &str| {
+            team(
+                name,
+                &[],
+                &[],
+                &format!("people.included-teams = ['{}']\n", included),
+            )
+        };
+        let teams = [
+            including("a", "b"),
+            including("b", "c"),
+            including("d", "a"),
+        ];
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &teams.iter().map(|t| t.as_str()).collect::<Vec<_>>(),
+        );
+
+        let missing = data
+            .team("a")
+            .unwrap()
+            .members(&data)
+            .unwrap_err()
+            .downcast::<MissingIncludedTeam>()
+            .unwrap();
+        assert_eq!(missing.path, ["a", "b", "c"]);
+
+        // Only run the checks not relying on an API.
+        let only = CHECKS.iter().map(|c| c.name).collect::<Vec<_>>();
+        let report = run_checks(
+            &data,
+            &CheckOptions {
+                only: &only,
+                ..CheckOptions::default()
+            },
+        )
+        .unwrap();
+        let errors = report
+            .errors
+            .iter()
+            .filter(|e| e.message.contains("non-existent"))
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            ["team 'b' includes members from non-existent team 'c' (through d => a => b => c)"]
+        );
+    }
+
+    #[test]
+    fn test_rfcbot_excluded_leads() {
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &[&team(
+                "team",
+                &["lead"],
+                &["lead", "member"],
+                "[rfcbot]\n\
+                 label = 'T-team'\n\
+                 name = 'Team'\n\
+                 ping = 'rust-lang/team'\n\
+                 exclude-members = ['lead', 'member']\n",
+            )],
+        );
+
+        let mut errors = Findings::default();
+        validate_rfcbot_exclude_members(&data, &mut errors);
+        assert_eq!(
+            errors,
+            Findings(vec![Finding::Warning(
+                "lead `lead` of team `team` is in rfcbot.exclude-members, so their votes don't \
+                 count toward the FCP quorum"
+                    .into()
+            )])
+        );
+    }
+
+    #[test]
+    fn test_rfcbot_ping_format() {
+        let with_ping = |name: &str, ping: &str| {
+            team(
+                name,
+                &[],
+                &[],
+                &format!(
+                    "[rfcbot]\nlabel = 'T-{0}'\nname = '{0}'\nping = '{1}'\n",
+                    name, ping
+                ),
+            )
+        };
+        let teams = [
+            with_ping("empty", " "),
+            with_ping("uppercase", "rust-lang/Compiler"),
+            with_ping("valid", "rust-lang/compiler"),
+        ];
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &teams.iter().map(|t| t.as_str()).collect::<Vec<_>>(),
+        );
+
+        let mut errors = Findings::default();
+        validate_rfcbot_ping_format(&data, &mut errors);
+        let mut errors = errors.errors();
+        errors.sort();
+        assert_eq!(
+            errors,
+            [
+                "the rfcbot ping of team `empty` is empty",
+                "the rfcbot ping of team `uppercase` is not lowercase: `rust-lang/Compiler`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_admin_team_size() {
+        let repo = |name: &str| {
+            format!(
+                "org = 'org'\n\
+                 name = '{}'\n\
+                 description = ''\n\
+                 bots = []\n\
+                 [access.teams]\n\
+                 solo = 'admin'\n\
+                 pair = 'admin'\n",
+                name
+            )
+        };
+        let admins = |name: &str, members: &[&str]| {
+            team(
+                name,
+                &[],
+                members,
+                "[[github]]\norgs = ['org']\nrepo-admin = true\n",
+            )
+        };
+        let teams = [admins("solo", &["foo"]), admins("pair", &["foo", "bar"])];
+        let data = Data::from_toml(
+            &config(
+                "allowed-github-orgs = ['org']\n\
+                 critical-repos = ['org/critical']\n",
+            ),
+            &[&person("foo", 1, ""), &person("bar", 2, "")],
+            &teams.iter().map(|t| t.as_str()).collect::<Vec<_>>(),
+        )
+        .with_repos(&[&repo("critical"), &repo("other")]);
+
+        let mut errors = Findings::default();
+        validate_admin_team_size(&data, &mut errors);
+        errors.0.sort();
+        assert_eq!(
+            errors,
+            Findings(vec![
+                Finding::Error(
+                    "team 'solo' has admin access to org/critical but only 1 member(s): add \
+                     another member or downgrade the permission"
+                        .into()
+                ),
+                Finding::Warning(
+                    "team 'solo' has admin access to org/other but only 1 member(s): add \
+                     another member or downgrade the permission"
+                        .into()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_repo_has_admin() {
+        let repo = |name: &str| {
+            format!(
+                "org = 'org'\n\
+                 name = '{}'\n\
+                 description = ''\n\
+                 bots = []\n\
+                 [access.teams]\n\
+                 foo = 'write'\n",
+                name
+            )
+        };
+        let data = Data::from_toml(
+            &config(
+                "allowed-github-orgs = ['org']\n\
+                 critical-repos = ['org/critical']\n\
+                 repos-without-admin = ['org/exempted']\n",
+            ),
+            &[],
+            &[],
+        )
+        .with_repos(&[&repo("critical"), &repo("other"), &repo("exempted")]);
+
+        let mut errors = Findings::default();
+        validate_repo_has_admin(&data, &mut errors);
+        errors.0.sort();
+        assert_eq!(
+            errors,
+            Findings(vec![
+                Finding::Error(
+                    "repo org/critical doesn't grant admin access to any team or person".into()
+                ),
+                Finding::Warning(
+                    "repo org/other doesn't grant admin access to any team or person".into()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_extra_team_email_coverage() {
+        let maybe_email = |github: &str, id: usize, email: bool| {
+            let email = if email {
+                format!("email = '{}@example.com'\n", github)
+            } else {
+                String::new()
+            };
+            person(github, id, &email)
+        };
+        let people = [
+            maybe_email("a", 1, true),
+            maybe_email("b", 2, true),
+            maybe_email("c", 3, true),
+            maybe_email("d", 4, false),
+            maybe_email("e", 5, false),
+        ];
+        let data = Data::from_toml(
+            &config("allowed-mailing-lists-domains = ['example.com']\n"),
+            &people.iter().map(|p| p.as_str()).collect::<Vec<_>>(),
+            &[
+                // A quarter of the members lack an email: exactly the default threshold.
+                &team("quarter", &[], &["a", "b", "c", "d"], ""),
+                // A third of the members lack an email: above the threshold.
+                &team("third", &[], &["a", "b", "e"], ""),
+                &team(
+                    "host",
+                    &[],
+                    &[],
+                    "[[lists]]\n\
+                     address = 'host@example.com'\n\
+                     extra-teams = ['quarter', 'third']\n",
+                ),
+            ],
+        );
+
+        let mut errors = Findings::default();
+        validate_extra_team_email_coverage(&data, &mut errors);
+        assert_eq!(
+            errors.errors(),
+            [
+                "list `host@example.com` includes team `third`, but 1 of its 3 members don't have \
+              an email address: e"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_branch_protection_ci_checks() {
+        let response = |body: &str| {
+            format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        };
+        let data = Data::from_toml(&config("allowed-github-orgs = ['org']\n"), &[], &[])
+            .with_repos(&["org = 'org'\n\
+                           name = 'repo'\n\
+                           description = ''\n\
+                           bots = []\n\
+                           [access.teams]\n\
+                           [[branch-protections]]\n\
+                           pattern = 'main'\n\
+                           ci-checks = ['CI', 'Lint']\n"]);
+        let check = |check_runs: &str, statuses: &str, strict: bool| {
+            let (url, server) = serve(vec![
+                response(r#"{"description": null, "default_branch": "main"}"#),
+                response(check_runs),
+                response(statuses),
+            ]);
+            let mut errors = Findings::default();
+            validate_branch_protection_ci_checks(
+                &data,
+                &GitHubApi::mocked(url),
+                strict,
+                &mut errors,
+            );
+            server.join().unwrap();
+            errors
+        };
+
+        let missing = "the branch protection `main` of org/repo requires the CI check `CI`, \
+                       which was never reported on `main`";
+        let runs = r#"{"check_runs": [{"name": "Build"}]}"#;
+        let statuses = r#"{"statuses": [{"context": "Lint"}]}"#;
+        assert_eq!(
+            check(runs, statuses, false),
+            Findings(vec![Finding::Warning(missing.into())])
+        );
+        assert_eq!(
+            check(runs, statuses, true),
+            Findings(vec![Finding::Error(missing.into())])
+        );
+        // Repos without any CI run reported yet are skipped.
+        assert_eq!(
+            check(r#"{"check_runs": []}"#, r#"{"statuses": []}"#, true),
+            Findings::default()
+        );
+    }
+
+    #[test]
+    fn test_outside_collaborators() {
+        let response = |body: &str| {
+            format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        };
+        let data = Data::from_toml(&config("allowed-github-orgs = ['org']\n"), &[], &[])
+            .with_repos(&["org = 'org'\n\
+                           name = 'repo'\n\
+                           description = ''\n\
+                           bots = []\n\
+                           [access.teams]\n\
+                           [access.individuals]\n\
+                           known = 'write'\n"]);
+        let (url, server) = serve(vec![
+            response(
+                r#"[
+                    {"login": "Known", "permissions": {"triage": true, "push": true, "maintain": false, "admin": false}},
+                    {"login": "writer", "permissions": {"triage": true, "push": true, "maintain": false, "admin": false}},
+                    {"login": "reader", "permissions": {"triage": false, "push": false, "maintain": false, "admin": false}}
+                ]"#,
+            ),
+            response("[]"),
+        ]);
+        let mut errors = Findings::default();
+        validate_outside_collaborators(&data, &GitHubApi::mocked(url), false, &mut errors);
+        server.join().unwrap();
+
+        let error = |name: &str| {
+            Finding::Error(format!(
+                "`{}` is an outside collaborator of repo org/repo on GitHub, but isn't in its \
+                 `access.individuals`",
+                name
+            ))
+        };
+        assert_eq!(
+            errors,
+            Findings(vec![
+                error("writer"),
+                error("reader"),
+                // Read access can't be granted through `access.individuals`.
+                Finding::Stub(
+                    "# repos/org/repo.toml\n\
+                     [access.individuals]\n\
+                     writer = \"write\"\n\
+                     # reader only has read access, remove it on GitHub or grant another permission"
+                        .into()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_list_only_members() {
+        let data = Data::from_toml(
+            &config(""),
+            &[
+                &person("lead", 1, "email = 'lead@example.com'\n"),
+                &person("director", 2, "email = 'director@example.com'\n"),
+                &person("promoted", 3, "email = 'promoted@example.com'\n"),
+                &person("offline", 4, ""),
+            ],
+            &[
+                &team(
+                    "council",
+                    &["lead"],
+                    &["lead", "promoted"],
+                    "list-only-members = ['director', 'promoted']\n\
+                     [[zulip-groups]]\n\
+                     name = 'council'\n\
+                     [[lists]]\n\
+                     address = 'agenda@example.com'\n",
+                ),
+                &team(
+                    "broken",
+                    &[],
+                    &["lead"],
+                    "list-only-members = ['offline', 'missing']\n\
+                     [[lists]]\n\
+                     address = 'broken@example.com'\n",
+                ),
+            ],
+        );
+        let team = data.team("council").unwrap();
+
+        // List-only members are only on the lists.
+        let mut emails = team.lists(&data).unwrap()[0].emails().to_vec();
+        emails.sort();
+        assert_eq!(
+            emails,
+            [
+                "director@example.com",
+                "lead@example.com",
+                "promoted@example.com"
+            ]
+        );
+        assert!(!team.members(&data).unwrap().contains("director"));
+        let zulip_members = team.zulip_groups(&data).unwrap()[0].members().len();
+        assert_eq!(zulip_members, 2);
+
+        let mut errors = Findings::default();
+        validate_list_only_members(&data, &mut errors);
+        errors.0.sort();
+        assert_eq!(
+            errors,
+            Findings(vec![
+                Finding::Error(
+                    "person `missing` does not exist (in the list-only-members of team `broken`)"
+                        .into()
+                ),
+                Finding::Error(
+                    "person `offline` is a list-only member of team `broken` but has no email \
+                     address"
+                        .into()
+                ),
+                Finding::Warning(
+                    "person `promoted` is a member of team `council`, so their list-only-members \
+                     entry is redundant"
+                        .into()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_orphaned_permissions() {
+        let data = Data::from_toml(
+            &config(
+                "permissions-bors-repos = ['rust']\n\
+                 permissions-bools = ['perf', 'crater']\n",
+            ),
+            &[
+                &person("foo", 1, "[permissions]\nperf = true\n"),
+                &person("bar", 2, ""),
+            ],
+            &[&team(
+                "team",
+                &["bar"],
+                &["bar"],
+                "[permissions]\n\
+                 crater = false\n\
+                 [leads-permissions]\n\
+                 bors.rust.review = true\n",
+            )],
+        );
+
+        let mut errors = Findings::default();
+        validate_orphaned_permissions(&data, &mut errors);
+        // The reviewers of a repo can also start try builds.
+        assert_eq!(
+            errors,
+            Findings(vec![Finding::Warning(
+                "permission `crater` is defined in config.toml, but nobody has it".into()
+            )])
+        );
+    }
+
+    #[test]
+    fn test_draft_teams() {
+        let data = Data::from_toml(
+            &config("draft-teams-max-age-days = 30\n"),
+            &[&person("foo", 1, "")],
+            &[
+                &team(
+                    "old",
+                    &[],
+                    &["foo", "missing"],
+                    "draft = true\ncreated-at = 2020-01-01\n",
+                ),
+                &team("undated", &[], &["foo"], "draft = true\n"),
+                &team("child", &[], &["foo"], "subteam-of = 'old'\n"),
+            ],
+        );
+        assert_eq!(data.teams().count(), 1);
+        assert!(data.team("old").is_none());
+
+        // The members of draft teams are still validated.
+        let mut errors = Findings::default();
+        validate_team_members(&data, &mut errors);
+        assert_eq!(
+            errors.errors(),
+            ["person `missing` is member of team `old` but doesn't exist"]
+        );
+
+        // Active teams can't be subteams of draft teams.
+        let mut errors = Findings::default();
+        validate_subteam_of(&data, &mut errors);
+        assert_eq!(
+            errors.errors(),
+            ["the parent of team `child` is a draft team: `old`"]
+        );
+
+        let mut errors = Findings::default();
+        validate_draft_teams(&data, &mut errors);
+        errors.0.sort();
+        let age = (chrono::Local::now().date_naive()
+            - chrono::NaiveDate::from_ymd_opt(2020, 1, 1).unwrap())
+        .num_days();
+        assert_eq!(
+            errors,
+            Findings(vec![
+                Finding::Error(
+                    "draft team `undated` has no `created-at` date, so its age can't be tracked"
+                        .into()
+                ),
+                Finding::Warning(format!(
+                    "team `old` has been a draft for {} days (more than 30): promote it by \
+                     removing `draft = true`, or remove it",
+                    age
+                )),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_zulip_streams_unique() {
+        let with_stream = |name: &str, stream: &str| {
+            team(
+                name,
+                &[],
+                &[],
+                &format!(
+                    "[website]\nname = '{}'\ndescription = ''\n{}\n",
+                    name, stream
+                ),
+            )
+        };
+        let teams = [
+            with_stream("a", "zulip-stream = 't-shared'"),
+            with_stream("b", "zulip-stream = 't-shared'"),
+            with_stream("c", "zulip-stream = 't-c'"),
+            with_stream("d", "zulip-stream-id = 42"),
+            with_stream("e", "zulip-stream-id = 42\nzulip-stream = 't-e'"),
+        ];
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &teams.iter().map(|t| t.as_str()).collect::<Vec<_>>(),
+        );
+
+        let mut errors = Findings::default();
+        validate_zulip_streams_unique(&data, &mut errors);
+        assert_eq!(
+            errors,
+            Findings(vec![
+                Finding::Warning("teams `a`, `b` share the Zulip stream `t-shared`".into()),
+                Finding::Warning("teams `d`, `e` share the Zulip stream with ID 42".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_missing_zulip_stream() {
+        let data = Data::from_toml(&config(""), &[], &[&team("team", &[], &[], "")]);
+        let team = data.team("team").unwrap();
+        let streams = vec!["t-compiler", "t-lang", "general"]
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let check = |stream| {
+            let mut errors = Findings::default();
+            missing_zulip_stream(team, stream, &streams, &mut errors);
+            errors
+        };
+
+        assert_eq!(check("t-lang"), Findings::default());
+        assert_eq!(
+            check("t-lnag"),
+            Findings(vec![Finding::Error(
+                "the Zulip stream `t-lnag` of team `team` doesn't exist (did you mean `t-lang`?)"
+                    .into()
+            )])
+        );
+        assert!(matches!(
+            check("secret-private-stream").0.as_slice(),
+            [Finding::Warning(_)]
+        ));
+    }
+
+    #[test]
+    fn test_list_email() {
+        let data = Data::from_toml(
+            &config(""),
+            &[
+                &person(
+                    "foo",
+                    1,
+                    "email = 'foo@example.com'\nlist-email = 'foo-lists@example.com'\n",
+                ),
+                &person("bar", 2, "list-email = 'bar@example.com'\n"),
+            ],
+            &[&team(
+                "team",
+                &[],
+                &["foo", "bar"],
+                "[[lists]]\naddress = 'team@example.com'\n",
+            )],
+        );
+
+        // The lists prefer the `list-email`.
+        let lists = data.team("team").unwrap().lists(&data).unwrap();
+        let mut emails = lists[0].emails().to_vec();
+        emails.sort();
+        assert_eq!(emails, ["bar@example.com", "foo-lists@example.com"]);
+
+        // A public address is still required.
+        let mut errors = Findings::default();
+        validate_list_email_addresses(&data, &mut errors);
+        let errors = errors.errors();
+        assert_eq!(
+            errors,
+            [
+                "person `bar` is a member of a mailing list and has a `list-email`, but has no \
+              public `email` address"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alumni_permissions() {
+        let errors = |exempted: &str| {
+            let data = Data::from_toml(
+                &config(&format!("alumni-with-permissions = [{}]\n", exempted)),
+                &[
+                    &person("foo", 1, "[permissions]\nbors.rust.review = true\n"),
+                    &person("bar", 2, ""),
+                ],
+                &[&team("alumni", &[], &["foo", "bar"], "")],
+            );
+            let mut errors = Findings::default();
+            validate_alumni_permissions(&data, &mut errors);
+            errors.errors()
+        };
+
+        assert_eq!(
+            errors(""),
+            ["alumni member 'foo' still has permissions in people/foo.toml"]
+        );
+        assert!(errors("'foo'").is_empty());
+    }
+
+    #[test]
+    fn test_zulip_reserved_names() {
+        let errors = |group: &str| {
+            let extra = format!("[[zulip-groups]]\nname = '{}'\n", group);
+            let data = Data::from_toml(&config(""), &[], &[&team("team", &[], &[], &extra)]);
+            let mut errors = Findings::default();
+            validate_zulip_reserved_names(&data, &mut errors);
+            errors.errors()
+        };
+
+        assert!(errors("T-lang").is_empty());
+        for name in [
+            "role:administrators",
+            "Role:Nobody",
+            "role:custom",
+            "@T-lang",
+            "channel:t-lang",
+            "T-lang, T-compiler",
+            " T-lang",
+            &"a".repeat(101),
+        ] {
+            assert_eq!(errors(name).len(), 1, "{:?} was not rejected", name);
+        }
+    }
+
+    #[test]
+    fn test_matrix_room_name() {
+        let with_room = |name: &str, room: &str| {
+            team(
+                name,
+                &[],
+                &[],
+                &format!(
+                    "[website]\nname = 'Team'\ndescription = 'A team'\nmatrix-room = '{}'\n",
+                    room
+                ),
+            )
+        };
+        let teams = [
+            with_room("handle", "#t-handle:matrix.org"),
+            with_room("link", "https://matrix.to/#/#t-link:matrix.org"),
+            with_room("no-server", "#t-no-server"),
+        ];
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &teams.iter().map(|t| t.as_str()).collect::<Vec<_>>(),
+        );
+
+        let mut errors = Findings::default();
+        validate_matrix_room_name(&data, &mut errors);
+        let mut errors = errors.errors();
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "the matrix room `#t-no-server` of the team `no-server` is not a `#room:server` \
+                 handle",
+                "the matrix room of the team `link` is a link: only the `#room:server` handle is \
+                 required",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_level_teams() {
+        let teams = [
+            team("parent", &[], &[], "top-level = true\n"),
+            team("implicit", &[], &[], ""),
+            team(
+                "child",
+                &[],
+                &[],
+                "subteam-of = 'parent'\ntop-level = false\n",
+            ),
+            team("orphan", &[], &[], "top-level = false\n"),
+            team(
+                "confused",
+                &[],
+                &[],
+                "subteam-of = 'parent'\ntop-level = true\n",
+            ),
+        ];
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &teams.iter().map(|t| t.as_str()).collect::<Vec<_>>(),
+        );
+        assert!(data.team("implicit").unwrap().top_level());
+        assert!(!data.team("child").unwrap().top_level());
+
+        let mut errors = Findings::default();
+        validate_top_level_teams(&data, &mut errors);
+        let mut errors = errors.errors();
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "team `confused` is top-level, but is a subteam of `parent`",
+                "team `orphan` is not top-level, but has no `subteam-of`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_canonical_name_mismatches() {
+        let data = Data::from_toml(
+            &config(""),
+            &[],
+            &[&team(
+                "foo",
+                &[],
+                &[],
+                "use-canonical-naming = true\n\
+                 [[github]]\n\
+                 orgs = ['org']\n\
+                 [[github]]\n\
+                 orgs = ['org']\n\
+                 team-name = 'foo-team'\n\
+                 [[zulip-groups]]\n\
+                 name = 'T-foo'\n\
+                 [[zulip-groups]]\n\
+                 name = 'T-foo/meetings'\n\
+                 [[discord-roles]]\n\
+                 name = 'foo'\n",
+            )],
+        );
+        assert_eq!(
+            canonical_name_mismatches(data.team("foo").unwrap()),
+            vec![
+                ("GitHub team", "foo-team", "foo".to_string()),
+                ("Zulip group", "T-foo/meetings", "T-foo".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_extra_team_leads() {
+        let data = Data::from_toml(
+            &config(""),
+            &[
+                &person("lead", 1, "email = 'lead@example.com'\n"),
+                &person("member", 2, "email = 'member@example.com'\n"),
+                &person("silent", 3, ""),
+            ],
+            &[
+                &team(
+                    "leads",
+                    &["lead"],
+                    &["lead", "member"],
+                    "[[lists]]\n\
+                     address = 'leads@example.com'\n\
+                     extra-team-leads = ['sub', 'other']\n",
+                ),
+                &team("sub", &["lead"], &["lead", "member"], ""),
+                &team("other", &["silent"], &["silent"], ""),
+            ],
+        );
+
+        // `lead` is both a member of the team and a lead of `sub`, but is only subscribed once.
+        let lists = data.team("leads").unwrap().lists(&data).unwrap();
+        let mut emails = lists[0].emails().to_vec();
+        emails.sort();
+        assert_eq!(emails, vec!["lead@example.com", "member@example.com"]);
+
+        let mut errors = Findings::default();
+        validate_list_email_addresses(&data, &mut errors);
+        let errors = errors.errors();
+        assert_eq!(
+            errors,
+            vec!["person `silent` is a member of a mailing list but has no email address"]
+        );
+    }
+
+    #[test]
+    fn test_every_check_is_explained() {
+        for (name, explanation) in explanations() {
+            assert!(
+                !explanation.summary.trim().is_empty(),
+                "{} has no summary",
+                name
+            );
+            assert!(!explanation.steps.is_empty(), "{} has no steps", name);
+            for step in explanation.steps {
+                assert!(!step.trim().is_empty(), "{} has an empty step", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let mut report = Report::default();
+        report.record("validate_foo", Findings::default());
+        report.record(
+            "validate_bar",
+            Findings(vec![
+                Finding::Error("second <error>".into()),
+                Finding::Error("first error".into()),
+            ]),
+        );
+        report.record("validate_baz", Findings::default());
+
+        let xml = report.to_junit();
+        assert!(xml.contains("<testsuite name=\"validate\" tests=\"3\" failures=\"1\">"));
+        assert_eq!(xml.matches("<testcase ").count(), 3);
+        assert_eq!(xml.matches("<failure ").count(), 2);
+
+        let bar = xml.find("name=\"validate_bar\">").unwrap();
+        let bar_end = xml[bar..].find("</testcase>").unwrap() + bar;
+        let first = xml.find("<failure message=\"first error\"/>").unwrap();
+        let second = xml
+            .find("<failure message=\"second &lt;error&gt;\"/>")
+            .unwrap();
+        assert!(bar < first && first < second && second < bar_end);
+        assert!(xml.contains("<testcase classname=\"validate\" name=\"validate_foo\"/>"));
+        assert!(xml.contains("<testcase classname=\"validate\" name=\"validate_baz\"/>"));
+    }
+
+    #[test]
+    fn test_discord_color_contrast() {
+        let background = (0x31, 0x33, 0x38);
+        assert_eq!(parse_hex_color("#31333a"), Some((0x31, 0x33, 0x3a)));
+        assert_eq!(parse_hex_color("#ggg000"), None);
+        assert_eq!(parse_hex_color("313338"), None);
+
+        assert!(contrast_ratio((0x31, 0x33, 0x3a), background) < 1.1);
+        assert!(contrast_ratio((0xff, 0xff, 0xff), background) > 10.0);
+        assert!((relative_luminance((0xff, 0xff, 0xff)) - 1.0).abs() < f64::EPSILON);
     }
 }
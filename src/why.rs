@@ -0,0 +1,197 @@
+//! Explain why a person is a member of a team, or has a permission.
+//!
+//! Membership can come from many places: the team's own members, included teams (possibly
+//! through a chain of them), the leads of other teams or the alumni. Each explanation is a chain
+//! of steps, from the team or permission asked about down to the entry listing the person.
+
+use crate::data::Data;
+use crate::schema::Permissions;
+use failure::{bail, Error};
+
+/// Why a person is related to a team.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct WhyMember {
+    /// Every chain of steps making the person a member of the team.
+    pub(crate) membership: Vec<Vec<String>>,
+    /// The other ways the person receives the team's mails or is in its Zulip groups, without
+    /// being a member of it.
+    pub(crate) extra: Vec<String>,
+}
+
+/// Explain why the person is a member of the team.
+pub(crate) fn why_member(data: &Data, person: &str, team: &str) -> Result<WhyMember, Error> {
+    let team = match data.team(team) {
+        Some(team) => team,
+        None => bail!("unknown team: {}", team),
+    };
+    let mut why = WhyMember {
+        membership: team.membership_provenance(data, person)?,
+        extra: Vec::new(),
+    };
+
+    if team.list_only_members().iter().any(|p| p == person) {
+        why.extra.push(format!(
+            "`{}` is a list-only member of team `{}`",
+            person,
+            team.name()
+        ));
+    }
+    for list in team.raw_lists() {
+        if list.extra_people.iter().any(|p| p == person) {
+            why.extra.push(format!(
+                "`{}` is in the `extra-people` of list `{}`",
+                person, list.address
+            ));
+        }
+    }
+    for group in team.raw_zulip_groups() {
+        if group.extra_people.iter().any(|p| p == person) {
+            why.extra.push(format!(
+                "`{}` is in the `extra-people` of Zulip group `{}`",
+                person, group.name
+            ));
+        }
+    }
+    Ok(why)
+}
+
+/// Explain why the person has the permission: every chain of steps granting it to them.
+pub(crate) fn why_permission(
+    data: &Data,
+    person: &str,
+    permission: &str,
+) -> Result<Vec<Vec<String>>, Error> {
+    if !Permissions::available(data.config())
+        .iter()
+        .any(|p| p == permission)
+    {
+        bail!("unknown permission: {}", permission);
+    }
+    let person = match data.person(person) {
+        Some(person) => person,
+        None => bail!("unknown person: {}", person),
+    };
+
+    let mut chains = Vec::new();
+    if person.permissions().has(permission) {
+        chains.push(vec![format!(
+            "`{}` is granted `{}` directly in their file",
+            person.github(),
+            permission
+        )]);
+    }
+    let mut teams = data.teams().collect::<Vec<_>>();
+    teams.sort_by_key(|team| team.name());
+    for team in teams {
+        if team.permissions().has(permission) {
+            for chain in team.membership_provenance(data, person.github())? {
+                let mut steps = vec![format!(
+                    "team `{}` grants `{}` to its members",
+                    team.name(),
+                    permission
+                )];
+                steps.extend(chain);
+                chains.push(steps);
+            }
+        }
+        if team.leads_permissions().has(permission) && team.leads().contains(person.github()) {
+            chains.push(vec![
+                format!(
+                    "team `{}` grants `{}` to its leads",
+                    team.name(),
+                    permission
+                ),
+                format!("`{}` leads team `{}`", person.github(), team.name()),
+            ]);
+        }
+    }
+    Ok(chains)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::{config, person, team};
+    use crate::permissions::allowed_people;
+
+    #[test]
+    fn test_why() {
+        let data = Data::from_toml(
+            &config("permissions-bools = ['crater', 'perf']\n"),
+            &[
+                &person("foo", 1, "[permissions]\nperf = true\n"),
+                &person("bar", 2, ""),
+            ],
+            &[
+                &team(
+                    "a",
+                    &[],
+                    &[],
+                    "people.included-teams = ['b']\n\
+                     [permissions]\n\
+                     crater = true\n\
+                     [[lists]]\n\
+                     address = 'a@example.com'\n\
+                     extra-people = ['bar']\n",
+                ),
+                &team(
+                    "b",
+                    &["foo"],
+                    &["foo"],
+                    "[leads-permissions]\nperf = true\n",
+                ),
+            ],
+        );
+
+        assert_eq!(
+            why_member(&data, "foo", "a").unwrap(),
+            WhyMember {
+                membership: vec![vec![
+                    "team `a` includes the members of team `b`".into(),
+                    "`foo` is listed in the members of team `b`".into(),
+                ]],
+                extra: vec![],
+            }
+        );
+        assert_eq!(
+            why_member(&data, "bar", "a").unwrap(),
+            WhyMember {
+                membership: vec![],
+                extra: vec!["`bar` is in the `extra-people` of list `a@example.com`".into()],
+            }
+        );
+
+        assert_eq!(
+            why_permission(&data, "foo", "crater").unwrap(),
+            [[
+                "team `a` grants `crater` to its members",
+                "team `a` includes the members of team `b`",
+                "`foo` is listed in the members of team `b`",
+            ]]
+        );
+        assert_eq!(
+            why_permission(&data, "foo", "perf").unwrap(),
+            [
+                vec!["`foo` is granted `perf` directly in their file"],
+                vec![
+                    "team `b` grants `perf` to its leads",
+                    "`foo` leads team `b`"
+                ],
+            ]
+        );
+        assert!(why_permission(&data, "bar", "crater").unwrap().is_empty());
+        assert!(why_permission(&data, "foo", "missing").is_err());
+
+        // The explanations match the resolved permissions.
+        for permission in ["crater", "perf"] {
+            let allowed = allowed_people(&data, permission).unwrap();
+            for person in data.people() {
+                let explained = !why_permission(&data, person.github(), permission)
+                    .unwrap()
+                    .is_empty();
+                let has = allowed.iter().any(|p| p.github() == person.github());
+                assert_eq!(explained, has);
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
+use crate::cache::DiskCache;
 use failure::{bail, Error};
 use reqwest::blocking::{Client, ClientBuilder, Response};
 use reqwest::Method;
@@ -8,32 +10,49 @@ use serde::Deserialize;
 const ZULIP_BASE_URL: &str = "https://rust-lang.zulipchat.com/api/v1";
 static TOKEN_VAR: &str = "ZULIP_TOKEN";
 static USER_VAR: &str = "ZULIP_USER";
+/// How long the responses stored in the on-disk cache are trusted before fetching them again.
+const CACHE_TTL: Duration = Duration::from_secs(15 * 60);
 
 /// Access to the Zulip API
 #[derive(Clone)]
 pub(crate) struct ZulipApi {
     client: Client,
+    base_url: String,
     auth: Option<(String, String)>,
+    disk_cache: Option<DiskCache>,
 }
 
 impl ZulipApi {
     /// Create a new `ZulipApi` instance
     pub(crate) fn new() -> Self {
-        let username = std::env::var(USER_VAR).ok();
-        let token = std::env::var(TOKEN_VAR).ok();
+        // CI sets the variables to empty strings when the secrets aren't configured.
+        let username = std::env::var(USER_VAR).ok().filter(|u| !u.is_empty());
+        let token = std::env::var(TOKEN_VAR).ok().filter(|t| !t.is_empty());
         let auth = match (username, token) {
             (Some(u), Some(t)) => Some((u, t)),
             _ => None,
         };
+        let credentials = auth.as_ref().map(|(u, t)| format!("{}:{}", u, t));
         Self {
             client: ClientBuilder::new()
                 .user_agent(crate::USER_AGENT)
                 .build()
                 .unwrap(),
+            base_url: ZULIP_BASE_URL.to_string(),
             auth,
+            disk_cache: DiskCache::open(credentials.as_deref()),
         }
     }
 
+    /// A `ZulipApi` without credentials, whose `require_auth` always fails.
+    #[cfg(test)]
+    pub(crate) fn unauthenticated() -> Self {
+        let mut zulip = Self::new();
+        zulip.auth = None;
+        zulip.disk_cache = None;
+        zulip
+    }
+
     pub(crate) fn require_auth(&self) -> Result<(), Error> {
         if self.auth.is_none() {
             bail!(
@@ -46,25 +65,43 @@ impl ZulipApi {
     }
 
     /// Get all users of the Rust Zulip instance
+    ///
+    /// The endpoint isn't paginated: it always returns all the users at once.
     pub(crate) fn get_users(&self) -> Result<Vec<ZulipUser>, Error> {
-        let response = self
-            .req(Method::GET, "/users", None)?
-            .error_for_status()?
-            .json::<ZulipUsers>()?
-            .members;
-
-        Ok(response)
+        Ok(self.get::<ZulipUsers>("/users")?.members)
     }
 
     /// Get all user groups of the Rust Zulip instance
     pub(crate) fn get_user_groups(&self) -> Result<Vec<ZulipUserGroup>, Error> {
-        let response = self
-            .req(Method::GET, "/user_groups", None)?
-            .error_for_status()?
-            .json::<ZulipUserGroups>()?
-            .user_groups;
+        Ok(self.get::<ZulipUserGroups>("/user_groups")?.user_groups)
+    }
 
-        Ok(response)
+    /// Get all the streams of the Rust Zulip instance visible to the bot: the public ones, and
+    /// the private ones it's subscribed to
+    pub(crate) fn get_streams(&self) -> Result<Vec<ZulipStream>, Error> {
+        Ok(self.get::<ZulipStreams>("/streams")?.streams)
+    }
+
+    /// Perform a GET request, reusing the response stored in the on-disk cache if it's recent.
+    fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        let cached = self
+            .disk_cache
+            .as_ref()
+            .and_then(|disk| disk.get::<String>("zulip", path, Some(CACHE_TTL)));
+        // Responses that can't be parsed anymore are fetched again.
+        if let Some(parsed) = cached.and_then(|body| serde_json::from_str(&body).ok()) {
+            return Ok(parsed);
+        }
+
+        let body = self
+            .req(Method::GET, path, None)?
+            .error_for_status()?
+            .text()?;
+        let parsed = serde_json::from_str(&body)?;
+        if let Some(disk) = &self.disk_cache {
+            disk.put("zulip", path, &body);
+        }
+        Ok(parsed)
     }
 
     /// Perform a request against the Zulip API
@@ -76,7 +113,7 @@ impl ZulipApi {
     ) -> Result<Response, Error> {
         let mut req = self
             .client
-            .request(method, format!("{}{}", ZULIP_BASE_URL, path));
+            .request(method, format!("{}{}", self.base_url, path));
 
         if let Some((username, token)) = &self.auth {
             req = req.basic_auth(username, Some(token))
@@ -116,3 +153,53 @@ pub(crate) struct ZulipUserGroup {
     pub(crate) members: Vec<usize>,
     pub(crate) is_system_group: bool,
 }
+
+/// A collection of Zulip streams
+#[derive(Deserialize)]
+struct ZulipStreams {
+    streams: Vec<ZulipStream>,
+}
+
+/// A single Zulip stream
+#[derive(Deserialize)]
+pub(crate) struct ZulipStream {
+    pub(crate) stream_id: u64,
+    pub(crate) name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::tests::serve;
+
+    #[test]
+    fn test_get_users() -> Result<(), Error> {
+        let body = serde_json::json!({
+            "members": [
+                { "user_id": 1, "full_name": "User 1" },
+                { "user_id": 2, "full_name": "User 2" },
+            ]
+        })
+        .to_string();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let (url, server) = serve(vec![response]);
+
+        let mut zulip = ZulipApi::new();
+        zulip.disk_cache = None;
+        zulip.base_url = url.trim_end_matches('/').to_string();
+        let users = zulip.get_users()?;
+        assert_eq!(
+            users.iter().map(|user| user.user_id).collect::<Vec<_>>(),
+            [1, 2]
+        );
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("/users "));
+        Ok(())
+    }
+}
@@ -4,9 +4,11 @@ set -euo pipefail
 IFS=$'\n\t'
 
 cd "$(dirname "$0")"
-if [[ -d static-api/_output ]]; then
-    rm -rf static-api/_expected
-    cp -r static-api/_output static-api/_expected
-else
-    echo "didn't bless static-api as there is no output to bless"
-fi
+for dir in static-api issues; do
+    if [[ -d "${dir}/_output" ]]; then
+        rm -rf "${dir}/_expected"
+        cp -r "${dir}/_output" "${dir}/_expected"
+    else
+        echo "didn't bless ${dir} as there is no output to bless"
+    fi
+done
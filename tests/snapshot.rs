@@ -1,7 +1,10 @@
 use duct::{cmd, Expression};
 use failure::Error;
+use flate2::read::GzDecoder;
+use rust_team_data::v1;
 use std::{
-    collections::HashSet,
+    collections::{BTreeSet, HashSet},
+    io::Read,
     path::{Path, PathBuf},
 };
 
@@ -24,11 +27,173 @@ fn static_api() -> Result<(), Error> {
         .dir(dir_valid())
         .assert_success()?;
 
+    step("checking whether the per-person files match teams.json");
+    assert_people_match_teams(&dir_output)?;
+
+    step("checking whether all.json matches teams.json");
+    assert_all_matches_teams(&dir_output)?;
+
+    step("checking whether the draft teams were left out");
+    let teams: v1::Teams =
+        serde_json::from_slice(&std::fs::read(dir_output.join("v1").join("teams.json"))?)?;
+    assert!(!teams.teams.contains_key("draft"));
+
     step("checking whether the output matched the expected one");
+    assert_same_contents(&dir_output, &dir_expected)
+}
 
+#[test]
+fn export_issues() -> Result<(), Error> {
+    let dir_output = dir_issues().join("_output");
+    let dir_expected = dir_issues().join("_expected");
+
+    if dir_output.exists() {
+        std::fs::remove_dir_all(&dir_output)?;
+    }
+
+    step("exporting the issue drafts");
+    cmd!(
+        bin(),
+        "export",
+        "issues",
+        "--check",
+        "validate_discord_team_members_have_discord_ids",
+        "--dest",
+        &dir_output
+    )
+    .dir(dir_issues())
+    .assert_success()?;
+
+    step("checking whether the output matched the expected one");
+    assert_same_contents(&dir_output, &dir_expected)
+}
+
+#[test]
+fn data_dir() -> Result<(), Error> {
+    let temp = temp_dir("data-dir")?;
+    let data = temp.join("data");
+    let dir_output = temp.join("output");
+    copy_fixture(&dir_valid(), &data)?;
+
+    step("generating the static api out of a copy of the data");
+    cmd!(
+        bin(),
+        "--data-dir",
+        &data,
+        "static-api",
+        &dir_output,
+        "--gzip"
+    )
+    .dir(&temp)
+    .assert_success()?;
+
+    step("checking whether all.json.gz matches all.json");
+    let gzip = dir_output.join("v1").join("all.json.gz");
+    let mut decompressed = String::new();
+    GzDecoder::new(std::fs::File::open(&gzip)?).read_to_string(&mut decompressed)?;
+    assert_eq!(
+        decompressed,
+        std::fs::read_to_string(dir_output.join("v1").join("all.json"))?
+    );
+    std::fs::remove_file(&gzip)?;
+
+    step("checking whether the output matched the expected one");
+    assert_same_contents(&dir_output, &dir_valid().join("_expected"))?;
+
+    std::fs::remove_dir_all(&temp)?;
+    Ok(())
+}
+
+#[test]
+fn data_dir_overlay() -> Result<(), Error> {
+    let temp = temp_dir("overlay")?;
+    let overlay = temp.join("overlay");
+    let dir_output = temp.join("output");
+    std::fs::create_dir_all(overlay.join("teams"))?;
+    std::fs::write(
+        overlay.join("teams").join("wg-empty.toml"),
+        "name = \"wg-empty\"\n\
+         kind = \"working-group\"\n\
+         \n\
+         [people]\n\
+         leads = [\"user-1\"]\n\
+         members = [\"user-1\"]\n",
+    )?;
+
+    step("generating the static api with an overlay");
+    cmd!(
+        bin(),
+        "--data-dir",
+        dir_valid(),
+        "--overlay",
+        &overlay,
+        "static-api",
+        &dir_output
+    )
+    .dir(&temp)
+    .assert_success()?;
+
+    step("checking whether the overlay replaced the team");
+    let team: v1::Team = serde_json::from_slice(&std::fs::read(
+        dir_output.join("v1").join("teams").join("wg-empty.json"),
+    )?)?;
+    let members = team
+        .members
+        .iter()
+        .map(|m| m.github.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(members, ["user-1"]);
+    assert!(dir_output
+        .join("v1")
+        .join("teams")
+        .join("foo.json")
+        .is_file());
+
+    std::fs::remove_dir_all(&temp)?;
+    Ok(())
+}
+
+fn assert_people_match_teams(dir_output: &Path) -> Result<(), Error> {
+    let v1 = dir_output.join("v1");
+    let teams: v1::Teams = serde_json::from_slice(&std::fs::read(v1.join("teams.json"))?)?;
+    let people: v1::People = serde_json::from_slice(&std::fs::read(v1.join("people.json"))?)?;
+
+    let mut from_teams = BTreeSet::new();
+    for team in teams.teams.values() {
+        for member in &team.members {
+            from_teams.insert((member.github.clone(), team.name.clone(), member.is_lead));
+        }
+    }
+
+    let mut from_people = BTreeSet::new();
+    for github in people.people.keys() {
+        let path = v1.join("people").join(format!("{}.json", github));
+        let details: v1::PersonDetails = serde_json::from_slice(&std::fs::read(path)?)?;
+        assert_eq!(&details.github, github);
+        for team in details.teams {
+            from_people.insert((github.clone(), team.name, team.is_lead));
+        }
+    }
+
+    assert_eq!(from_teams, from_people);
+    Ok(())
+}
+
+fn assert_all_matches_teams(dir_output: &Path) -> Result<(), Error> {
+    let v1 = dir_output.join("v1");
+    let json = std::fs::read_to_string(v1.join("all.json"))?;
+    let all: v1::All = serde_json::from_str(&json)?;
+    assert_eq!(serde_json::to_string_pretty(&all)?, json);
+
+    let teams: serde_json::Value = serde_json::from_slice(&std::fs::read(v1.join("teams.json"))?)?;
+    assert_eq!(serde_json::to_value(&all.teams)?, teams);
+    Ok(())
+}
+
+fn assert_same_contents(dir_output: &Path, dir_expected: &Path) -> Result<(), Error> {
     // Collect all the files present in either the output or expected dirs
     let mut files = HashSet::new();
-    for dir in &[&dir_output, &dir_expected] {
+    for dir in &[dir_output, dir_expected] {
         for entry in walkdir::WalkDir::new(dir) {
             let entry = entry?;
             if !entry.file_type().is_file() {
@@ -56,7 +221,7 @@ fn static_api() -> Result<(), Error> {
                 ansi_term::Color::Red.bold().paint("!!! the file"),
                 ansi_term::Color::White
                     .bold()
-                    .paint(&file.to_str().unwrap().to_string()),
+                    .paint(file.to_str().unwrap().to_string()),
                 ansi_term::Color::Red.bold().paint("does not match"),
             );
             println!("{}", changeset);
@@ -77,6 +242,33 @@ fn static_api() -> Result<(), Error> {
     Ok(())
 }
 
+/// Copy the data of a fixture to `dest`, without the expected and generated output.
+fn copy_fixture(fixture: &Path, dest: &Path) -> Result<(), Error> {
+    let walk = walkdir::WalkDir::new(fixture)
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('_'));
+    for entry in walk {
+        let entry = entry?;
+        let target = dest.join(entry.path().strip_prefix(fixture)?);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// A fresh directory to run a test in, outside of the repository.
+fn temp_dir(name: &str) -> Result<PathBuf, Error> {
+    let dir = std::env::temp_dir().join(format!("rust-team-{}-{}", name, std::process::id()));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
 fn bin() -> &'static str {
     env!("CARGO_BIN_EXE_rust-team")
 }
@@ -87,12 +279,18 @@ fn dir_valid() -> PathBuf {
         .join("static-api")
 }
 
+fn dir_issues() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("issues")
+}
+
 fn step(name: &str) {
     println!(
         "{}",
         ansi_term::Color::White
             .bold()
-            .paint(&format!("==> {}", name))
+            .paint(format!("==> {}", name))
     );
 }
 